@@ -1,57 +1,245 @@
 // src/Link/mod.rs
-use colored::Colorize;
-use std::fs::{self, OpenOptions};
-use std::io::Write;
+// Pila de contextos por identidad: ~/.this/me/<alias>/links.json
+//
+//     link a → link b → link c     entries = [a, b, c], cursor en c
+//     back → back                  cursor en a (current = a)
+//     forward                      cursor en b
+//     link d                       entries = [a, b, d]: como un navegador, se descarta lo que había adelante
+//
+// Los bookmarks son nombres para un path (`go <name>` equivale a `link <path>`).
+// Cada operación toma un lock del archivo (compartido para leer, exclusivo para escribir),
+// así que dos procesos de la misma identidad (p. ej. `me shell` y `me link`) no se pisan.
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 
-/// Guarda la ruta actual en el historial y como contexto activo
-pub fn save_link(path: &str) -> Result<(), std::io::Error> {
-    let history_file = "/tmp/.me_history";
-    let context_file = "/tmp/.me_context";
-
-    // Guarda en el historial
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(history_file)?;
-    writeln!(file, "{}", path)?;
-
-    // Actualiza el contexto actual
-    fs::write(context_file, path)?;
-    println!("{} {}", "🔗 Linking to:".bright_cyan().bold(), path);
-    println!("{} '{}'", "✅ Context updated to".bright_green().bold(), path);
-    Ok(())
+pub use crate::core::links::{Bookmark, History, HistoryError, LinkEntry, MAX_ENTRIES};
+
+/// Historial de contextos de una identidad.
+#[derive(Debug, Clone)]
+pub struct ContextStack {
+    file: PathBuf,
 }
 
-/// Muestra el historial de links
-pub fn show_history() {
-    match fs::read_to_string("/tmp/.me_history") {
-        Ok(data) => {
-            println!("{}", "📜 Link history:".bright_white().bold());
-            for (i, line) in data.lines().enumerate() {
-                println!("{} {}", format!("{:>2}.", i + 1).bright_black(), line);
+impl ContextStack {
+    /// `~/.this/me/<alias>/links.json` (el directorio de la base del alias).
+    pub fn for_identity(alias: &str) -> Result<Self, HistoryError> {
+        let home = dirs::home_dir().ok_or(HistoryError::NoHome)?;
+        Ok(Self::at(home.join(".this").join("me").join(alias).join("links.json")))
+    }
+
+    /// Historial en un archivo arbitrario (tests, directorios temporales).
+    pub fn at(file: PathBuf) -> Self {
+        Self { file }
+    }
+
+    /// Estado completo: entradas, posición y bookmarks.
+    pub fn history(&self) -> Result<History, HistoryError> {
+        if !self.file.exists() {
+            return Ok(History::default());
+        }
+        let mut file = File::open(&self.file)?;
+        file.lock_shared()?;
+        read(&mut file)
+    }
+
+    /// Contexto activo, si hay alguno.
+    pub fn current(&self) -> Result<Option<LinkEntry>, HistoryError> {
+        Ok(self.history()?.current().cloned())
+    }
+
+    /// Pasa a `path` y lo deja como contexto activo.
+    pub fn link(&self, path: &str) -> Result<LinkEntry, HistoryError> {
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(HistoryError::EmptyPath);
+        }
+        self.update(|history| Ok(history.link(path)))
+    }
+
+    /// Vuelve al contexto anterior.
+    pub fn back(&self) -> Result<LinkEntry, HistoryError> {
+        self.update(|history| history.step(false))
+    }
+
+    /// Deshace un `back`.
+    pub fn forward(&self) -> Result<LinkEntry, HistoryError> {
+        self.update(|history| history.step(true))
+    }
+
+    /// Guarda `path` (o el contexto activo) con el nombre `name`, reemplazando uno existente.
+    pub fn bookmark(&self, name: &str, path: Option<&str>) -> Result<Bookmark, HistoryError> {
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(HistoryError::InvalidBookmark(name.to_string()));
+        }
+        self.update(|history| {
+            let path = match path.map(str::trim) {
+                Some("") => return Err(HistoryError::EmptyPath),
+                Some(path) => path.to_string(),
+                None => history.current().ok_or(HistoryError::NothingLinked)?.path.clone(),
+            };
+            let bookmark = Bookmark { path, created_at: now() };
+            history.bookmarks.insert(name.to_string(), bookmark.clone());
+            Ok(bookmark)
+        })
+    }
+
+    pub fn remove_bookmark(&self, name: &str) -> Result<Bookmark, HistoryError> {
+        self.update(|history| history.bookmarks.remove(name).ok_or_else(|| HistoryError::UnknownBookmark(name.to_string())))
+    }
+
+    /// Carga un historial traído de otra máquina (`core::bundle`). Solo escribe si el local está
+    /// vacío o ya es igual; si no, devuelve `false` y deja el local como estaba.
+    pub fn import(&self, imported: &History) -> Result<bool, HistoryError> {
+        imported.check()?;
+        self.update(|history| {
+            if *history == History::default() {
+                *history = imported.clone();
             }
+            Ok(history == imported)
+        })
+    }
+
+    /// Hace `link` al path del bookmark `name`.
+    pub fn go(&self, name: &str) -> Result<LinkEntry, HistoryError> {
+        self.update(|history| {
+            let path = history
+                .bookmarks
+                .get(name)
+                .map(|b| b.path.clone())
+                .ok_or_else(|| HistoryError::UnknownBookmark(name.to_string()))?;
+            Ok(history.link(&path))
+        })
+    }
+
+    /// Lee, modifica y reescribe el archivo bajo un lock exclusivo. Si `change` falla no se escribe nada.
+    fn update<T>(&self, change: impl FnOnce(&mut History) -> Result<T, HistoryError>) -> Result<T, HistoryError> {
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
         }
-        Err(_) => println!("📭 No link history found."),
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&self.file)?;
+        file.lock()?;
+        let mut history = read(&mut file)?;
+        let out = change(&mut history)?;
+        let json = serde_json::to_vec_pretty(&history)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        Ok(out)
     }
 }
 
-/// Regresa al último contexto previo
-pub fn go_back() {
-    let history_file = "/tmp/.me_history";
-    if let Ok(data) = fs::read_to_string(history_file) {
-        let mut lines: Vec<_> = data.lines().collect();
-        if lines.len() > 1 {
-            lines.pop();
-            if let Some(prev) = lines.last() {
-                fs::write("/tmp/.me_context", prev).unwrap_or_default();
-                fs::write(history_file, lines.join("\n")).unwrap_or_default();
-                println!("🔙 Returned to '{}'", prev);
-            }
-        } else {
-            println!("⚠️ No previous context.");
+fn read(file: &mut File) -> Result<History, HistoryError> {
+    let mut data = String::new();
+    file.read_to_string(&mut data)?;
+    if data.trim().is_empty() {
+        return Ok(History::default());
+    }
+    let history: History = serde_json::from_str(&data)?;
+    history.check()?;
+    Ok(history)
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn temp_stack(name: &str) -> (ContextStack, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("this-me-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        (ContextStack::at(dir.join("suign").join("links.json")), dir)
+    }
+
+    fn paths(stack: &ContextStack) -> Vec<String> {
+        stack.history().unwrap().entries.into_iter().map(|e| e.path).collect()
+    }
+
+    #[test]
+    fn back_and_forward_move_through_the_stack() {
+        let (stack, dir) = temp_stack("link_stack");
+        assert_eq!(stack.current().unwrap(), None);
+        assert!(matches!(stack.back(), Err(HistoryError::NothingLinked)));
+
+        for path in ["a", "b", "c"] {
+            stack.link(path).unwrap();
         }
-    } else {
-        println!("📭 No history file found.");
+        stack.link("c").unwrap();
+        assert_eq!(paths(&stack), ["a", "b", "c"]);
+        assert_eq!(stack.back().unwrap().path, "b");
+        assert_eq!(stack.back().unwrap().path, "a");
+        assert!(matches!(stack.back(), Err(HistoryError::NoPrevious)));
+        assert_eq!(stack.forward().unwrap().path, "b");
+
+        // link después de back descarta lo que había adelante
+        stack.link("d").unwrap();
+        assert_eq!(paths(&stack), ["a", "b", "d"]);
+        assert!(matches!(stack.forward(), Err(HistoryError::NoNext)));
+        assert_eq!(stack.current().unwrap().unwrap().path, "d");
+        assert!(matches!(stack.link("  "), Err(HistoryError::EmptyPath)));
+
+        std::fs::remove_dir_all(dir).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn bookmarks_name_paths() {
+        let (stack, dir) = temp_stack("link_bookmarks");
+        assert!(matches!(stack.bookmark("home", None), Err(HistoryError::NothingLinked)));
+        stack.link("wikipedia.org/page").unwrap();
+        stack.bookmark("wiki", None).unwrap();
+        stack.bookmark("work", Some("cleaker:public")).unwrap();
+        assert!(stack.bookmark("my work", Some("x")).is_err());
+
+        assert_eq!(stack.go("work").unwrap().path, "cleaker:public");
+        assert_eq!(stack.back().unwrap().path, "wikipedia.org/page");
+        assert!(matches!(stack.go("nope"), Err(HistoryError::UnknownBookmark(_))));
+        stack.remove_bookmark("wiki").unwrap();
+        let names: Vec<String> = stack.history().unwrap().bookmarks.into_keys().collect();
+        assert_eq!(names, ["work"]);
+
+        // otro handle (otro proceso) ve el mismo estado
+        let again = ContextStack::at(stack.file.clone());
+        assert_eq!(again.current().unwrap().unwrap().path, "wikipedia.org/page");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn imports_only_into_an_empty_history() {
+        let (source, dir) = temp_stack("link_import");
+        source.link("a").unwrap();
+        source.bookmark("home", None).unwrap();
+        let history = source.history().unwrap();
+
+        let target = ContextStack::at(dir.join("other").join("links.json"));
+        assert!(target.import(&history).unwrap());
+        assert_eq!(target.history().unwrap(), history);
+        assert!(target.import(&history).unwrap());
+        target.link("b").unwrap();
+        assert!(!target.import(&history).unwrap());
+        assert_eq!(target.current().unwrap().unwrap().path, "b");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_a_cursor_past_the_entries() {
+        let (stack, dir) = temp_stack("link_corrupt");
+        std::fs::create_dir_all(stack.file.parent().unwrap()).unwrap();
+        std::fs::write(&stack.file, r#"{"entries":[{"path":"a","linked_at":"x"}],"cursor":7}"#).unwrap();
+        assert!(matches!(stack.back(), Err(HistoryError::CursorOutOfRange { cursor: 7, len: 1 })));
+        assert!(matches!(stack.current(), Err(HistoryError::CursorOutOfRange { .. })));
+
+        let corrupt = History { entries: Vec::new(), cursor: Some(0), bookmarks: BTreeMap::new() };
+        let target = ContextStack::at(dir.join("other").join("links.json"));
+        assert!(matches!(target.import(&corrupt), Err(HistoryError::CursorOutOfRange { cursor: 0, len: 0 })));
+        assert_eq!(target.history().unwrap(), History::default());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1 +1,748 @@
-// me/crate/src/cli.rs
+//this.me/crate/src/cli.rs
+// Subcomandos del binario `me`. Cada identidad vive en ~/.this/me/<username>/<username>.db (SqliteStore).
+//
+//     me create --username suign
+//     me be --username suign --key name --value "Ana"
+//     me get --username suign --verb have --value json:type=eth --json
+//
+// El password sale de `--password`, de $ME_PASSWORD o de un prompt sin eco, en ese orden.
+// Con `--json` cada comando escribe una sola línea JSON en stdout (y los errores `{"error": ...}`).
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use clap::{Args, Parser, Subcommand};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::json;
+use crate::core::{Entry, GetFilter, Me, MeStore, Query, Verb, VerbSelector};
+use crate::core::bundle::{Bundle, Conflict};
+use crate::db::sqlite::SqliteStore;
+use crate::utils::validate_input::{validate_password, validate_username};
+use crate::{host, manager, qrcode, shell};
+use crate::Link::{ContextStack, History, LinkEntry};
+
+type CliResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+/// Variable de entorno con el password (scripts, CI).
+pub const PASSWORD_ENV: &str = "ME_PASSWORD";
+
+#[derive(Debug, Parser)]
+#[command(name = "me", version, about = ".me identity engine")]
+pub struct Cli {
+    /// Print one line of JSON per command instead of human output.
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create a new identity in ~/.this/me/<username>/.
+    Create(IdentityArgs),
+    /// Unlock an identity and show its public key and context.
+    Load(IdentityArgs),
+    /// List the identities on this machine.
+    List,
+    /// be: something you are.
+    Be(VerbArgs),
+    /// have: something you have.
+    Have(VerbArgs),
+    /// do: something you do.
+    Do(VerbArgs),
+    /// at: somewhere you are.
+    At(VerbArgs),
+    /// relate: something you relate to.
+    Relate(VerbArgs),
+    /// react: an emoji (value) on a target (key).
+    React(VerbArgs),
+    /// communicate: a message (value) to a target (key).
+    #[command(alias = "say")]
+    Communicate(VerbArgs),
+    /// Declare a secondary verb from the ontology (travel, work, think...).
+    Declare(DeclareArgs),
+    /// Query verb entries.
+    Get(GetArgs),
+    /// Query verb entries with the query language, e.g.
+    /// `have where key ~ "wallet%" and value.$.chain = "eth" since 2025-01 limit 20`.
+    Query(QueryArgs),
+    /// Show information about this machine.
+    Host,
+    /// List this machine's network interfaces.
+    Network,
+    /// Link to a path and make it the current context.
+    Link {
+        #[command(flatten)]
+        alias: AliasArgs,
+        path: String,
+    },
+    /// Show the link history and bookmarks.
+    History(AliasArgs),
+    /// Return to the previous context.
+    Back(AliasArgs),
+    /// Undo a `back`.
+    Forward(AliasArgs),
+    /// Name a path (the current context if none is given).
+    Bookmark {
+        #[command(flatten)]
+        alias: AliasArgs,
+        name: String,
+        path: Option<String>,
+        /// Remove the bookmark instead.
+        #[arg(long, conflicts_with = "path")]
+        delete: bool,
+    },
+    /// Link to a bookmarked path.
+    Go {
+        #[command(flatten)]
+        alias: AliasArgs,
+        name: String,
+    },
+    /// Export the identity as an encrypted QR seal.
+    Qr(QrArgs),
+    /// Restore an identity from a QR seal (PNG/JPEG image or pasted blob).
+    QrImport(QrImportArgs),
+    /// Unlock once and keep writing verbs in an interactive shell.
+    Shell(IdentityArgs),
+    /// Show the 24-word recovery phrase of the identity.
+    Backup(IdentityArgs),
+    /// Rebuild an identity from its recovery phrase under a new password.
+    Restore(RestoreArgs),
+    /// Write the identity (keys, verbs, ARC, link history) to a signed, encrypted bundle file.
+    Export(ExportArgs),
+    /// Load a bundle written by `me export`; nothing already on this machine is overwritten.
+    Import(ImportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct IdentityArgs {
+    #[arg(long, short = 'u')]
+    pub username: String,
+    /// Falls back to $ME_PASSWORD, then to a prompt.
+    #[arg(long, short = 'p')]
+    pub password: Option<String>,
+}
+
+/// Identidad sin password (comandos que no descifran nada).
+#[derive(Debug, Args)]
+pub struct AliasArgs {
+    #[arg(long, short = 'u')]
+    pub username: String,
+}
+
+#[derive(Debug, Args)]
+pub struct VerbArgs {
+    #[command(flatten)]
+    pub identity: IdentityArgs,
+    /// Context to write to (defaults to the identity's own context).
+    #[arg(long)]
+    pub context: Option<String>,
+    #[arg(long, short = 'k')]
+    pub key: String,
+    #[arg(long, short = 'v')]
+    pub value: String,
+}
+
+#[derive(Debug, Args)]
+pub struct DeclareArgs {
+    /// Secondary verb name, as defined in the ontology.
+    pub secondary: String,
+    #[command(flatten)]
+    pub verb: VerbArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct GetArgs {
+    #[command(flatten)]
+    pub identity: IdentityArgs,
+    /// A verb, a comma-separated list (`be,have`) or `all`.
+    #[arg(long, default_value = "all")]
+    pub verb: VerbSelector,
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct QueryArgs {
+    #[command(flatten)]
+    pub identity: IdentityArgs,
+    /// The query (see `core::query` for the grammar).
+    pub query: Query,
+}
+
+/// Opciones de `GetFilter` salvo el verbo (compartidas con `get` dentro de `me shell`).
+#[derive(Debug, Args)]
+pub struct FilterArgs {
+    /// Exact key, or `like:<pattern>`.
+    #[arg(long, short = 'k')]
+    pub key: Option<String>,
+    /// Exact value, `like:<pattern>` or `json:<path>=<value>`.
+    #[arg(long, short = 'v')]
+    pub value: Option<String>,
+    /// Only this context (`me get` defaults to every context in the identity's database).
+    #[arg(long)]
+    pub context: Option<String>,
+    /// Rows per verb table (default 100).
+    #[arg(long)]
+    pub limit: Option<usize>,
+    #[arg(long)]
+    pub offset: Option<usize>,
+    /// Inclusive RFC 3339 lower bound (`2025-01-01` works as a prefix).
+    #[arg(long)]
+    pub since: Option<String>,
+    /// Inclusive RFC 3339 upper bound.
+    #[arg(long)]
+    pub until: Option<String>,
+}
+
+impl FilterArgs {
+    pub fn into_filter(self, verb: VerbSelector) -> GetFilter {
+        GetFilter {
+            verb,
+            key: self.key,
+            value: self.value,
+            context_id: self.context,
+            limit: self.limit,
+            offset: self.offset,
+            since: self.since,
+            until: self.until,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct QrArgs {
+    #[command(flatten)]
+    pub identity: IdentityArgs,
+    /// Passphrase for the seal (prompted if missing).
+    #[arg(long)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct QrImportArgs {
+    /// Image with the seal (PNG or JPEG). Without it, `--blob` or a prompt.
+    pub image: Option<PathBuf>,
+    /// The hybrid blob as printed by `me qr` (`<base64 pubkey>.<envelope>`).
+    #[arg(long, conflicts_with = "image")]
+    pub blob: Option<String>,
+    /// Passphrase of the seal (prompted if missing).
+    #[arg(long)]
+    pub passphrase: Option<String>,
+    /// Skip the public key confirmation (required with --json).
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    // el password es el nuevo
+    #[command(flatten)]
+    pub identity: IdentityArgs,
+    /// The 24 words, space separated (prompted if missing).
+    #[arg(long)]
+    pub words: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub identity: IdentityArgs,
+    /// Passphrase for the bundle (prompted if missing).
+    #[arg(long)]
+    pub passphrase: Option<String>,
+    /// Output file (default `./<username>.mebundle`); never overwritten.
+    #[arg(long, short = 'o')]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Bundle written by `me export`.
+    pub file: PathBuf,
+    /// Passphrase of the bundle (prompted if missing).
+    #[arg(long)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Serialize)]
+struct IdentitySummary<'a> {
+    username: &'a str,
+    public_key: &'a str,
+    context_id: &'a str,
+}
+
+impl<'a> IdentitySummary<'a> {
+    fn of(me: &'a Me<SqliteStore>) -> Self {
+        Self { username: &me.username, public_key: &me.public_key, context_id: &me.context_id }
+    }
+}
+
+/// JSON en una línea o la salida para humanos.
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn emit<T: Serialize>(&self, value: &T, human: impl FnOnce(&T)) -> CliResult {
+        if self.json {
+            println!("{}", serde_json::to_string(value)?);
+        } else {
+            human(value);
+        }
+        Ok(())
+    }
+}
+
+pub async fn run(cli: Cli) -> CliResult {
+    let out = Output { json: cli.json };
+    match cli.command {
+        Command::Create(args) => create(&out, args).await,
+        Command::Load(args) => {
+            let me = unlock(&args).await?;
+            out.emit(&IdentitySummary::of(&me), |s| print_identity("🔓 Unlocked", s))
+        }
+        Command::List => list(&out),
+        Command::Be(args) => write_verb(&out, Verb::Be, args).await,
+        Command::Have(args) => write_verb(&out, Verb::Have, args).await,
+        Command::Do(args) => write_verb(&out, Verb::Do, args).await,
+        Command::At(args) => write_verb(&out, Verb::At, args).await,
+        Command::Relate(args) => write_verb(&out, Verb::Relate, args).await,
+        Command::React(args) => write_verb(&out, Verb::React, args).await,
+        Command::Communicate(args) => write_verb(&out, Verb::Communicate, args).await,
+        Command::Declare(args) => declare(&out, args).await,
+        Command::Get(args) => get(&out, args).await,
+        Command::Query(args) => {
+            let me = unlock(&args.identity).await?;
+            let entries = me.query(&args.query).await?;
+            out.emit(&entries, |entries| print_entries(entries))
+        }
+        Command::Host => {
+            if out.json {
+                out.emit(&host::host_info(), |_| ())
+            } else {
+                Ok(host::print_host_summary()?)
+            }
+        }
+        Command::Network => {
+            if out.json {
+                out.emit(&host::network_interfaces()?, |_| ())
+            } else {
+                Ok(host::print_network_summary()?)
+            }
+        }
+        Command::Link { alias, path } => {
+            let entry = context_stack(&alias)?.link(&path)?;
+            out.emit(&entry, |e| print_link("🔗 Linked to", e))
+        }
+        Command::History(alias) => {
+            let history = context_stack(&alias)?.history()?;
+            out.emit(&history, print_history)
+        }
+        Command::Back(alias) => {
+            let entry = context_stack(&alias)?.back()?;
+            out.emit(&entry, |e| print_link("🔙 Back to", e))
+        }
+        Command::Forward(alias) => {
+            let entry = context_stack(&alias)?.forward()?;
+            out.emit(&entry, |e| print_link("🔜 Forward to", e))
+        }
+        Command::Bookmark { alias, name, path, delete } => {
+            let stack = context_stack(&alias)?;
+            if delete {
+                let bookmark = stack.remove_bookmark(&name)?;
+                out.emit(&bookmark, |b| println!("🗑️  Removed bookmark {} ({})", name.bright_cyan(), b.path))
+            } else {
+                let bookmark = stack.bookmark(&name, path.as_deref())?;
+                out.emit(&bookmark, |b| println!("🔖 {} → {}", name.bright_cyan(), b.path))
+            }
+        }
+        Command::Go { alias, name } => {
+            let entry = context_stack(&alias)?.go(&name)?;
+            out.emit(&entry, |e| print_link("🔗 Linked to", e))
+        }
+        Command::Qr(args) => qr(&out, args).await,
+        Command::QrImport(args) => qr_import(&out, args).await,
+        Command::Shell(args) => {
+            if out.json {
+                return Err("`me shell` is interactive and has no --json mode".into());
+            }
+            shell::run(unlock(&args).await?).await
+        }
+        Command::Backup(args) => backup(&out, args).await,
+        Command::Restore(args) => restore(&out, args).await,
+        Command::Export(args) => export(&out, args).await,
+        Command::Import(args) => import(&out, args).await,
+    }
+}
+
+/// Imprime `err` como lo espera el modo de salida (stdout en JSON, stderr para humanos).
+pub fn report_error(json: bool, err: &(dyn Error + Send + Sync)) {
+    if json {
+        println!("{}", json!({ "error": err.to_string() }));
+    } else {
+        eprintln!("{} {}", "❌".red(), err);
+    }
+}
+
+fn password(identity: &IdentityArgs, confirm: bool) -> Result<String, Box<dyn Error + Send + Sync>> {
+    if let Some(password) = &identity.password {
+        return Ok(password.clone());
+    }
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+    let password = rpassword::prompt_password(format!("🔑 Password for {}: ", identity.username))?;
+    if confirm && rpassword::prompt_password("🔑 Repeat password: ")? != password {
+        return Err("Passwords do not match".into());
+    }
+    Ok(password)
+}
+
+fn ensure_exists(username: &str) -> CliResult {
+    if !manager::db_path(username)?.exists() {
+        return Err(format!("Identity '{}' does not exist (create it with `me create`)", username).into());
+    }
+    Ok(())
+}
+
+fn context_stack(alias: &AliasArgs) -> Result<ContextStack, Box<dyn Error + Send + Sync>> {
+    ensure_exists(&alias.username)?;
+    Ok(ContextStack::for_identity(&alias.username)?)
+}
+
+/// Abre la base del alias (sin crearla) y descifra la identidad.
+pub async fn unlock(identity: &IdentityArgs) -> Result<Me<SqliteStore>, Box<dyn Error + Send + Sync>> {
+    ensure_exists(&identity.username)?;
+    let store = Arc::new(SqliteStore::open(&identity.username)?);
+    Ok(Me::load(store, &identity.username, &password(identity, false)?).await?)
+}
+
+async fn create(out: &Output, args: IdentityArgs) -> CliResult {
+    validate_username(&args.username)?;
+    if manager::db_path(&args.username)?.exists() {
+        return Err(format!("Identity '{}' already exists", args.username).into());
+    }
+    let password = password(&args, true)?;
+    validate_password(&password)?;
+    let store = Arc::new(SqliteStore::open(&args.username)?);
+    let me = Me::create(store, &args.username, &password).await?;
+    out.emit(&IdentitySummary::of(&me), |s| print_identity("✨ Created", s))
+}
+
+fn list(out: &Output) -> CliResult {
+    let mut identities = manager::list_us()?;
+    identities.sort_by(|a, b| a.username.cmp(&b.username));
+    out.emit(&identities, |identities| {
+        if identities.is_empty() {
+            println!("📭 No identities found.");
+        }
+        for me in identities {
+            println!("👤 {} {}", me.username.bright_cyan().bold(), me.path.display().bright_black());
+        }
+    })
+}
+
+async fn write_verb(out: &Output, verb: Verb, args: VerbArgs) -> CliResult {
+    let me = unlock(&args.identity).await?;
+    let context_id = args.context.unwrap_or_else(|| me.context_id.clone());
+    me.insert(verb, &context_id, &args.key, &args.value).await?;
+    let written = json!({ "verb": verb, "context_id": context_id, "key": args.key, "value": args.value });
+    out.emit(&written, |_| println!("{} {} {} = {}", "✅".green(), verb.bright_cyan().bold(), args.key, args.value))
+}
+
+async fn declare(out: &Output, args: DeclareArgs) -> CliResult {
+    let me = unlock(&args.verb.identity).await?;
+    let VerbArgs { context, key, value, .. } = args.verb;
+    let context_id = context.unwrap_or_else(|| me.context_id.clone());
+    let correlation_id = me.declare(&context_id, &args.secondary, &key, &value).await?;
+    let written = json!({
+        "secondary": args.secondary,
+        "correlation_id": correlation_id,
+        "context_id": context_id,
+        "key": key,
+        "value": value,
+    });
+    out.emit(&written, |_| {
+        println!("{} {} {} = {} {}", "✅".green(), args.secondary.bright_cyan().bold(), key, value, correlation_id.bright_black())
+    })
+}
+
+async fn get(out: &Output, args: GetArgs) -> CliResult {
+    let me = unlock(&args.identity).await?;
+    let entries = me.get(&args.filter.into_filter(args.verb)).await?;
+    out.emit(&entries, |entries| print_entries(entries))
+}
+
+async fn backup(out: &Output, args: IdentityArgs) -> CliResult {
+    ensure_exists(&args.username)?;
+    let password = password(&args, false)?;
+    let store = Arc::new(SqliteStore::open(&args.username)?);
+    let me = Me::load(store, &args.username, &password).await?;
+    let words = me.export_mnemonic(&password).await?;
+    out.emit(&json!({ "username": me.username, "mnemonic": words }), |_| {
+        println!("{}", "🧩 Recovery phrase — write it down and keep it offline:".bright_white().bold());
+        for (i, word) in words.split(' ').enumerate() {
+            print!("{:>2}. {:<10}", i + 1, word);
+            if i % 4 == 3 {
+                println!();
+            }
+        }
+        println!("{}", "Anyone with these words can take over the identity.".yellow());
+    })
+}
+
+async fn restore(out: &Output, args: RestoreArgs) -> CliResult {
+    validate_username(&args.identity.username)?;
+    let words = match args.words {
+        Some(words) => words,
+        None => rpassword::prompt_password("🧩 Recovery phrase: ")?,
+    };
+    let password = password(&args.identity, true)?;
+    validate_password(&password)?;
+    let store = Arc::new(SqliteStore::open(&args.identity.username)?);
+    let me = Me::restore_from_mnemonic(store, &args.identity.username, &words, &password).await?;
+    out.emit(&IdentitySummary::of(&me), |s| print_identity("♻️  Restored", s))
+}
+
+async fn export(out: &Output, args: ExportArgs) -> CliResult {
+    let me = unlock(&args.identity).await?;
+    let path = args.out.unwrap_or_else(|| PathBuf::from(format!("{}.mebundle", me.username)));
+    let passphrase = match args.passphrase {
+        Some(passphrase) => passphrase,
+        None => {
+            let passphrase = rpassword::prompt_password("📦 Bundle passphrase: ")?;
+            if rpassword::prompt_password("📦 Repeat passphrase: ")? != passphrase {
+                return Err("Passphrases do not match".into());
+            }
+            passphrase
+        }
+    };
+    validate_password(&passphrase)?;
+    let history = context_stack(&AliasArgs { username: me.username.clone() })?.history()?;
+    let links = (history != History::default()).then_some(history);
+    let bundle = me.export_bundle(&passphrase, links).await?;
+    // create_new: falla si el archivo ya existe, sin ventana entre comprobar y escribir
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => format!("{} already exists", path.display()).into(),
+        _ => Box::<dyn Error + Send + Sync>::from(e),
+    })?;
+    file.write_all(bundle.to_json()?.as_bytes())?;
+    out.emit(&json!({ "username": me.username, "path": path }), |_| {
+        println!("📦 Exported {} to {}", me.username.bright_cyan().bold(), path.display());
+    })
+}
+
+async fn import(out: &Output, args: ImportArgs) -> CliResult {
+    let bundle = Bundle::from_json(&std::fs::read_to_string(&args.file)?)?;
+    validate_username(&bundle.username)?;
+    let passphrase = match args.passphrase {
+        Some(passphrase) => passphrase,
+        None => rpassword::prompt_password(format!("📦 Passphrase for {}'s bundle: ", bundle.username))?,
+    };
+    let contents = bundle.open(&passphrase)?;
+    let store = SqliteStore::open(&bundle.username)?;
+    let report = contents.import_into(&store).await?;
+    let links = match &contents.links {
+        Some(history) => Some(ContextStack::for_identity(&bundle.username)?.import(history)?),
+        None => None,
+    };
+    out.emit(&json!({ "report": report, "links_imported": links }), |_| {
+        let verb = if report.created { "✨ Imported" } else { "🔁 Merged into" };
+        println!("{} {}", verb.bright_green().bold(), report.username.bright_cyan().bold());
+        println!(
+            "   {} keys, {} entries ({} already here), {} ARC samples",
+            report.keys_imported.len(),
+            report.entries_imported,
+            report.entries_present,
+            report.arc_imported
+        );
+        for conflict in &report.conflicts {
+            let what = match conflict {
+                Conflict::MainKey => "main key is sealed with another password here; kept this machine's".to_string(),
+                Conflict::Key { authority, .. } => format!("key '{}' differs here; kept this machine's", authority),
+                Conflict::Context { context_id, store_only } => {
+                    format!("context {} has {} entries the bundle lacks; skipped", context_id, store_only)
+                }
+            };
+            println!("{} {}", "⚠️ ".yellow(), what);
+        }
+        if links == Some(false) {
+            println!("{} link history differs here; kept this machine's", "⚠️ ".yellow());
+        }
+    })
+}
+
+async fn qr(out: &Output, args: QrArgs) -> CliResult {
+    let me = unlock(&args.identity).await?;
+    let passphrase = match args.passphrase {
+        Some(passphrase) => passphrase,
+        None => qrcode::prompt_passphrase("🔏 Seal passphrase: ")?,
+    };
+    let (_, encrypted) = me.store.load_keys(&me.username).await?;
+    let payload = qrcode::SealPayload::new(&me.username, Some(&me.context_id), &me.public_key, &encrypted);
+    let payload = serde_json::to_string(&payload)?;
+    if out.json {
+        let blob = qrcode::create_hybrid_blob(&me.public_key, &payload, &passphrase)?;
+        return out.emit(&json!({ "username": me.username, "blob": blob }), |_| ());
+    }
+    let ctx_path = manager::db_path(&me.username)?;
+    let ctx_path = ctx_path.parent().ok_or("Invalid identity path")?;
+    Ok(qrcode::render_hybrid_qr(&me.public_key, &payload, &passphrase, ctx_path)?)
+}
+
+async fn qr_import(out: &Output, args: QrImportArgs) -> CliResult {
+    if out.json && !args.yes {
+        return Err("`me qr-import --json` needs --yes (there is no prompt to confirm the key)".into());
+    }
+    let blob = match (&args.image, args.blob) {
+        (Some(image), _) => qrcode::decode_qr_image(image)?,
+        (None, Some(blob)) => blob,
+        (None, None) => {
+            print!("🔏 Paste the seal blob: ");
+            std::io::Write::flush(&mut std::io::stdout())?;
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line
+        }
+    };
+    let (public_key, _) = qrcode::parse_hybrid_blob(blob.trim())?;
+    if !args.yes {
+        println!("{} {}", "🔑 Public key :".bright_blue().bold(), public_key.bright_yellow().bold());
+        print!("Import the identity with this key? [y/N] ");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            return Err("Import cancelled".into());
+        }
+    }
+    let passphrase = match args.passphrase {
+        Some(passphrase) => passphrase,
+        None => qrcode::prompt_passphrase("🔏 Seal passphrase: ")?,
+    };
+    let payload = qrcode::open_seal(&blob, &passphrase)?;
+    validate_username(&payload.username)?;
+    let store = SqliteStore::open(&payload.username)?;
+    let created = qrcode::import_seal(&store, &payload).await?;
+    let summary = json!({ "username": payload.username, "public_key": public_key, "created": created });
+    out.emit(&summary, |_| {
+        let title = if created { "🔓 Imported" } else { "👌 Already here:" };
+        println!("{} {}", title.bright_green().bold(), payload.username.bright_cyan().bold());
+        println!("   unlock it with its usual password (`me load -u {}`)", payload.username);
+    })
+}
+
+fn print_identity(title: &str, identity: &IdentitySummary<'_>) {
+    println!("{} {}", title.bright_green().bold(), identity.username.bright_cyan().bold());
+    println!("{} {}", "🔑 Public key :".bright_blue().bold(), identity.public_key);
+    println!("{} {}", "🧭 Context    :".magenta().bold(), identity.context_id);
+}
+
+pub(crate) fn print_link(title: &str, entry: &LinkEntry) {
+    println!("{} '{}'", title.bright_green().bold(), entry.path);
+}
+
+fn print_history(history: &History) {
+    if history.entries.is_empty() {
+        println!("📭 No link history found.");
+    } else {
+        println!("{}", "📜 Link history:".bright_white().bold());
+    }
+    for (i, entry) in history.entries.iter().enumerate() {
+        let marker = if history.cursor == Some(i) { "→" } else { " " };
+        println!("{} {} {} {}", marker.bright_green(), format!("{:>2}.", i + 1).bright_black(), entry.path, entry.linked_at.bright_black());
+    }
+    if !history.bookmarks.is_empty() {
+        println!("{}", "🔖 Bookmarks:".bright_white().bold());
+    }
+    for (name, bookmark) in &history.bookmarks {
+        println!("   {} → {}", name.bright_cyan(), bookmark.path);
+    }
+}
+
+pub(crate) fn print_entries(entries: &[Entry]) {
+    if entries.is_empty() {
+        println!("📭 No entries found.");
+    }
+    for entry in entries {
+        println!(
+            "{} {:<11} {} = {}",
+            entry.timestamp.bright_black(),
+            entry.verb.name().bright_cyan().bold(),
+            entry.key,
+            entry.value
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // parseo de do/say, get, query, bookmark, qr-import, restore, export e import (el resto de `Command` no tiene test propio)
+    #[test]
+    fn parses_verbs_with_long_and_short_flags() {
+        // tests/me_restore.sh
+        let cli = Cli::try_parse_from(["me", "do", "--username", "suign", "--password", "123456", "--key", "task", "--value", "build"]).unwrap();
+        let Command::Do(args) = cli.command else { panic!("expected do") };
+        assert_eq!((args.identity.username.as_str(), args.key.as_str()), ("suign", "task"));
+        assert_eq!(args.identity.password.as_deref(), Some("123456"));
+
+        let cli = Cli::try_parse_from(["me", "say", "-u", "suign", "-k", "group", "-v", "hi", "--json"]).unwrap();
+        assert!(cli.json && matches!(cli.command, Command::Communicate(_)));
+    }
+
+    #[test]
+    fn parses_get() {
+        let cli = Cli::try_parse_from(["me", "--json", "get", "-u", "suign", "--verb", "be,have", "--limit", "5", "--since", "2025-01-01"]).unwrap();
+        let Command::Get(args) = cli.command else { panic!("expected get") };
+        assert_eq!(args.verb, VerbSelector::Many(vec![Verb::Be, Verb::Have]));
+        assert_eq!((args.filter.limit, args.filter.since.as_deref()), (Some(5), Some("2025-01-01")));
+        assert!(Cli::try_parse_from(["me", "get", "-u", "suign", "--verb", "dance"]).is_err());
+    }
+
+    #[test]
+    fn parses_query() {
+        let cli = Cli::try_parse_from(["me", "query", "-u", "suign", r#"have where value.$.chain = "eth" limit 5"#]).unwrap();
+        let Command::Query(args) = cli.command else { panic!("expected query") };
+        assert_eq!((args.query.verbs, args.query.limit), (VerbSelector::One(Verb::Have), Some(5)));
+        assert!(Cli::try_parse_from(["me", "query", "-u", "suign", "have where key"]).is_err());
+    }
+
+    #[test]
+    fn parses_bookmark() {
+        let cli = Cli::try_parse_from(["me", "bookmark", "-u", "suign", "wiki", "--delete"]).unwrap();
+        assert!(matches!(cli.command, Command::Bookmark { delete: true, path: None, .. }));
+        assert!(Cli::try_parse_from(["me", "bookmark", "-u", "suign", "wiki", "x", "--delete"]).is_err());
+    }
+
+    #[test]
+    fn parses_qr_import() {
+        let cli = Cli::try_parse_from(["me", "qr-import", "seal_hybrid_encrypted.png", "-y"]).unwrap();
+        assert!(matches!(cli.command, Command::QrImport(QrImportArgs { image: Some(_), yes: true, .. })));
+        assert!(Cli::try_parse_from(["me", "qr-import", "seal.png", "--blob", "x.y"]).is_err());
+    }
+
+    #[test]
+    fn parses_restore() {
+        let cli = Cli::try_parse_from(["me", "restore", "-u", "suign", "-p", "abcdef", "--words", "abandon art"]).unwrap();
+        let Command::Restore(args) = cli.command else { panic!("expected restore") };
+        assert_eq!((args.identity.username.as_str(), args.words.as_deref()), ("suign", Some("abandon art")));
+    }
+
+    #[test]
+    fn parses_export() {
+        let cli = Cli::try_parse_from(["me", "export", "-u", "suign", "-o", "suign.mebundle"]).unwrap();
+        let Command::Export(args) = cli.command else { panic!("expected export") };
+        assert_eq!(args.out.as_deref(), Some(std::path::Path::new("suign.mebundle")));
+    }
+
+    #[test]
+    fn parses_import() {
+        let cli = Cli::try_parse_from(["me", "import", "suign.mebundle", "--passphrase", "pass"]).unwrap();
+        assert!(matches!(cli.command, Command::Import(ImportArgs { passphrase: Some(_), .. })));
+    }
+}
@@ -0,0 +1,136 @@
+//this.me/crate/src/core/arc.rs
+// ARC (Affinity–Reality–Communication): la fuerza de una relación como serie temporal de
+// tres puntajes, de `actor` hacia `target`. Cada `Me::arc` agrega una muestra a la tabla `arc`;
+// el puntaje actual es la última muestra y la tendencia se calcula sobre una ventana.
+//
+// La relación es dirigida: lo que `me1` siente por `me2` no es lo que `me2` siente por `me1`.
+use serde::{Deserialize, Serialize};
+use crate::utils::me_error::MeError;
+
+/// Los tres puntajes de una muestra (o su promedio / variación en una ventana).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArcScores {
+    pub affinity: f64,
+    pub reality: f64,
+    pub communication: f64,
+}
+
+impl ArcScores {
+    pub fn new(affinity: f64, reality: f64, communication: f64) -> Self {
+        Self { affinity, reality, communication }
+    }
+
+    /// Fuerza de la relación: promedio de los tres puntajes (el triángulo sube o baja junto).
+    pub fn strength(&self) -> f64 {
+        (self.affinity + self.reality + self.communication) / 3.0
+    }
+
+    fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.affinity), f(self.reality), f(self.communication))
+    }
+
+    fn zip(self, other: Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        Self::new(
+            f(self.affinity, other.affinity),
+            f(self.reality, other.reality),
+            f(self.communication, other.communication),
+        )
+    }
+
+    /// NaN e infinitos romperían promedios y tendencias.
+    pub fn validate(&self) -> Result<(), MeError> {
+        if [self.affinity, self.reality, self.communication].iter().all(|s| s.is_finite()) {
+            Ok(())
+        } else {
+            Err(MeError::Validation("ARC scores must be finite numbers".into()))
+        }
+    }
+}
+
+/// Una fila de la tabla `arc`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArcEntry {
+    pub actor: String,
+    pub target: String,
+    #[serde(flatten)]
+    pub scores: ArcScores,
+    pub timestamp: String,
+}
+
+/// Agregado de una línea de tiempo ARC (ver `trend`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArcTrend {
+    pub samples: usize,
+    /// Primera y última muestra de la ventana.
+    pub since: String,
+    pub until: String,
+    /// Puntaje actual (la última muestra).
+    pub current: ArcScores,
+    pub average: ArcScores,
+    /// Última menos primera: positivo = la relación se fortalece.
+    pub change: ArcScores,
+}
+
+/// Resume `timeline` (en orden cronológico ascendente, como lo devuelve `MeStore::arc_timeline`).
+/// `None` si no hay muestras.
+pub fn trend(timeline: &[ArcEntry]) -> Option<ArcTrend> {
+    let (first, last) = (timeline.first()?, timeline.last()?);
+    let n = timeline.len() as f64;
+    let sum = timeline.iter().fold(ArcScores::default(), |acc, e| acc.zip(e.scores, |a, b| a + b));
+    Some(ArcTrend {
+        samples: timeline.len(),
+        since: first.timestamp.clone(),
+        until: last.timestamp.clone(),
+        current: last.scores,
+        average: sum.map(|s| s / n),
+        change: last.scores.zip(first.scores, |a, b| a - b),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use chrono::Duration;
+    use crate::core::Me;
+    use crate::db::memory::MemoryStore;
+
+    fn sample(ts: &str, a: f64, r: f64, c: f64) -> ArcEntry {
+        ArcEntry { actor: "me1".into(), target: "me2".into(), scores: ArcScores::new(a, r, c), timestamp: ts.into() }
+    }
+
+    #[test]
+    fn trend_reports_current_average_and_change() {
+        assert!(trend(&[]).is_none());
+        let t = trend(&[sample("t1", 0.2, 0.4, 0.0), sample("t2", 0.4, 0.4, 0.3), sample("t3", 0.6, 0.1, 0.6)]).unwrap();
+        assert_eq!((t.samples, t.since.as_str(), t.until.as_str()), (3, "t1", "t3"));
+        assert_eq!(t.current, ArcScores::new(0.6, 0.1, 0.6));
+        assert!((t.average.affinity - 0.4).abs() < 1e-9 && (t.average.communication - 0.3).abs() < 1e-9);
+        assert!((t.change.reality + 0.3).abs() < 1e-9);
+        assert!((t.current.strength() - 13.0 / 30.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn me_records_arc_timelines_per_direction() {
+        let store = Arc::new(MemoryStore::new());
+        let me = Me::create(store, "suign", "123456").await.unwrap();
+        let cid = me.context_id.clone();
+
+        assert!(me.arc_current(&cid, "me1", "me2").await.unwrap().is_none());
+        me.arc(&cid, "me1", "me2", 0.5, 0.5, 0.2).await.unwrap();
+        me.arc(&cid, "me2", "me1", -0.4, 0.0, 0.1).await.unwrap();
+        me.arc(&cid, "me1", "me2", 0.8, 0.6, 0.7).await.unwrap();
+        assert!(me.arc(&cid, "me1", "me2", f64::NAN, 0.0, 0.0).await.is_err());
+        assert!(me.arc(&cid, "", "me2", 0.0, 0.0, 0.0).await.is_err());
+
+        let timeline = me.arc_timeline(&cid, "me1", "me2", None, None).await.unwrap();
+        let affinity: Vec<f64> = timeline.iter().map(|e| e.scores.affinity).collect();
+        assert_eq!(affinity, [0.5, 0.8]);
+        assert_eq!(me.arc_current(&cid, "me2", "me1").await.unwrap().unwrap().scores.affinity, -0.4);
+
+        let t = me.arc_trend(&cid, "me1", "me2", Duration::hours(1)).await.unwrap().unwrap();
+        assert_eq!(t.samples, 2);
+        assert!((t.change.communication - 0.5).abs() < 1e-9);
+        assert!(me.arc_trend(&cid, "me1", "nobody", Duration::hours(1)).await.unwrap().is_none());
+    }
+}
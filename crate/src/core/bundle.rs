@@ -0,0 +1,397 @@
+//this.me/crate/src/core/bundle.rs
+// Bundle portable de una identidad: un archivo JSON para mover la identidad entre máquinas o
+// entre backends (SQLite ↔ Postgres ↔ memoria) sin copiar `~/.this/me/<alias>/<alias>.db`.
+//
+// Formato v1 (`BUNDLE_FORMAT`, `BUNDLE_VERSION`):
+//
+//     {
+//       "format": "this.me/bundle", "version": 1,
+//       "username": "...", "public_key": "...", "created_at": "<RFC 3339>",
+//       "payload": "<Envelope en base64>",
+//       "signature": "<ed25519 en base64>"
+//     }
+//
+// - `payload` es un `Envelope` (ChaCha20-Poly1305, KDF de la passphrase registrado adentro) con el
+//   JSON de `BundleContents`. Su AAD es el JSON de la cabecera (format, version, username,
+//   public_key, created_at, en ese orden): no se puede pegar un payload bajo otra cabecera.
+// - `signature` firma con la llave `main` el JSON de la cabecera con `payload` al final.
+// - Las llaves viajan como están en el store, cifradas con el password de la identidad: la
+//   passphrase del bundle protege el resto (verbos, ARC, historial de links).
+//
+// La firma la hace la misma llave que viaja adentro: prueba integridad y que quien exportó tenía la
+// llave, no que la identidad sea quien dice. Al importar, un username que ya existe con otra public
+// key se rechaza.
+//
+// Contextos incluidos: el de la identidad y los que tienen al menos una entrada firmada por su llave
+// `main`, cada uno con todas sus filas y sus muestras ARC.
+//
+// Cambiar la forma de `BundleContents` o de la cabecera sube `BUNDLE_VERSION`.
+use std::collections::HashSet;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{Signature, Signer};
+use serde::{Deserialize, Serialize};
+use super::arc::ArcEntry;
+use super::chain;
+use super::keys::KeyPair;
+use super::model::Entry;
+use super::sign::verify;
+use super::store::MeStore;
+use crate::utils::envelope::{AeadId, Envelope};
+use crate::utils::kdf::Kdf;
+use crate::utils::me_error::MeError;
+use super::links::History;
+
+pub const BUNDLE_FORMAT: &str = "this.me/bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// El archivo tal cual se escribe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub username: String,
+    pub public_key: String,
+    pub created_at: String,
+    pub payload: String,
+    pub signature: String,
+}
+
+/// Cabecera: AAD del envelope (sin `payload`) y mensaje firmado (con `payload`).
+#[derive(Serialize)]
+struct Header<'a> {
+    format: &'a str,
+    version: u32,
+    username: &'a str,
+    public_key: &'a str,
+    created_at: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<&'a str>,
+}
+
+/// Lo que va cifrado en `payload`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleContents {
+    pub identity: IdentityRecord,
+    pub keys: Vec<KeyRecord>,
+    pub contexts: Vec<ContextRecord>,
+    /// `links.json` de la identidad, si el que exporta lo tiene (el store no lo guarda).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<History>,
+    pub metadata: BundleMetadata,
+}
+
+/// Fila de `me`: la llave `main` cifrada con el password de la identidad.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityRecord {
+    pub username: String,
+    pub public_key: String,
+    pub encrypted_private_key: String,
+}
+
+/// Fila de `keys`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRecord {
+    pub authority: String,
+    pub public_key: String,
+    pub encrypted_private_key: String,
+}
+
+/// Un contexto completo: el log (orden cronológico, con sus pruebas) y la tabla `arc`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextRecord {
+    pub context_id: String,
+    pub entries: Vec<Entry>,
+    #[serde(default)]
+    pub arc: Vec<ArcEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleMetadata {
+    pub exported_at: String,
+    /// Versión del crate que exportó.
+    pub exporter: String,
+    pub entries: usize,
+    pub arc_samples: usize,
+}
+
+/// Algo del bundle que no se importó porque el store ya tenía otra cosa. Nunca se sobreescribe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Conflict {
+    /// Misma llave `main` cifrada con otro password: queda la del store.
+    MainKey,
+    /// La autoridad ya existe con otra llave (o la misma con otro cifrado): queda la del store.
+    Key { authority: String, same_public_key: bool },
+    /// El store tiene entradas del contexto que el bundle no: las cadenas no se mezclan y no se
+    /// importa ninguna entrada del contexto.
+    Context { context_id: String, store_only: usize },
+}
+
+/// Resultado de `BundleContents::import_into`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub username: String,
+    /// `false` si la fila de la identidad ya estaba (misma public key).
+    pub created: bool,
+    pub keys_imported: Vec<String>,
+    pub entries_imported: usize,
+    /// Entradas que el store ya tenía.
+    pub entries_present: usize,
+    pub arc_imported: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Bundle {
+    /// Cifra `contents` con `passphrase` y firma con `key` (la llave `main` de la identidad).
+    pub fn seal(contents: &BundleContents, key: &KeyPair, passphrase: &str, kdf: Kdf) -> Result<Self, MeError> {
+        let identity = &contents.identity;
+        if key.public_key_base64() != identity.public_key {
+            return Err(MeError::Validation("The bundle must be signed with the identity's main key".into()));
+        }
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let mut header = Header {
+            format: BUNDLE_FORMAT,
+            version: BUNDLE_VERSION,
+            username: &identity.username,
+            public_key: &identity.public_key,
+            created_at: &created_at,
+            payload: None,
+        };
+        let aad = serde_json::to_vec(&header)?;
+        let plaintext = serde_json::to_vec(contents)?;
+        let payload = Envelope::seal_with_password(&plaintext, passphrase, AeadId::ChaCha20Poly1305, kdf, &aad)?.to_base64()?;
+        header.payload = Some(&payload);
+        let signature = STANDARD.encode(key.signing_key.sign(&serde_json::to_vec(&header)?).to_bytes());
+        Ok(Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            username: identity.username.clone(),
+            public_key: identity.public_key.clone(),
+            created_at,
+            payload,
+            signature,
+        })
+    }
+
+    /// Verifica firma, descifra y comprueba el contenido: que la identidad sea la de la cabecera y
+    /// que el log de cada contexto encadene con su public key (`core::chain`).
+    /// Passphrase incorrecta: `MeError::WrongPassword`; firma: `InvalidSignature`; contenido: `CorruptData`.
+    pub fn open(&self, passphrase: &str) -> Result<BundleContents, MeError> {
+        if self.format != BUNDLE_FORMAT {
+            return Err(MeError::Validation(format!("Not a this.me bundle (format '{}')", self.format)));
+        }
+        if self.version != BUNDLE_VERSION {
+            return Err(MeError::Validation(format!("Unsupported bundle version {}", self.version)));
+        }
+        let mut header = self.header();
+        header.payload = Some(&self.payload);
+        let signature = Signature::from_slice(&STANDARD.decode(&self.signature)?)?;
+        verify(&self.public_key, &serde_json::to_vec(&header)?, &signature)?;
+
+        header.payload = None;
+        let envelope = Envelope::from_base64(&self.payload).map_err(|e| MeError::CorruptData(e.to_string()))?;
+        if envelope.aad != serde_json::to_vec(&header)? {
+            return Err(MeError::CorruptData("bundle payload does not belong to its header".into()));
+        }
+        let plaintext = envelope.open_with_password(passphrase).map_err(MeError::from_unseal)?;
+        let contents: BundleContents = serde_json::from_slice(&plaintext).map_err(|e| MeError::CorruptData(e.to_string()))?;
+
+        let identity = &contents.identity;
+        if (identity.username.as_str(), identity.public_key.as_str()) != (self.username.as_str(), self.public_key.as_str()) {
+            return Err(MeError::CorruptData("bundle contents do not match its header".into()));
+        }
+        for context in &contents.contexts {
+            let report = chain::verify_chain(&self.public_key, &context.context_id, &context.entries);
+            if let Some(broken) = report.broken {
+                return Err(MeError::CorruptData(format!(
+                    "context {} breaks at entry {}: {}",
+                    context.context_id, broken.position, broken.reason
+                )));
+            }
+        }
+        Ok(contents)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, MeError> {
+        serde_json::from_str(json).map_err(|e| MeError::Validation(format!("Invalid bundle file: {}", e)))
+    }
+
+    fn header(&self) -> Header<'_> {
+        Header {
+            format: &self.format,
+            version: self.version,
+            username: &self.username,
+            public_key: &self.public_key,
+            created_at: &self.created_at,
+            payload: None,
+        }
+    }
+}
+
+impl BundleContents {
+    /// Escribe el contenido en `store` sin pisar nada (ver `Conflict`). Importar dos veces el mismo
+    /// bundle no duplica filas. Con otra public key bajo el mismo username falla con
+    /// `MeError::AlreadyExists` antes de escribir.
+    pub async fn import_into<S: MeStore + ?Sized>(&self, store: &S) -> Result<ImportReport, MeError> {
+        let identity = &self.identity;
+        let mut report = ImportReport { username: identity.username.clone(), ..Default::default() };
+
+        match store.create_identity(&identity.username, &identity.public_key, &identity.encrypted_private_key).await {
+            Ok(()) => report.created = true,
+            Err(MeError::AlreadyExists(_)) => {
+                let (public_key, encrypted) = store.load_keys(&identity.username).await?;
+                if public_key != identity.public_key {
+                    return Err(MeError::AlreadyExists(identity.username.clone()));
+                }
+                if encrypted != identity.encrypted_private_key {
+                    report.conflicts.push(Conflict::MainKey);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        for key in &self.keys {
+            match store.insert_key(&identity.username, &key.authority, &key.public_key, &key.encrypted_private_key).await {
+                Ok(()) => report.keys_imported.push(key.authority.clone()),
+                Err(MeError::KeyExists(_)) => {
+                    let stored = store.load_key(&identity.username, &key.authority).await?;
+                    if stored != (key.public_key.clone(), key.encrypted_private_key.clone()) {
+                        report.conflicts.push(Conflict::Key {
+                            authority: key.authority.clone(),
+                            same_public_key: stored.0 == key.public_key,
+                        });
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        for context in &self.contexts {
+            let cid = &context.context_id;
+            // una entrada es la misma si coincide todo, prueba incluida
+            let bundled: HashSet<String> = context.entries.iter().map(serde_json::to_string).collect::<Result<_, _>>()?;
+            let stored: HashSet<String> = store.log(cid).await?.iter().map(serde_json::to_string).collect::<Result<_, _>>()?;
+            let store_only = stored.difference(&bundled).count();
+            if store_only > 0 {
+                report.conflicts.push(Conflict::Context { context_id: cid.clone(), store_only });
+            } else {
+                for (entry, json) in context.entries.iter().zip(context.entries.iter().map(serde_json::to_string)) {
+                    if stored.contains(&json?) {
+                        report.entries_present += 1;
+                    } else {
+                        store.insert_signed(cid, entry).await?;
+                        report.entries_imported += 1;
+                    }
+                }
+            }
+
+            // ARC no está firmado ni encadenado: se agregan las muestras que falten
+            let stored_arc = store.arc_log(cid).await?;
+            for sample in context.arc.iter().filter(|s| !stored_arc.contains(s)) {
+                store.insert_arc(cid, sample).await?;
+                report.arc_imported += 1;
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::core::{Me, Verb};
+    use crate::db::memory::MemoryStore;
+    use crate::utils::kdf::FAST;
+
+    #[tokio::test]
+    async fn moves_an_identity_between_stores() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create_with_kdf(store.clone(), "suign", "123456", FAST).await.unwrap();
+        me.add_key("cleaker", KeyPair::generate(), "123456").await.unwrap();
+        me.be(&me.context_id, "species", "human").await.unwrap();
+        me.declare(&me.context_id, "travel", "to", "cdmx").await.unwrap();
+        me.arc(&me.context_id, "suign", "alice", 0.5, 0.25, 1.0).await.unwrap();
+        // fila sin firma de antes del log firmado: viaja igual
+        store.insert(Verb::Have, "legacy", "k", "v", "2000-01-01T00:00:00+00:00").await.unwrap();
+        let shared = "shared-context";
+        me.relate(shared, "friend", "alice").await.unwrap();
+
+        let bundle = me.export_bundle_with_kdf("bundle-pass", None, FAST).await.unwrap();
+        let bundle = Bundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        assert!(matches!(bundle.open("wrong"), Err(MeError::WrongPassword)));
+        let contents = bundle.open("bundle-pass").unwrap();
+        let ids: Vec<&str> = contents.contexts.iter().map(|c| c.context_id.as_str()).collect();
+        assert!(ids.contains(&me.context_id.as_str()) && ids.contains(&shared));
+        assert!(!ids.contains(&"legacy"), "contexts with no entry signed by the identity stay out");
+
+        let target = Arc::new(MemoryStore::new());
+        let report = contents.import_into(target.as_ref()).await.unwrap();
+        assert!(report.created && report.conflicts.is_empty());
+        assert_eq!((report.keys_imported.as_slice(), report.arc_imported), (&["cleaker".to_string()][..], 1));
+        assert_eq!(report.entries_imported, contents.metadata.entries);
+
+        let moved = Me::load(target.clone(), "suign", "123456").await.unwrap();
+        assert_eq!((&moved.context_id, moved.list_keys()), (&me.context_id, me.list_keys()));
+        assert_eq!(target.log(&me.context_id).await.unwrap().len(), store.log(&me.context_id).await.unwrap().len());
+        assert!(moved.verify_log(&me.context_id).await.unwrap().is_intact());
+        assert!(moved.verify_log(shared).await.unwrap().is_intact());
+        assert!(moved.arc_current(&me.context_id, "suign", "alice").await.unwrap().is_some());
+
+        // importar de nuevo no duplica nada
+        let again = contents.import_into(target.as_ref()).await.unwrap();
+        assert!(!again.created && again.conflicts.is_empty());
+        assert_eq!((again.entries_imported, again.entries_present, again.arc_imported), (0, contents.metadata.entries, 0));
+    }
+
+    #[tokio::test]
+    async fn reports_conflicts_and_rejects_tampering() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create_with_kdf(store.clone(), "suign", "123456", FAST).await.unwrap();
+        me.add_key("cleaker", KeyPair::generate(), "123456").await.unwrap();
+        me.be(&me.context_id, "species", "human").await.unwrap();
+        let bundle = me.export_bundle_with_kdf("pass", None, FAST).await.unwrap();
+        let contents = bundle.open("pass").unwrap();
+
+        // el mismo store siguió escribiendo y cambió de password después de exportar
+        me.do_(&me.context_id, "task", "build").await.unwrap();
+        me.change_password_with_kdf("123456", "abcdef", FAST).await.unwrap();
+        let report = contents.import_into(store.as_ref()).await.unwrap();
+        assert_eq!(report.entries_imported, 0);
+        assert!(report.conflicts.contains(&Conflict::MainKey));
+        assert!(report.conflicts.contains(&Conflict::Key { authority: "cleaker".into(), same_public_key: true }));
+        assert!(report.conflicts.iter().any(|c| matches!(c, Conflict::Context { store_only, .. } if *store_only > 0)));
+        assert!(Me::load(store.clone(), "suign", "abcdef").await.is_ok());
+
+        // otra identidad con el mismo username
+        let other = Arc::new(MemoryStore::new());
+        Me::create_with_kdf(other.clone(), "suign", "123456", FAST).await.unwrap();
+        assert!(matches!(contents.import_into(other.as_ref()).await, Err(MeError::AlreadyExists(_))));
+
+        // cabecera cambiada, firma ajena, payload de otro bundle
+        let mut renamed = bundle.clone();
+        renamed.username = "mallory".into();
+        assert!(matches!(renamed.open("pass"), Err(MeError::InvalidSignature(_))));
+        let mallory = Me::create_with_kdf(Arc::new(MemoryStore::new()), "mallory", "123456", FAST).await.unwrap();
+        let theirs = mallory.export_bundle_with_kdf("pass", None, FAST).await.unwrap();
+        let mut swapped = theirs.clone();
+        swapped.payload = bundle.payload.clone();
+        assert!(swapped.open("pass").is_err());
+        let mut future = bundle.clone();
+        future.version = BUNDLE_VERSION + 1;
+        assert!(matches!(future.open("pass"), Err(MeError::Validation(_))));
+
+        // entrada editada dentro de un bundle re-firmado: la cadena no verifica
+        let mut forged = contents.clone();
+        forged.contexts[0].entries[0].value = "robot".into();
+        let words = me.export_mnemonic("abcdef").await.unwrap();
+        let key = KeyPair::from_secret(&crate::core::mnemonic::mnemonic_to_seed(&words).unwrap());
+        let resealed = Bundle::seal(&forged, &key, "pass", FAST).unwrap();
+        assert!(matches!(resealed.open("pass"), Err(MeError::CorruptData(_))));
+    }
+}
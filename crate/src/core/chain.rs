@@ -0,0 +1,196 @@
+//this.me/crate/src/core/chain.rs
+// Log de verbos encadenado por hash y firmado (como los `prevHash` de las memorias del kernel npm).
+// Cada entrada de un contexto guarda:
+//   hash      = sha256(JSON canónico de {context_id, verb, key, value, timestamp, prev_hash[, correlation_id]}) en hex
+//   prev_hash = hash de la entrada anterior del mismo contexto ("" en el génesis)
+//   signature = ed25519(hash) con la llave `main`, en base64
+// Editar, borrar o reordenar una entrada rompe el hash, la firma o el eslabón siguiente.
+//
+// Límites: el log no guarda su propio largo, así que `verify_chain` no detecta
+// - que se trunquen entradas del final: lo que queda sigue siendo una cadena válida;
+// - filas sin firma insertadas antes del génesis: cuentan como `unsigned`, no como quiebre.
+// Quien necesite probar completitud debe guardar aparte el `head` (o `verified`) firmado
+// que espera y compararlo con el `LogReport`.
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::Signature;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use super::model::Entry;
+use super::sign::verify;
+
+/// Orden fijo de campos: el JSON de este struct es el preimage del hash.
+#[derive(Serialize)]
+struct Link<'a> {
+    context_id: &'a str,
+    verb: &'a str,
+    key: &'a str,
+    value: &'a str,
+    timestamp: &'a str,
+    prev_hash: &'a str,
+    /// Solo en filas de verbos secundarios: las demás conservan el preimage original.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<&'a str>,
+}
+
+/// Hash (hex) de `entry` encadenada a `prev_hash`; ignora `entry.proof`.
+/// El verbo entra como nombre de tabla (`Verb::table`: `do_`, no `do`).
+pub fn entry_hash(context_id: &str, entry: &Entry, prev_hash: &str) -> String {
+    let link = Link {
+        context_id,
+        verb: entry.verb.table(),
+        key: &entry.key,
+        value: &entry.value,
+        timestamp: &entry.timestamp,
+        prev_hash,
+        correlation_id: entry.correlation_id.as_deref(),
+    };
+    let json = serde_json::to_string(&link).expect("serializing a struct of &str cannot fail");
+    hex::encode(Sha256::digest(json.as_bytes()))
+}
+
+/// Por qué se rompe la cadena en una entrada.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LinkError {
+    #[error("entry is not signed")]
+    Unsigned,
+    #[error("content does not match its hash (expected {expected}, stored {stored})")]
+    HashMismatch { expected: String, stored: String },
+    #[error("previous hash does not match (expected {expected:?}, stored {stored:?})")]
+    PrevMismatch { expected: String, stored: String },
+    #[error("signature does not verify with the identity key")]
+    BadSignature,
+}
+
+/// Primer eslabón roto: posición dentro del log (orden cronológico) y la entrada.
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    pub position: usize,
+    pub entry: Entry,
+    pub reason: LinkError,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogReport {
+    pub context_id: String,
+    /// Entradas firmadas que verifican antes del primer quiebre.
+    pub verified: usize,
+    /// Filas sin firma anteriores al génesis (escritas antes del log firmado); no se pueden probar.
+    pub unsigned: usize,
+    /// Hash de la última entrada verificada. Compararlo con un head conocido es la
+    /// única forma de notar entradas borradas al final del log.
+    pub head: Option<String>,
+    pub broken: Option<BrokenLink>,
+}
+
+impl LogReport {
+    /// Ningún eslabón roto. No implica que el log esté completo: una cola truncada
+    /// o filas sin firma antes del génesis también dan `true` (ver el encabezado del módulo).
+    pub fn is_intact(&self) -> bool {
+        self.broken.is_none()
+    }
+}
+
+/// Verifica `entries` (todas las del contexto, en orden cronológico) contra `public_key_b64`.
+/// Se detiene en el primer eslabón roto.
+pub fn verify_chain(public_key_b64: &str, context_id: &str, entries: &[Entry]) -> LogReport {
+    let mut report = LogReport { context_id: context_id.to_string(), verified: 0, unsigned: 0, head: None, broken: None };
+    let mut prev = String::new();
+
+    for (position, entry) in entries.iter().enumerate() {
+        let reason = match &entry.proof {
+            // sin firma: tolerado solo antes del génesis
+            None if report.head.is_none() => {
+                report.unsigned += 1;
+                continue;
+            }
+            None => Some(LinkError::Unsigned),
+            Some(_) => check_link(public_key_b64, context_id, entry, &prev).err(),
+        };
+        if let Some(reason) = reason {
+            report.broken = Some(BrokenLink { position, entry: entry.clone(), reason });
+            break;
+        }
+        let hash = entry.proof.as_ref().map(|p| p.hash.clone()).unwrap_or_default();
+        prev = hash.clone();
+        report.head = Some(hash);
+        report.verified += 1;
+    }
+    report
+}
+
+fn check_link(public_key_b64: &str, context_id: &str, entry: &Entry, prev: &str) -> Result<(), LinkError> {
+    let proof = entry.proof.as_ref().ok_or(LinkError::Unsigned)?;
+    let expected = entry_hash(context_id, entry, &proof.prev_hash);
+    if expected != proof.hash {
+        return Err(LinkError::HashMismatch { expected, stored: proof.hash.clone() });
+    }
+    if proof.prev_hash != prev {
+        return Err(LinkError::PrevMismatch { expected: prev.to_string(), stored: proof.prev_hash.clone() });
+    }
+    let signature = STANDARD
+        .decode(&proof.signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or(LinkError::BadSignature)?;
+    verify(public_key_b64, proof.hash.as_bytes(), &signature).map_err(|_| LinkError::BadSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::core::{Me, MeStore, Verb};
+    use crate::db::memory::MemoryStore;
+
+    #[tokio::test]
+    async fn reports_the_first_broken_link() {
+        let store = Arc::new(MemoryStore::new());
+        let me = Me::create(store.clone(), "suign", "123456").await.unwrap();
+        let cid = me.context_id.clone();
+        store.insert(Verb::Be, &cid, "legacy", "row", "2000-01-01T00:00:00+00:00").await.unwrap();
+        me.be(&cid, "species", "human").await.unwrap();
+        me.react(&cid, "project:cleaker", "🚀").await.unwrap();
+        me.do_(&cid, "task", "build").await.unwrap();
+
+        let report = me.verify_log(&cid).await.unwrap();
+        assert!(report.is_intact(), "{:?}", report.broken);
+        assert_eq!((report.unsigned, report.verified), (1, 3));
+
+        let log = store.log(&cid).await.unwrap();
+        assert_eq!(log[1].proof.as_ref().unwrap().prev_hash, "");
+        assert_eq!(log[2].proof.as_ref().unwrap().prev_hash, log[1].proof.as_ref().unwrap().hash);
+
+        // valor editado después de firmar
+        let mut edited = log.clone();
+        edited[2].value = "💀".into();
+        let broken = verify_chain(&me.public_key, &cid, &edited).broken.unwrap();
+        assert_eq!(broken.position, 2);
+        assert!(matches!(broken.reason, LinkError::HashMismatch { .. }));
+
+        // entrada borrada: el siguiente eslabón ya no apunta a su anterior
+        let mut removed = log.clone();
+        removed.remove(2);
+        let broken = verify_chain(&me.public_key, &cid, &removed).broken.unwrap();
+        assert_eq!((broken.position, broken.entry.key.as_str()), (2, "task"));
+        assert!(matches!(broken.reason, LinkError::PrevMismatch { .. }));
+
+        // cola truncada: sigue intacta, solo el head delata el borrado
+        let full = verify_chain(&me.public_key, &cid, &log);
+        let truncated = verify_chain(&me.public_key, &cid, &log[..3]);
+        assert!(truncated.is_intact());
+        assert_eq!(truncated.verified, 2);
+        assert_ne!(truncated.head, full.head);
+
+        // re-encadenada con otra llave
+        let other = Me::create(Arc::new(MemoryStore::new()), "mallory", "123456").await.unwrap();
+        let report = verify_chain(&other.public_key, &cid, &log);
+        assert_eq!(report.broken.unwrap().reason, LinkError::BadSignature);
+
+        // fila sin firma después del génesis
+        store.insert(Verb::Have, &cid, "forged", "row", "2999-01-01T00:00:00+00:00").await.unwrap();
+        let report = me.verify_log(&cid).await.unwrap();
+        assert_eq!(report.broken.unwrap().reason, LinkError::Unsigned);
+        assert_eq!(report.verified, 3);
+    }
+}
@@ -0,0 +1,179 @@
+//this.me/crate/src/core/context.rs
+// Derivación estructurada de `context_id` (ver el comentario de verbs.rs):
+//   me1                                  → .participant(me1)
+//   me1 + me2                            → .participant(me1).participant(me2)
+//   me1 + me2 + secret                   → ... .secret(b"...")
+//   wikipedia.org/page + cleaker:public  → ContextId::builder("cleaker:public").resource("wikipedia.org/page")
+//
+// El hash es canónico: participantes, secretos y recursos se ordenan y deduplican, así que
+// me1 + me2 == me2 + me1. Cada componente va con prefijo de longitud y sección, y el dominio
+// (tag) separa espacios que comparten componentes.
+use std::collections::BTreeSet;
+use std::fmt;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use sha2::{Sha256, Digest};
+use crate::utils::me_error::MeError;
+
+/// Prefijo de versión del preimage; cambiarlo cambia todos los ids.
+const CONTEXT_ID_V1: &[u8] = b"this.me/context/v1";
+
+/// `context_id` derivado (base64 de SHA-256, el mismo formato que `Me::context_id`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContextId(String);
+
+impl ContextId {
+    /// Empieza un contexto en el dominio `domain` (p. ej. `"public"`, `"private"`, `"cleaker:public"`).
+    pub fn builder(domain: &str) -> ContextIdBuilder {
+        ContextIdBuilder {
+            domain: domain.to_string(),
+            participants: BTreeSet::new(),
+            secrets: BTreeSet::new(),
+            resources: BTreeSet::new(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for ContextId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for ContextId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<ContextId> for String {
+    fn from(id: ContextId) -> Self {
+        id.0
+    }
+}
+
+/// Componentes de un `ContextId`. Los secretos se guardan ya hasheados: ni `Debug`
+/// ni el id resultante los exponen.
+#[derive(Debug, Clone)]
+pub struct ContextIdBuilder {
+    domain: String,
+    participants: BTreeSet<String>,
+    secrets: BTreeSet<[u8; 32]>,
+    resources: BTreeSet<String>,
+}
+
+impl ContextIdBuilder {
+    /// Una identidad del espacio: public key, username o alias (lo que el caller use en todas partes).
+    pub fn participant(mut self, participant: &str) -> Self {
+        self.participants.insert(participant.trim().to_string());
+        self
+    }
+
+    pub fn participants<'a>(mut self, participants: impl IntoIterator<Item = &'a str>) -> Self {
+        for p in participants {
+            self = self.participant(p);
+        }
+        self
+    }
+
+    /// Secreto compartido: vuelve privado un espacio que de otro modo cualquiera podría derivar.
+    pub fn secret(mut self, secret: &[u8]) -> Self {
+        self.secrets.insert(Sha256::digest(secret).into());
+        self
+    }
+
+    /// Recurso externo (`wikipedia.org/page`, una URL...). Se ignora un `/` final.
+    pub fn resource(mut self, uri: &str) -> Self {
+        let uri = uri.trim();
+        self.resources.insert(uri.strip_suffix('/').unwrap_or(uri).to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<ContextId, MeError> {
+        if self.domain.trim().is_empty() {
+            return Err(MeError::Validation("Context domain cannot be empty".into()));
+        }
+        if self.participants.is_empty() && self.resources.is_empty() {
+            return Err(MeError::Validation("A context needs at least one participant or resource".into()));
+        }
+        if self.participants.iter().chain(&self.resources).any(|c| c.is_empty()) {
+            return Err(MeError::Validation("Context participants and resources cannot be empty".into()));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(CONTEXT_ID_V1);
+        section(&mut hasher, b"domain", std::iter::once(self.domain.as_bytes()));
+        section(&mut hasher, b"participant", self.participants.iter().map(|p| p.as_bytes()));
+        section(&mut hasher, b"secret", self.secrets.iter().map(|s| &s[..]));
+        section(&mut hasher, b"resource", self.resources.iter().map(|r| r.as_bytes()));
+        Ok(ContextId(STANDARD.encode(hasher.finalize())))
+    }
+}
+
+/// nombre ‖ cantidad ‖ (longitud ‖ bytes)*, todo con prefijos u32 big-endian para que
+/// "a" + "bc" y "ab" + "c" no colisionen.
+fn section<'a>(hasher: &mut Sha256, name: &[u8], items: impl ExactSizeIterator<Item = &'a [u8]>) {
+    put(hasher, name);
+    hasher.update((items.len() as u32).to_be_bytes());
+    for item in items {
+        put(hasher, item);
+    }
+}
+
+fn put(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u32).to_be_bytes());
+    hasher.update(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(b: ContextIdBuilder) -> String {
+        b.build().unwrap().into_string()
+    }
+
+    #[test]
+    fn hashing_is_canonical_and_separated() {
+        let pair = id(ContextId::builder("shared").participant("me1").participant("me2"));
+        assert_eq!(pair, id(ContextId::builder("shared").participants(["me2", " me1", "me2"])));
+        assert_ne!(pair, id(ContextId::builder("private").participants(["me1", "me2"])));
+
+        let secret = id(ContextId::builder("shared").participants(["me1", "me2"]).secret(b"s3cr3t"));
+        assert_ne!(pair, secret);
+        assert_ne!(secret, id(ContextId::builder("shared").participants(["me1", "me2"]).secret(b"other")));
+
+        // límites entre componentes y entre secciones
+        assert_ne!(
+            id(ContextId::builder("shared").participants(["a", "bc"])),
+            id(ContextId::builder("shared").participants(["ab", "c"]))
+        );
+        assert_ne!(
+            id(ContextId::builder("x").participant("wikipedia.org/page")),
+            id(ContextId::builder("x").resource("wikipedia.org/page"))
+        );
+        assert_eq!(
+            id(ContextId::builder("cleaker:public").resource("wikipedia.org/page/")),
+            id(ContextId::builder("cleaker:public").resource("wikipedia.org/page"))
+        );
+    }
+
+    #[test]
+    fn rejects_empty_contexts_and_hides_secrets() {
+        assert!(ContextId::builder("shared").build().is_err());
+        assert!(ContextId::builder("").participant("me1").build().is_err());
+        assert!(ContextId::builder("shared").participant("  ").build().is_err());
+        assert!(ContextId::builder("shared").secret(b"only a secret").build().is_err());
+
+        let debug = format!("{:?}", ContextId::builder("shared").participant("me1").secret(b"hunter2"));
+        assert!(!debug.contains("hunter2"));
+    }
+}
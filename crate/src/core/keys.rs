@@ -0,0 +1,58 @@
+//this.me/crate/src/core/keys.rs
+// Llaves por autoridad (next.md): `main` es la llave de la identidad (tabla `me`),
+// las demás (cleaker, networkX, ...) viven en la tabla `keys`, cifradas con el mismo password.
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Sha256, Digest};
+use crate::utils::me_error::MeError;
+
+/// Autoridad de la llave principal. Obligatoria y no se puede borrar.
+pub const MAIN_AUTHORITY: &str = "main";
+
+/// Par de llaves ed25519 de una autoridad.
+#[derive(Clone)]
+pub struct KeyPair {
+    pub signing_key: SigningKey,
+}
+
+impl KeyPair {
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        Self::from_secret(&secret)
+    }
+
+    pub fn from_secret(secret: &[u8; 32]) -> Self {
+        Self { signing_key: SigningKey::from_bytes(secret) }
+    }
+
+    /// Lee la privada en base64 (el formato de `private_key_raw` y de los blobs cifrados).
+    pub fn from_base64(private_key_b64: &str) -> Result<Self, MeError> {
+        let bytes = STANDARD.decode(private_key_b64)?;
+        let secret: [u8; 32] = bytes.as_slice().try_into().map_err(|_| MeError::CorruptData("invalid secret key length".into()))?;
+        Ok(Self::from_secret(&secret))
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn public_key_base64(&self) -> String {
+        STANDARD.encode(self.verifying_key().to_bytes())
+    }
+
+    pub fn private_key_base64(&self) -> String {
+        STANDARD.encode(self.signing_key.to_bytes())
+    }
+}
+
+/// Clave primaria de la fila en `keys` para (username, authority).
+pub fn key_context_id(username: &str, authority: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(username.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(authority.as_bytes());
+    STANDARD.encode(hasher.finalize())
+}
@@ -0,0 +1,113 @@
+//this.me/crate/src/core/links.rs
+// Historial de contextos de una identidad (entradas, cursor y bookmarks) como dato puro.
+// `Link::ContextStack` lo guarda en ~/.this/me/<alias>/links.json y `core::bundle` lo
+// transporta entre máquinas; aquí no se toca el sistema de archivos.
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// Entradas que se conservan; al pasarse se descartan las más viejas.
+pub const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("Link history I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Corrupt link history: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Corrupt link history: cursor {cursor} is past {len} entries")]
+    CursorOutOfRange { cursor: usize, len: usize },
+    #[error("No HOME directory")]
+    NoHome,
+    #[error("Context path cannot be empty")]
+    EmptyPath,
+    #[error("Invalid bookmark name '{0}'")]
+    InvalidBookmark(String),
+    #[error("No bookmark named '{0}'")]
+    UnknownBookmark(String),
+    #[error("No context linked yet")]
+    NothingLinked,
+    #[error("No previous context")]
+    NoPrevious,
+    #[error("No next context")]
+    NoNext,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkEntry {
+    pub path: String,
+    pub linked_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub path: String,
+    pub created_at: String,
+}
+
+/// Contenido de links.json.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct History {
+    pub entries: Vec<LinkEntry>,
+    /// Índice del contexto activo en `entries` (`None` si no hay ninguno).
+    pub cursor: Option<usize>,
+    #[serde(default)]
+    pub bookmarks: BTreeMap<String, Bookmark>,
+}
+
+impl History {
+    /// El cursor tiene que apuntar a una entrada (el archivo o el bundle pueden venir editados).
+    pub(crate) fn check(&self) -> Result<(), HistoryError> {
+        match self.cursor {
+            Some(cursor) if cursor >= self.entries.len() => {
+                Err(HistoryError::CursorOutOfRange { cursor, len: self.entries.len() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn current(&self) -> Option<&LinkEntry> {
+        self.cursor.and_then(|i| self.entries.get(i))
+    }
+
+    pub(crate) fn link(&mut self, path: &str) -> LinkEntry {
+        if let Some(current) = self.current().filter(|c| c.path == path) {
+            return current.clone();
+        }
+        let keep = self.cursor.map_or(0, |i| i + 1);
+        self.entries.truncate(keep);
+        self.entries.push(LinkEntry { path: path.to_string(), linked_at: chrono::Utc::now().to_rfc3339() });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+        self.cursor = Some(self.entries.len() - 1);
+        self.entries[self.entries.len() - 1].clone()
+    }
+
+    pub(crate) fn step(&mut self, forward: bool) -> Result<LinkEntry, HistoryError> {
+        let cursor = self.cursor.ok_or(HistoryError::NothingLinked)?;
+        let next = if forward {
+            Some(cursor + 1).filter(|i| *i < self.entries.len()).ok_or(HistoryError::NoNext)?
+        } else {
+            cursor.checked_sub(1).ok_or(HistoryError::NoPrevious)?
+        };
+        let entry = self.entries.get(next).cloned().ok_or(HistoryError::NoPrevious)?;
+        self.cursor = Some(next);
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_entries() {
+        let mut history = History::default();
+        for i in 0..MAX_ENTRIES + 5 {
+            history.link(&i.to_string());
+        }
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert_eq!(history.entries[0].path, "5");
+        assert_eq!(history.current().unwrap().path, (MAX_ENTRIES + 4).to_string());
+    }
+}
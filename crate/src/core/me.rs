@@ -1,57 +1,107 @@
 //this.me/crate/src/core/me.rs
+use std::collections::HashMap;
 use std::sync::Arc;
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD;
 use sha2::{Sha256, Digest};
-use chrono::Utc;
-use ed25519_dalek::{SigningKey, SecretKey, VerifyingKey};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, SecretKey, VerifyingKey};
 use rand::{rngs::OsRng, RngCore};
 use std::convert::TryFrom;
 use super::store::MeStore;
-use super::model::{Entry, GetFilter};
+use super::model::{Entry, GetFilter, Proof};
+use super::chain::{self, LogReport};
+use super::verbs::Verb;
+use super::ontology::{self, Declaration, Ontology, OntologyError};
+use super::arc::{self, ArcEntry, ArcScores, ArcTrend};
+use super::query::Query;
+use super::keys::{KeyPair, MAIN_AUTHORITY};
+use super::mnemonic;
+use super::bundle::{Bundle, BundleContents, BundleMetadata, ContextRecord, IdentityRecord, KeyRecord};
+use super::recovery::{self, Delivery, Guardian, GuardianRecord, RecoveryShare, SealedShare, RECOVERY_KEY_PREFIX};
+use super::sign::DetachedSignature;
+use crate::utils::crypto::CryptoError;
+use crate::utils::kdf::{self, Kdf};
+use crate::utils::me_error::MeError;
+use super::links::History;
+
+/// Key de la entrada que `change_password` deja en el log de la identidad.
+pub const PASSWORD_CHANGED: &str = "me:password_changed";
+/// Key de la entrada que `restore_from_mnemonic` y `recover_from_shares` dejan en el log de la identidad.
+pub const RESTORED: &str = "me:restored";
 
 pub struct Me<S: MeStore> {
     pub username: String,
     pub public_key: String,
     pub context_id: String,
-    #[allow(dead_code)]
     private_key_raw: String,
+    /// Llaves de otras autoridades, ya descifradas (sin `main`).
+    keys: HashMap<String, KeyPair>,
+    /// Serializa leer la cabeza de la cadena + insertar, para no bifurcar el log desde este proceso.
+    chain_lock: tokio::sync::Mutex<()>,
+    /// Verbos secundarios que entiende `declare` (por defecto la ontología del crate).
+    ontology: Arc<Ontology>,
     pub store: Arc<S>,
 }
 
 impl<S: MeStore> Me<S> {
     pub fn with_store(username: String, public_key: String, private_key_raw: String, store: Arc<S>) -> Self {
-        // context_id derivado del private_key (como tenías)
+        // context_id derivado del private_key (como tenías). Se mantiene: las filas existentes viven
+        // bajo este id. Los espacios compartidos se derivan con `core::context::ContextId`.
         let mut hasher = Sha256::new();
         hasher.update(&private_key_raw);
         let context_id = STANDARD.encode(hasher.finalize());
 
-        Self { username, public_key, context_id, private_key_raw, store }
+        Self { username, public_key, context_id, private_key_raw, keys: HashMap::new(), chain_lock: tokio::sync::Mutex::new(()), ontology: Ontology::builtin(), store }
     }
 
+    /// Crea y persiste una identidad nueva. Si el username ya existe falla con `MeError::AlreadyExists`
+    /// (nunca devuelve un `Me` con llaves que no quedaron guardadas).
     pub async fn create(
         store: Arc<S>,
         username: &str,
         password: &str,
-    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<Self, MeError> {
+        Self::create_with_kdf(store, username, password, Kdf::default()).await
+    }
+
+    /// Crea la identidad o, si el username ya existe, la carga con `password`.
+    /// Seguro ante creaciones concurrentes: el store decide quién gana y el resto carga esa identidad
+    /// (si el password no coincide, `MeError::WrongPassword`).
+    pub async fn claim_or_load(store: Arc<S>, username: &str, password: &str) -> Result<Self, MeError> {
+        Self::claim_or_load_with_kdf(store, username, password, Kdf::default()).await
+    }
+
+    /// Igual que `claim_or_load`; `kdf` solo se usa si esta llamada crea la identidad.
+    pub async fn claim_or_load_with_kdf(store: Arc<S>, username: &str, password: &str, kdf: Kdf) -> Result<Self, MeError> {
+        match Self::create_with_kdf(store.clone(), username, password, kdf).await {
+            Err(MeError::AlreadyExists(_)) => Self::load(store, username, password).await,
+            created => created,
+        }
+    }
+
+    /// Igual que `create`, eligiendo el KDF y su costo (p. ej. `Kdf::pbkdf2()` en equipos con poca memoria).
+    pub async fn create_with_kdf(
+        store: Arc<S>,
+        username: &str,
+        password: &str,
+        kdf: Kdf,
+    ) -> Result<Self, MeError> {
         // 1) Generar secret 32 bytes y llaves ed25519
         let mut csprng = OsRng {};
         let mut secret_bytes = [0u8; 32];
         csprng.fill_bytes(&mut secret_bytes);
 
         let secret = SecretKey::try_from(&secret_bytes[..])
-            .map_err(|_| "Invalid secret key")?;
+            .map_err(|_| MeError::Crypto(CryptoError::InvalidKeyLength))?;
         let signing_key = SigningKey::from(&secret);
         let verify_key = VerifyingKey::from(&signing_key);
 
         let public_key = STANDARD.encode(verify_key.to_bytes());
         let private_key_raw = STANDARD.encode(signing_key.to_bytes()); // en claro (base64)
 
-        // 2) Derivar clave y cifrar privada
-        let key = Self::derive_key(username, password)?;
-        let encoded_key = STANDARD.encode(&key);
-        let encrypted_binary = crate::utils::crypto::encrypt_string(&encoded_key, &private_key_raw)?;
-        let encrypted_private_key = STANDARD.encode(&encrypted_binary);
+        // 2) Cifrar privada con clave derivada del password (salt aleatorio por identidad)
+        let encrypted_private_key = kdf::seal(password, &private_key_raw, kdf)?;
 
         // 3) Persistir en el store
         store.create_identity(username, &public_key, &encrypted_private_key).await?;
@@ -65,37 +115,322 @@ impl<S: MeStore> Me<S> {
         ))
     }
 
-    pub async fn create_identity(store: Arc<S>, username: &str, encrypted_private_key: &str, public_key: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn create_identity(store: Arc<S>, username: &str, encrypted_private_key: &str, public_key: &str) -> Result<(), MeError> {
         store.create_identity(username, public_key, encrypted_private_key).await
     }
 
-    pub async fn load(store: Arc<S>, username: &str, password: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn load(store: Arc<S>, username: &str, password: &str) -> Result<Self, MeError> {
+        let (public_key, private_key_raw) = Self::unlock_main(&store, username, password).await?;
+        // llaves de otras autoridades, cifradas con el mismo password
+        let mut keys = HashMap::new();
+        for authority in store.list_keys(username).await? {
+            let (public, encrypted) = store.load_key(username, &authority).await?;
+            let keypair = KeyPair::from_base64(&kdf::open(password, &encrypted).map_err(MeError::from_unseal)?)?;
+            if keypair.public_key_base64() != public {
+                return Err(MeError::CorruptData(format!("key '{}' does not match its public key", authority)));
+            }
+            keys.insert(authority, keypair);
+        }
+        let mut me = Self::with_store(username.to_string(), public_key, private_key_raw, store);
+        me.keys = keys;
+        Ok(me)
+    }
+
+    /// Descifra la llave `main` → (public_key, private_key_raw).
+    async fn unlock_main(store: &S, username: &str, password: &str) -> Result<(String, String), MeError> {
         // 1) store.load_keys -> (public_key, encrypted_private_key)
         let (public_key, encrypted_private_key) = store.load_keys(username).await?;
-        // 2) derivar clave y desencriptar (igual que en tu código actual)
-        let key = Self::derive_key(username, password)?;
-        let encoded_key = STANDARD.encode(&key);
-        let private_key_raw = crate::utils::crypto::decrypt_string(&encoded_key, &STANDARD.decode(encrypted_private_key)?)?;
-        Ok(Self::with_store(username.to_string(), public_key, private_key_raw, store))
-    }
-
-    pub async fn change_password(
-        &self,
-        new_password: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let key = Self::derive_key(&self.username, new_password)?;
-        let encoded_key = STANDARD.encode(&key);
-        let encrypted_binary = crate::utils::crypto::encrypt_string(&encoded_key, &self.private_key_raw)?;
-        let new_encrypted = STANDARD.encode(&encrypted_binary);
+        // 2) desencriptar según el formato guardado
+        let private_key_raw = if kdf::is_legacy(&encrypted_private_key) {
+            let key = Self::legacy_derive_key(username, password)?;
+            let encoded_key = STANDARD.encode(&key);
+            let private_key_raw = crate::utils::crypto::decrypt_string(&encoded_key, &STANDARD.decode(&encrypted_private_key)?)
+                .map_err(MeError::from_unseal)?;
+            // 3) migración transparente: el password ya es correcto, re-sellar con el KDF actual.
+            // Best-effort: un store de solo lectura no debe impedir cargar la identidad.
+            if let Ok(sealed) = kdf::seal(password, &private_key_raw, Kdf::default()) {
+                let _ = store.update_encrypted_private(username, &sealed).await;
+            }
+            private_key_raw
+        } else {
+            kdf::open(password, &encrypted_private_key).map_err(MeError::from_unseal)?
+        };
+        Ok((public_key, private_key_raw))
+    }
+
+    /// Comprueba el password contra la llave `main` guardada.
+    async fn verify_password(&self, password: &str) -> Result<(), MeError> {
+        let (_, private_key_raw) = Self::unlock_main(&self.store, &self.username, password).await?;
+        if private_key_raw != self.private_key_raw {
+            return Err(MeError::CorruptData("stored identity key does not match the loaded one".into()));
+        }
+        Ok(())
+    }
 
+    // ----- Llaves por autoridad -----
+    /// Agrega la llave de otra autoridad (cleaker, networkX...), cifrada con el password de la identidad.
+    pub async fn add_key(&mut self, authority: &str, keypair: KeyPair, password: &str) -> Result<(), MeError> {
+        if authority == MAIN_AUTHORITY {
+            return Err(MeError::Validation("The main key is created with the identity and cannot be replaced".into()));
+        }
+        if authority.is_empty() {
+            return Err(MeError::Validation("Authority name cannot be empty".into()));
+        }
+        if self.keys.contains_key(authority) {
+            return Err(MeError::KeyExists(authority.to_string()));
+        }
+        self.verify_password(password).await?;
+
+        let sealed = kdf::seal(password, &keypair.private_key_base64(), Kdf::default())?;
         self.store
-            .update_encrypted_private(&self.username, &new_encrypted)
+            .insert_key(&self.username, authority, &keypair.public_key_base64(), &sealed)
             .await?;
+        self.keys.insert(authority.to_string(), keypair);
+        Ok(())
+    }
 
+    /// Borra la llave de una autoridad. `main` no se puede borrar.
+    pub async fn delete_key(&mut self, authority: &str) -> Result<(), MeError> {
+        if authority == MAIN_AUTHORITY {
+            return Err(MeError::Validation("The main key cannot be deleted".into()));
+        }
+        if !self.keys.contains_key(authority) {
+            return Err(MeError::KeyNotFound(authority.to_string()));
+        }
+        self.store.delete_key(&self.username, authority).await?;
+        self.keys.remove(authority);
         Ok(())
     }
 
-    fn derive_key(username: &str, password: &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    /// Firma con la llave `main` de la identidad.
+    pub fn sign(&self, message: &[u8]) -> Result<Signature, MeError> {
+        self.sign_with(MAIN_AUTHORITY, message)
+    }
+
+    /// Firma separada, con username y public key del firmante; `None` = `main`.
+    pub fn sign_detached(&self, authority: Option<&str>, message: &[u8]) -> Result<DetachedSignature, MeError> {
+        let authority = authority.unwrap_or(MAIN_AUTHORITY);
+        let signature = self.sign_with(authority, message)?;
+        let public_key = self.get_public_key(Some(authority))?;
+        Ok(DetachedSignature::new(&self.username, authority, &public_key, &signature))
+    }
+
+    /// Firma con la llave de `authority` (`"main"` para la llave de la identidad).
+    pub fn sign_with(&self, authority: &str, message: &[u8]) -> Result<Signature, MeError> {
+        Ok(self.keypair(authority)?.signing_key.sign(message))
+    }
+
+    /// Public key (base64) de `authority`; `None` = `main`.
+    pub fn get_public_key(&self, authority: Option<&str>) -> Result<String, MeError> {
+        match authority.unwrap_or(MAIN_AUTHORITY) {
+            MAIN_AUTHORITY => Ok(self.public_key.clone()),
+            other => Ok(self.keypair(other)?.public_key_base64()),
+        }
+    }
+
+    /// Autoridades con llave: `main` primero, luego el resto en orden alfabético.
+    pub fn list_keys(&self) -> Vec<String> {
+        let mut others: Vec<String> = self.keys.keys().cloned().collect();
+        others.sort();
+        std::iter::once(MAIN_AUTHORITY.to_string()).chain(others).collect()
+    }
+
+    fn keypair(&self, authority: &str) -> Result<KeyPair, MeError> {
+        match authority {
+            MAIN_AUTHORITY => KeyPair::from_base64(&self.private_key_raw),
+            other => self
+                .keys
+                .get(other)
+                .cloned()
+                .ok_or_else(|| MeError::KeyNotFound(other.to_string())),
+        }
+    }
+
+    /// Cambia el password: exige el actual, vuelve a cifrar la llave `main` y todas las de `keys`
+    /// y las guarda en una sola transacción (`MeStore::rewrap_keys`). Después deja en el log del
+    /// contexto de la identidad una entrada firmada `do` `me:password_changed`.
+    /// Si otra escritura agrega una llave mientras tanto, `MeError::KeysChanged` (si la borra, `KeyNotFound`); nada cambia.
+    pub async fn change_password(&self, old_password: &str, new_password: &str) -> Result<(), MeError> {
+        self.change_password_with_kdf(old_password, new_password, Kdf::default()).await
+    }
+
+    /// Igual que `change_password`, eligiendo el KDF del nuevo cifrado.
+    pub async fn change_password_with_kdf(&self, old_password: &str, new_password: &str, kdf: Kdf) -> Result<(), MeError> {
+        // una sesión desbloqueada no basta: el password actual tiene que abrir la llave guardada
+        self.verify_password(old_password).await?;
+        let main = kdf::seal(new_password, &self.private_key_raw, kdf)?;
+        // las llaves se leen del store (no de `self.keys`) para no dejar ninguna con el password viejo
+        let mut keys = Vec::new();
+        for authority in self.store.list_keys(&self.username).await? {
+            let (_, encrypted) = self.store.load_key(&self.username, &authority).await?;
+            let private_key = kdf::open(old_password, &encrypted).map_err(MeError::from_unseal)?;
+            keys.push((authority, kdf::seal(new_password, &private_key, kdf)?));
+        }
+        self.store.rewrap_keys(&self.username, &main, &keys).await?;
+
+        let authorities: Vec<&str> = std::iter::once(MAIN_AUTHORITY).chain(keys.iter().map(|(a, _)| a.as_str())).collect();
+        let event = serde_json::json!({ "keys": authorities }).to_string();
+        self.append(&self.context_id, Verb::Do, PASSWORD_CHANGED, &event, None).await
+    }
+
+    // ----- Respaldo (core::mnemonic) -----
+    /// Frase BIP39 de 24 palabras con el seed de la llave `main`. Pide el password: una sesión
+    /// abierta no basta para sacar la llave.
+    pub async fn export_mnemonic(&self, password: &str) -> Result<String, MeError> {
+        self.verify_password(password).await?;
+        Ok(mnemonic::seed_to_mnemonic(&self.seed()?))
+    }
+
+    fn seed(&self) -> Result<[u8; 32], MeError> {
+        STANDARD
+            .decode(&self.private_key_raw)?
+            .try_into()
+            .map_err(|_| MeError::CorruptData("invalid secret key length".into()))
+    }
+
+    /// Reconstruye la identidad desde la frase de `export_mnemonic`, cifrada con `new_password`:
+    /// misma public key y mismo `context_id`.
+    /// - Sin fila para `username` (base perdida), la crea.
+    /// - Con la fila de esta misma llave (password olvidado), re-cifra `main` y borra las llaves de
+    ///   otras autoridades, que estaban cifradas con el password perdido y ya no se pueden abrir.
+    /// - Con otra llave bajo ese username, `MeError::AlreadyExists`.
+    ///
+    /// Deja en el log una entrada firmada `do` `me:restored` con las autoridades borradas.
+    pub async fn restore_from_mnemonic(store: Arc<S>, username: &str, words: &str, new_password: &str) -> Result<Self, MeError> {
+        Self::restore_from_mnemonic_with_kdf(store, username, words, new_password, Kdf::default()).await
+    }
+
+    /// Igual que `restore_from_mnemonic`, eligiendo el KDF.
+    pub async fn restore_from_mnemonic_with_kdf(store: Arc<S>, username: &str, words: &str, new_password: &str, kdf: Kdf) -> Result<Self, MeError> {
+        let seed = mnemonic::mnemonic_to_seed(words)?;
+        Self::restore_seed(store, username, &seed, new_password, kdf, "mnemonic").await
+    }
+
+    /// Común a `restore_from_mnemonic` y `recover_from_shares`; `via` queda en la entrada `me:restored`.
+    async fn restore_seed(store: Arc<S>, username: &str, seed: &[u8; 32], new_password: &str, kdf: Kdf, via: &str) -> Result<Self, MeError> {
+        let keypair = KeyPair::from_secret(seed);
+        let (public_key, private_key_raw) = (keypair.public_key_base64(), keypair.private_key_base64());
+        let sealed = kdf::seal(new_password, &private_key_raw, kdf)?;
+
+        let mut dropped = Vec::new();
+        match store.create_identity(username, &public_key, &sealed).await {
+            Ok(()) => {}
+            Err(MeError::AlreadyExists(_)) => {
+                let (stored_public, _) = store.load_keys(username).await?;
+                if stored_public != public_key {
+                    return Err(MeError::AlreadyExists(username.to_string()));
+                }
+                // primero `main`: si algo falla después, repetir el restore termina el trabajo
+                store.update_encrypted_private(username, &sealed).await?;
+                for authority in store.list_keys(username).await? {
+                    store.delete_key(username, &authority).await?;
+                    dropped.push(authority);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        let me = Self::with_store(username.to_string(), public_key, private_key_raw, store);
+        let event = serde_json::json!({ "via": via, "dropped_keys": dropped }).to_string();
+        me.append(&me.context_id, Verb::Do, RESTORED, &event, None).await?;
+        Ok(me)
+    }
+
+    // ----- Bundle portable (core::bundle) -----
+    /// Empaqueta la identidad (llaves tal como están cifradas en el store, contextos con su log y
+    /// ARC, y `links` si se pasa) en un `Bundle` firmado y cifrado con `passphrase`.
+    /// Se importa con `Bundle::open` + `BundleContents::import_into` en cualquier `MeStore`.
+    pub async fn export_bundle(&self, passphrase: &str, links: Option<History>) -> Result<Bundle, MeError> {
+        self.export_bundle_with_kdf(passphrase, links, Kdf::default()).await
+    }
+
+    /// Igual que `export_bundle`, eligiendo el KDF de la passphrase.
+    pub async fn export_bundle_with_kdf(&self, passphrase: &str, links: Option<History>, kdf: Kdf) -> Result<Bundle, MeError> {
+        let (public_key, encrypted_private_key) = self.store.load_keys(&self.username).await?;
+        let identity = IdentityRecord { username: self.username.clone(), public_key, encrypted_private_key };
+        let mut keys = Vec::new();
+        for authority in self.store.list_keys(&self.username).await? {
+            let (public_key, encrypted_private_key) = self.store.load_key(&self.username, &authority).await?;
+            keys.push(KeyRecord { authority, public_key, encrypted_private_key });
+        }
+
+        let mut contexts = Vec::new();
+        for context_id in self.store.contexts().await? {
+            let entries = self.store.log(&context_id).await?;
+            // contextos de otros (p. ej. un PgStore compartido) no tienen nada firmado por esta llave
+            if context_id != self.context_id && chain::verify_chain(&self.public_key, &context_id, &entries).verified == 0 {
+                continue;
+            }
+            let arc = self.store.arc_log(&context_id).await?;
+            contexts.push(ContextRecord { context_id, entries, arc });
+        }
+        let metadata = BundleMetadata {
+            exported_at: Utc::now().to_rfc3339(),
+            exporter: env!("CARGO_PKG_VERSION").to_string(),
+            entries: contexts.iter().map(|c| c.entries.len()).sum(),
+            arc_samples: contexts.iter().map(|c| c.arc.len()).sum(),
+        };
+        let contents = BundleContents { identity, keys, contexts, links, metadata };
+        Bundle::seal(&contents, &self.keypair(MAIN_AUTHORITY)?, passphrase, kdf)
+    }
+
+    // ----- Recuperación social (core::recovery) -----
+    /// Parte el seed de `main` en un share por guardián, de los que bastan `threshold` para
+    /// `recover_from_shares`. Pide el password, como `export_mnemonic`.
+    /// Cada guardián queda en el log como entrada firmada `relate` `recovery:<guardián>` (sin el share);
+    /// un reparto nuevo reemplaza al anterior en `recovery_set`, pero los shares viejos siguen
+    /// sirviendo mientras la llave sea la misma.
+    pub async fn create_recovery_set(&self, password: &str, threshold: u8, guardians: &[Guardian]) -> Result<Vec<Delivery>, MeError> {
+        self.verify_password(password).await?;
+        let total = u8::try_from(guardians.len())
+            .map_err(|_| MeError::Validation("A recovery set holds at most 255 guardians".into()))?;
+        let mut names = std::collections::HashSet::new();
+        if let Some(repeated) = guardians.iter().find(|g| !names.insert(g.name())) {
+            return Err(MeError::Validation(format!("Guardian '{}' is listed twice", repeated.name())));
+        }
+        let shares = recovery::split(&self.seed()?, &self.public_key, threshold, total)?;
+
+        // se sella todo antes de escribir: una public key inválida no deja el reparto a medias en el log
+        let deliveries = guardians
+            .iter()
+            .zip(&shares)
+            .map(|(guardian, share)| match guardian {
+                Guardian::Identity { username, public_key } => recovery::seal_share(share, username, public_key).map(Delivery::Sealed),
+                Guardian::Printed { label } => Ok(Delivery::Printed { label: label.clone(), share: share.clone() }),
+            })
+            .collect::<Result<Vec<_>, MeError>>()?;
+        for (guardian, share) in guardians.iter().zip(&shares) {
+            let record = GuardianRecord::new(guardian, share).to_value()?;
+            let key = format!("{}{}", RECOVERY_KEY_PREFIX, guardian.name());
+            self.append(&self.context_id, Verb::Relate, &key, &record, None).await?;
+        }
+        Ok(deliveries)
+    }
+
+    /// Guardianes del último reparto de `create_recovery_set`, ordenados por índice.
+    pub async fn recovery_set(&self) -> Result<Vec<GuardianRecord>, MeError> {
+        Ok(recovery::recovery_set(&self.store.log(&self.context_id).await?))
+    }
+
+    /// Abre, como guardián, un share sellado a la llave `main` de esta identidad.
+    pub fn open_recovery_share(&self, sealed: &SealedShare) -> Result<RecoveryShare, MeError> {
+        recovery::open_share(sealed, &self.keypair(MAIN_AUTHORITY)?)
+    }
+
+    /// Reconstruye la identidad con `threshold` shares y la deja cifrada con `new_password`.
+    /// Mismos casos que `restore_from_mnemonic`; la entrada `me:restored` lleva `"via":"shares"`.
+    pub async fn recover_from_shares(store: Arc<S>, username: &str, shares: &[RecoveryShare], new_password: &str) -> Result<Self, MeError> {
+        Self::recover_from_shares_with_kdf(store, username, shares, new_password, Kdf::default()).await
+    }
+
+    /// Igual que `recover_from_shares`, eligiendo el KDF.
+    pub async fn recover_from_shares_with_kdf(store: Arc<S>, username: &str, shares: &[RecoveryShare], new_password: &str, kdf: Kdf) -> Result<Self, MeError> {
+        let seed = recovery::combine(shares)?;
+        Self::restore_seed(store, username, &seed, new_password, kdf, "shares").await
+    }
+
+    /// Derivación original (SHA-256 de username‖password), solo para leer identidades sin migrar.
+    fn legacy_derive_key(username: &str, password: &str) -> Result<Vec<u8>, MeError> {
         let mut hasher = Sha256::new();
         hasher.update(username.as_bytes());
         hasher.update(password.as_bytes());
@@ -103,34 +438,339 @@ impl<S: MeStore> Me<S> {
     }
 
     // ----- Verbos -----
-    pub async fn be(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("be", context_id, key, value).await
+    pub async fn be(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::Be, context_id, key, value).await
+    }
+    pub async fn have(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::Have, context_id, key, value).await
+    }
+    pub async fn do_(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::Do, context_id, key, value).await
+    }
+    pub async fn at(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::At, context_id, key, value).await
     }
-    pub async fn have(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("have", context_id, key, value).await
+    pub async fn relate(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::Relate, context_id, key, value).await
+    }
+    pub async fn react(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::React, context_id, key, value).await
+    }
+    pub async fn communicate(&self, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.insert(Verb::Communicate, context_id, key, value).await
+    }
+
+    /// Cualquier verbo primario (lo que usan be/have/... y el CLI cuando el verbo llega como texto).
+    pub async fn insert(&self, verb: Verb, context_id: &str, key: &str, value: &str) -> Result<(), MeError> {
+        self.append(context_id, verb, key, value, None).await
     }
-    pub async fn do_(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("do_", context_id, key, value).await
+
+    /// Inserta la entrada encadenada a la cabeza del contexto y firmada con la llave `main`.
+    async fn append(&self, context_id: &str, verb: Verb, key: &str, value: &str, correlation_id: Option<&str>) -> Result<(), MeError> {
+        let _guard = self.chain_lock.lock().await;
+        let head = self.store.log_head(context_id).await?;
+
+        let mut now = Utc::now();
+        // el log se ordena por timestamp: la nueva entrada nunca puede quedar antes que la cabeza
+        if let Some(head_ts) = head.as_ref().and_then(|h| DateTime::parse_from_rfc3339(&h.timestamp).ok()) {
+            let head_ts = head_ts.with_timezone(&Utc);
+            if now <= head_ts {
+                now = head_ts + Duration::nanoseconds(1);
+            }
+        }
+        let mut entry = Entry::new(verb, key, value, &now.to_rfc3339());
+        entry.correlation_id = correlation_id.map(str::to_string);
+
+        let prev_hash = head.and_then(|h| h.proof).map(|p| p.hash).unwrap_or_default();
+        let hash = chain::entry_hash(context_id, &entry, &prev_hash);
+        let signature = STANDARD.encode(self.sign(hash.as_bytes())?.to_bytes());
+        entry.proof = Some(Proof { hash, prev_hash, signature });
+        self.store.insert_signed(context_id, &entry).await
     }
-    pub async fn at(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("at", context_id, key, value).await
+
+    // ----- Verbos secundarios (core::ontology) -----
+    pub fn ontology(&self) -> &Ontology {
+        &self.ontology
     }
-    pub async fn relate(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("relate", context_id, key, value).await
+
+    /// Reemplaza la ontología (p. ej. `Ontology::from_path` con el archivo del equipo).
+    pub fn set_ontology(&mut self, ontology: Ontology) {
+        self.ontology = Arc::new(ontology);
     }
-    pub async fn react(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("react", context_id, key, value).await
+
+    /// Declara un verbo secundario: una fila por primario que lo compone, todas con el mismo
+    /// `correlation_id`, que se devuelve.
+    pub async fn declare(&self, context_id: &str, secondary: &str, key: &str, value: &str) -> Result<String, MeError> {
+        let verbs = self.ontology.expand(secondary)?.to_vec();
+        let correlation_id = ontology::new_correlation_id(secondary);
+        for verb in verbs {
+            self.append(context_id, verb, key, value, Some(&correlation_id)).await?;
+        }
+        Ok(correlation_id)
     }
-    pub async fn communicate(&self, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.insert("communicate", context_id, key, value).await
+
+    /// Declaraciones de `secondary` en el contexto, de la más reciente a la más antigua.
+    pub async fn declared(&self, context_id: &str, secondary: &str) -> Result<Vec<Declaration>, MeError> {
+        let definition = self
+            .ontology
+            .secondary(secondary)
+            .ok_or_else(|| OntologyError::UnknownSecondary(secondary.to_string()))?;
+        let entries = self.store.log(context_id).await?;
+        Ok(ontology::assemble(definition, &entries))
     }
 
-    async fn insert(&self, verb: &str, context_id: &str, key: &str, value: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let ts = Utc::now().to_rfc3339();
-        self.store.insert(verb, context_id, key, value, &ts).await
+    // ----- ARC (core::arc) -----
+    /// Registra una muestra de affinity/reality/communication de `actor` hacia `target`.
+    pub async fn arc(&self, context_id: &str, actor: &str, target: &str, affinity: f64, reality: f64, communication: f64) -> Result<(), MeError> {
+        if actor.trim().is_empty() || target.trim().is_empty() {
+            return Err(MeError::Validation("ARC actor and target cannot be empty".into()));
+        }
+        let scores = ArcScores::new(affinity, reality, communication);
+        scores.validate()?;
+        let entry = ArcEntry { actor: actor.to_string(), target: target.to_string(), scores, timestamp: Utc::now().to_rfc3339() };
+        self.store.insert_arc(context_id, &entry).await
     }
 
-    pub async fn get(&self, filter: &GetFilter) -> Result<Vec<Entry>, Box<dyn std::error::Error + Send + Sync>> {
+    /// Línea de tiempo de `actor` hacia `target`, de la muestra más antigua a la más reciente.
+    pub async fn arc_timeline(&self, context_id: &str, actor: &str, target: &str, since: Option<&str>, until: Option<&str>) -> Result<Vec<ArcEntry>, MeError> {
+        self.store.arc_timeline(context_id, actor, target, since, until).await
+    }
+
+    /// Última muestra de `actor` hacia `target`.
+    pub async fn arc_current(&self, context_id: &str, actor: &str, target: &str) -> Result<Option<ArcEntry>, MeError> {
+        Ok(self.arc_timeline(context_id, actor, target, None, None).await?.pop())
+    }
+
+    /// Puntaje actual, promedio y variación de las muestras de la última `window`.
+    pub async fn arc_trend(&self, context_id: &str, actor: &str, target: &str, window: Duration) -> Result<Option<ArcTrend>, MeError> {
+        let since = (Utc::now() - window).to_rfc3339();
+        let timeline = self.arc_timeline(context_id, actor, target, Some(&since), None).await?;
+        Ok(arc::trend(&timeline))
+    }
+
+    /// Recorre el log del contexto y reporta el primer eslabón roto (hash, prev_hash o firma).
+    pub async fn verify_log(&self, context_id: &str) -> Result<LogReport, MeError> {
+        let entries = self.store.log(context_id).await?;
+        Ok(chain::verify_chain(&self.public_key, context_id, &entries))
+    }
+
+    pub async fn get(&self, filter: &GetFilter) -> Result<Vec<Entry>, MeError> {
         self.store.get(filter).await
     }
-}
\ No newline at end of file
+
+    /// Lee verbos con el lenguaje de `core::query`:
+    /// `me.query(&"have where key ~ \"wallet%\" and value.$.chain = \"eth\"".parse()?)`.
+    pub async fn query(&self, query: &Query) -> Result<Vec<Entry>, MeError> {
+        self.store.query(query).await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryStore;
+    use crate::utils::kdf::FAST;
+
+    #[tokio::test]
+    async fn load_migrates_legacy_private_keys() {
+        let store = Arc::new(MemoryStore::new());
+        // identidad escrita con el esquema anterior: SHA-256(username‖password) sin salt
+        // (nonce || ciphertext, ChaCha20-Poly1305 con los primeros 32 bytes del hash en base64)
+        use chacha20poly1305::aead::{Aead, KeyInit};
+        let key = STANDARD.encode(Me::<MemoryStore>::legacy_derive_key("suign", "123456").unwrap());
+        let cipher = chacha20poly1305::ChaCha20Poly1305::new_from_slice(&key.as_bytes()[..32]).unwrap();
+        let mut legacy = vec![9u8; 12];
+        legacy.extend(cipher.encrypt(legacy[..].into(), b"cHJpdmF0ZQ==".as_ref()).unwrap());
+        store.create_identity("suign", "pub", &STANDARD.encode(&legacy)).await.unwrap();
+
+        let me = Me::load(store.clone(), "suign", "123456").await.unwrap();
+        assert_eq!(me.private_key_raw, "cHJpdmF0ZQ==");
+
+        let (_, stored) = store.load_keys("suign").await.unwrap();
+        assert!(!kdf::is_legacy(&stored));
+        let reloaded = Me::load(store.clone(), "suign", "123456").await.unwrap();
+        assert_eq!(reloaded.context_id, me.context_id);
+        assert!(Me::load(store, "suign", "wrong").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn authority_keys_are_encrypted_listed_and_reloaded() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create(store.clone(), "jabellae", "123456").await.unwrap();
+        let cleaker = KeyPair::generate();
+        let cleaker_pub = cleaker.public_key_base64();
+
+        assert!(me.add_key("cleaker", cleaker.clone(), "wrong").await.is_err());
+        me.add_key("cleaker", cleaker, "123456").await.unwrap();
+        assert!(me.add_key("cleaker", KeyPair::generate(), "123456").await.is_err());
+        assert!(me.add_key("main", KeyPair::generate(), "123456").await.is_err());
+        assert_eq!(me.list_keys(), ["main", "cleaker"]);
+
+        let (_, stored) = store.load_key("jabellae", "cleaker").await.unwrap();
+        assert!(!stored.contains(&me.keypair("cleaker").unwrap().private_key_base64()));
+
+        let mut loaded = Me::load(store.clone(), "jabellae", "123456").await.unwrap();
+        assert_eq!(loaded.get_public_key(Some("cleaker")).unwrap(), cleaker_pub);
+        assert_eq!(loaded.get_public_key(None).unwrap(), me.public_key);
+
+        let sig = loaded.sign_with("cleaker", b"hello").unwrap();
+        let vk = loaded.keypair("cleaker").unwrap().verifying_key();
+        assert!(vk.verify_strict(b"hello", &sig).is_ok());
+        assert!(loaded.sign_with("networkX", b"hello").is_err());
+
+        assert!(loaded.delete_key("main").await.is_err());
+        loaded.delete_key("cleaker").await.unwrap();
+        assert_eq!(loaded.list_keys(), ["main"]);
+    }
+
+    #[tokio::test]
+    async fn load_tells_wrong_password_from_corrupt_data() {
+        let store = Arc::new(MemoryStore::new());
+        Me::create(store.clone(), "suign", "123456").await.unwrap();
+
+        assert!(matches!(Me::load(store.clone(), "nobody", "123456").await, Err(MeError::IdentityNotFound(_))));
+        assert!(matches!(Me::load(store.clone(), "suign", "wrong").await, Err(MeError::WrongPassword)));
+
+        store.update_encrypted_private("suign", "$argon2id$garbage").await.unwrap();
+        let err = Me::load(store, "suign", "123456").await.err().unwrap();
+        assert!(matches!(err, MeError::CorruptData(_)), "{:?}", err);
+        assert_eq!(err.status_code(), 500);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_creates_of_one_username_have_a_single_winner() {
+        let store = Arc::new(MemoryStore::new());
+        let tasks: Vec<_> = (0..8)
+            .map(|_| tokio::spawn(Me::create_with_kdf(store.clone(), "race", "123456", FAST)))
+            .collect();
+        let mut created = Vec::new();
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(me) => created.push(me),
+                Err(e) => assert!(matches!(e, MeError::AlreadyExists(_)), "{:?}", e),
+            }
+        }
+        assert_eq!(created.len(), 1);
+        let loaded = Me::load(store.clone(), "race", "123456").await.unwrap();
+        assert_eq!(loaded.public_key, created[0].public_key);
+
+        let claims: Vec<_> = (0..8).map(|_| tokio::spawn(Me::claim_or_load_with_kdf(store.clone(), "claimed", "123456", FAST))).collect();
+        let mut public_keys = Vec::new();
+        for claim in claims {
+            public_keys.push(claim.await.unwrap().unwrap().public_key);
+        }
+        public_keys.dedup();
+        assert_eq!(public_keys.len(), 1);
+        assert!(matches!(Me::claim_or_load(store, "claimed", "wrong").await, Err(MeError::WrongPassword)));
+    }
+
+    #[tokio::test]
+    async fn change_password_requires_the_old_one_and_rewraps_every_key() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create_with_kdf(store.clone(), "jabellae", "123456", FAST).await.unwrap();
+        let cleaker = KeyPair::generate();
+        me.add_key("cleaker", cleaker.clone(), "123456").await.unwrap();
+
+        // la sesión está abierta, pero sin el password actual no se puede cambiar
+        assert!(matches!(me.change_password_with_kdf("stolen", "hijacked", FAST).await, Err(MeError::WrongPassword)));
+        assert!(Me::load(store.clone(), "jabellae", "123456").await.is_ok());
+
+        me.change_password_with_kdf("123456", "654321", FAST).await.unwrap();
+        assert!(matches!(Me::load(store.clone(), "jabellae", "123456").await, Err(MeError::WrongPassword)));
+        let reloaded = Me::load(store.clone(), "jabellae", "654321").await.unwrap();
+        assert_eq!(reloaded.public_key, me.public_key);
+        assert_eq!(reloaded.get_public_key(Some("cleaker")).unwrap(), cleaker.public_key_base64());
+
+        let log = store.log(&me.context_id).await.unwrap();
+        let event = log.iter().find(|e| e.key == PASSWORD_CHANGED).unwrap();
+        assert_eq!(event.verb, Verb::Do);
+        assert_eq!(event.value, r#"{"keys":["main","cleaker"]}"#);
+        assert!(me.verify_log(&me.context_id).await.unwrap().is_intact());
+
+        // todo o nada: una autoridad inexistente no deja la llave `main` a medio cambiar
+        let (_, main) = store.load_keys("jabellae").await.unwrap();
+        let missing = [("ghost".to_string(), "enc".to_string())];
+        assert!(matches!(store.rewrap_keys("jabellae", "other", &missing).await, Err(MeError::KeyNotFound(_))));
+        assert_eq!(store.load_keys("jabellae").await.unwrap().1, main);
+    }
+
+    #[tokio::test]
+    async fn mnemonic_restores_the_same_identity() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create_with_kdf(store.clone(), "suign", "123456", FAST).await.unwrap();
+        me.add_key("cleaker", KeyPair::generate(), "123456").await.unwrap();
+        assert!(matches!(me.export_mnemonic("wrong").await, Err(MeError::WrongPassword)));
+        let words = me.export_mnemonic("123456").await.unwrap();
+
+        // base perdida: otro store, mismo username
+        let fresh = Arc::new(MemoryStore::new());
+        let restored = Me::restore_from_mnemonic_with_kdf(fresh.clone(), "suign", &words, "abcdef", FAST).await.unwrap();
+        assert_eq!((&restored.public_key, &restored.context_id), (&me.public_key, &me.context_id));
+        let reloaded = Me::load(fresh, "suign", "abcdef").await.unwrap();
+        assert_eq!(reloaded.context_id, me.context_id);
+
+        // password olvidado: misma fila, `main` re-cifrada y la llave de cleaker descartada
+        let restored = Me::restore_from_mnemonic_with_kdf(store.clone(), "suign", &words, "abcdef", FAST).await.unwrap();
+        assert_eq!(restored.public_key, me.public_key);
+        assert!(matches!(Me::load(store.clone(), "suign", "123456").await, Err(MeError::WrongPassword)));
+        let reloaded = Me::load(store.clone(), "suign", "abcdef").await.unwrap();
+        assert_eq!(reloaded.list_keys(), ["main"]);
+        let log = store.log(&me.context_id).await.unwrap();
+        assert_eq!(log.last().unwrap().key, RESTORED);
+        assert_eq!(log.last().unwrap().value, r#"{"dropped_keys":["cleaker"],"via":"mnemonic"}"#);
+        assert!(reloaded.verify_log(&me.context_id).await.unwrap().is_intact());
+
+        // otra identidad bajo el mismo username
+        let other = Me::create_with_kdf(Arc::new(MemoryStore::new()), "x", "123456", FAST).await.unwrap();
+        let other_words = other.export_mnemonic("123456").await.unwrap();
+        assert!(matches!(
+            Me::restore_from_mnemonic_with_kdf(store, "suign", &other_words, "abcdef", FAST).await,
+            Err(MeError::AlreadyExists(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn guardians_rebuild_the_identity_from_a_threshold_of_shares() {
+        let store = Arc::new(MemoryStore::new());
+        let me = Me::create_with_kdf(store.clone(), "suign", "123456", FAST).await.unwrap();
+        let alice = Me::create_with_kdf(Arc::new(MemoryStore::new()), "alice", "pw", FAST).await.unwrap();
+        let bob = Me::create_with_kdf(Arc::new(MemoryStore::new()), "bob", "pw", FAST).await.unwrap();
+        let guardians = [
+            Guardian::Identity { username: "alice".into(), public_key: alice.public_key.clone() },
+            Guardian::Identity { username: "bob".into(), public_key: bob.public_key.clone() },
+            Guardian::Printed { label: "safe-box".into() },
+        ];
+        assert!(matches!(me.create_recovery_set("wrong", 2, &guardians).await, Err(MeError::WrongPassword)));
+        let deliveries = me.create_recovery_set("123456", 2, &guardians).await.unwrap();
+
+        let set = me.recovery_set().await.unwrap();
+        assert_eq!(set.iter().map(|r| (r.guardian.as_str(), r.index, r.kind.as_str())).collect::<Vec<_>>(), [
+            ("alice", 1, "identity"),
+            ("bob", 2, "identity"),
+            ("safe-box", 3, "printed"),
+        ]);
+        assert!(set.iter().all(|r| r.threshold == 2 && r.total == 3));
+        // el log registra quién tiene cada share, nunca el share
+        let log = store.log(&me.context_id).await.unwrap();
+        let Delivery::Printed { share: printed, .. } = &deliveries[2] else { panic!("safe-box gets a printed share") };
+        assert!(log.iter().all(|e| !e.value.contains(&printed.share)));
+        assert!(me.verify_log(&me.context_id).await.unwrap().is_intact());
+
+        let Delivery::Sealed(for_bob) = &deliveries[1] else { panic!("bob gets a sealed share") };
+        assert!(alice.open_recovery_share(for_bob).is_err());
+        let bobs = bob.open_recovery_share(for_bob).unwrap();
+        assert!(matches!(
+            Me::recover_from_shares_with_kdf(Arc::new(MemoryStore::new()), "suign", std::slice::from_ref(&bobs), "abcdef", FAST).await,
+            Err(MeError::Validation(_))
+        ));
+
+        let fresh = Arc::new(MemoryStore::new());
+        let recovered = Me::recover_from_shares_with_kdf(fresh.clone(), "suign", &[bobs, printed.clone()], "abcdef", FAST).await.unwrap();
+        assert_eq!((&recovered.public_key, &recovered.context_id), (&me.public_key, &me.context_id));
+        assert!(Me::load(fresh.clone(), "suign", "abcdef").await.is_ok());
+        let log = fresh.log(&me.context_id).await.unwrap();
+        assert_eq!(log.last().unwrap().value, r#"{"dropped_keys":[],"via":"shares"}"#);
+
+        let duplicated = [Guardian::Printed { label: "x".into() }, Guardian::Printed { label: "x".into() }];
+        assert!(matches!(me.create_recovery_set("123456", 2, &duplicated).await, Err(MeError::Validation(_))));
+    }
+}
@@ -0,0 +1,65 @@
+//this.me/crate/src/core/mnemonic.rs
+// Respaldo de la identidad como frase BIP39 (lista en inglés, 24 palabras).
+// La entropía es el seed ed25519 de 32 bytes tal cual (no el seed PBKDF2 que BIP39 usa para wallets):
+// la frase vuelve a dar la misma llave y con ella la misma public key y el mismo context_id.
+use bip39::{Language, Mnemonic};
+use crate::utils::me_error::MeError;
+
+/// Palabras de una frase de respaldo (256 bits de entropía + 8 de checksum).
+pub const MNEMONIC_WORDS: usize = 24;
+
+/// Frase de 24 palabras para `seed`.
+pub fn seed_to_mnemonic(seed: &[u8; 32]) -> String {
+    // 32 bytes siempre es un largo de entropía válido
+    Mnemonic::from_entropy_in(Language::English, seed)
+        .expect("32 bytes of entropy")
+        .to_string()
+}
+
+/// Inverso de `seed_to_mnemonic`. Acepta mayúsculas y espacios extra; valida palabras y checksum.
+pub fn mnemonic_to_seed(words: &str) -> Result<[u8; 32], MeError> {
+    let normalized = words.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let count = normalized.split(' ').filter(|w| !w.is_empty()).count();
+    if count != MNEMONIC_WORDS {
+        return Err(MeError::Validation(format!("A recovery phrase has {} words, got {}", MNEMONIC_WORDS, count)));
+    }
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, &normalized).map_err(|e| match e {
+        bip39::Error::UnknownWord(i) => {
+            MeError::Validation(format!("Unknown word '{}' in the recovery phrase", normalized.split(' ').nth(i).unwrap_or_default()))
+        }
+        bip39::Error::InvalidChecksum => MeError::Validation("Recovery phrase checksum does not match (check the word order)".into()),
+        other => MeError::Validation(format!("Invalid recovery phrase: {}", other)),
+    })?;
+    let (entropy, len) = mnemonic.to_entropy_array();
+    entropy[..len]
+        .try_into()
+        .map_err(|_| MeError::Validation("Recovery phrase does not hold a 32-byte seed".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_bad_phrases() {
+        let seed = [7u8; 32];
+        let words = seed_to_mnemonic(&seed);
+        assert_eq!(words.split(' ').count(), MNEMONIC_WORDS);
+        assert_eq!(mnemonic_to_seed(&words).unwrap(), seed);
+        assert_eq!(mnemonic_to_seed(&format!("  {}\n", words.to_uppercase().replace(' ', "   "))).unwrap(), seed);
+
+        // vector de BIP39: entropía 0x00…00 → "abandon" × 23 + "art"
+        let zero = seed_to_mnemonic(&[0u8; 32]);
+        assert_eq!(zero, format!("{}art", "abandon ".repeat(23)));
+
+        let mut swapped: Vec<&str> = words.split(' ').collect();
+        swapped.swap(0, 1);
+        if swapped[0] != swapped[1] {
+            assert!(mnemonic_to_seed(&swapped.join(" ")).is_err());
+        }
+        assert!(mnemonic_to_seed(&format!("{}zzz", "abandon ".repeat(23))).is_err());
+        // frase BIP39 válida de 12 palabras: 128 bits no alcanzan para un seed
+        assert!(mnemonic_to_seed(&format!("{}about", "abandon ".repeat(11))).is_err());
+        assert!(matches!(mnemonic_to_seed("abandon abandon"), Err(MeError::Validation(_))));
+    }
+}
@@ -3,8 +3,29 @@
 pub mod model;
 pub mod store;
 pub mod me;
+pub mod keys;
+pub mod sign;
+pub mod chain;
+pub mod context;
+pub mod verbs;
+pub mod ontology;
+pub mod arc;
+pub mod query;
+pub mod mnemonic;
+pub mod recovery;
+pub mod bundle;
+pub mod links;
 // Re-exports for ergonomic access from crate users:
 // use this_me::core::{Me, MeStore, Entry, GetFilter};
-pub use model::{Entry, GetFilter};
+pub use model::{Entry, GetFilter, Proof};
 pub use store::MeStore;
 pub use me::Me;
+pub use keys::KeyPair;
+pub use sign::{verify, DetachedSignature};
+pub use chain::{LogReport, BrokenLink, LinkError};
+pub use context::{ContextId, ContextIdBuilder};
+pub use verbs::{Verb, VerbSelector, ParseVerbError};
+pub use ontology::{Ontology, Declaration};
+pub use arc::{ArcEntry, ArcScores, ArcTrend};
+pub use query::{Query, QueryError};
+pub use crate::utils::me_error::MeError;
@@ -1,12 +1,47 @@
 //this.me/crate/src/core/model.rs
 use serde::{Serialize, Deserialize};
+use super::verbs::{Verb, VerbSelector};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Entry { pub verb: String, pub key: String, pub value: String, pub timestamp: String }
+pub struct Entry {
+    pub verb: Verb,
+    pub key: String,
+    pub value: String,
+    pub timestamp: String,
+    /// Eslabón firmado de la cadena del contexto; `None` en filas anteriores al log firmado.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<Proof>,
+    /// Agrupa las filas primarias de un verbo secundario (`<secundario>:<id>`, ver `core::ontology`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+impl Entry {
+    /// Entrada sin firma ni correlación.
+    pub fn new(verb: Verb, key: &str, value: &str, timestamp: &str) -> Self {
+        Self {
+            verb,
+            key: key.to_string(),
+            value: value.to_string(),
+            timestamp: timestamp.to_string(),
+            proof: None,
+            correlation_id: None,
+        }
+    }
+}
+
+/// Hash del contenido, hash del eslabón anterior en el mismo contexto (`""` en el génesis)
+/// y firma ed25519 (base64) del hash con la llave `main`. Ver `core::chain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proof {
+    pub hash: String,
+    pub prev_hash: String,
+    pub signature: String,
+}
 
 #[derive(Debug, Clone)]
 pub struct GetFilter {
-    pub verb: String,
+    pub verb: VerbSelector,
     pub key: Option<String>,
     pub value: Option<String>,
     pub context_id: Option<String>,
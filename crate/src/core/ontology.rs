@@ -0,0 +1,276 @@
+//this.me/crate/src/core/ontology.rs
+// Verbos secundarios (ontology.hsib.json): cada uno se compone de verbos primarios,
+// p. ej. `travel` = at + do. `Me::declare` expande un secundario en una fila por primario,
+// todas con el mismo `correlation_id` (`<secundario>:<id>`), y `Me::declared` las vuelve a juntar.
+//
+// La ontología por defecto se compila dentro del crate; cada equipo puede cargar la suya
+// con `Ontology::from_json` / `Ontology::from_path` y pasarla a `Me::set_ontology`.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use super::model::Entry;
+use super::verbs::Verb;
+use crate::utils::me_error::MeError;
+
+/// `src/ontology.hsib.json`, incluido en tiempo de compilación.
+pub const BUILTIN_ONTOLOGY: &str = include_str!("../ontology.hsib.json");
+
+#[derive(Debug, thiserror::Error)]
+pub enum OntologyError {
+    #[error("Invalid ontology JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("'{0}' is not a primary verb")]
+    UnknownPrimary(String),
+    #[error("Invalid secondary verb name '{0}'")]
+    InvalidName(String),
+    #[error("'{0}' is declared both as a primary and as a secondary verb")]
+    NameClash(String),
+    #[error("Secondary verb '{0}' is not composed of any primary")]
+    EmptySecondary(String),
+    #[error("Secondary verb '{secondary}' is composed of '{verb}', which is not a declared primary")]
+    UnknownComponent { secondary: String, verb: String },
+    #[error("Secondary verb '{secondary}' lists '{verb}' more than once")]
+    DuplicateComponent { secondary: String, verb: String },
+    #[error("Unknown secondary verb '{0}'")]
+    UnknownSecondary(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Primary {
+    pub verb: Verb,
+    pub description: String,
+    pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Secondary {
+    pub name: String,
+    pub description: String,
+    /// Primarios en el orden del archivo; `Me::declare` inserta en este orden.
+    pub composed_of: Vec<Verb>,
+    pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ontology {
+    primaries: BTreeMap<Verb, Primary>,
+    secondaries: BTreeMap<String, Secondary>,
+}
+
+// Formato del archivo: { "ontology": { "primaries": {...}, "secondaries": {...} } }
+#[derive(Deserialize)]
+struct RawFile {
+    ontology: RawOntology,
+}
+
+#[derive(Deserialize)]
+struct RawOntology {
+    primaries: BTreeMap<String, RawVerb>,
+    #[serde(default)]
+    secondaries: BTreeMap<String, RawVerb>,
+}
+
+#[derive(Deserialize)]
+struct RawVerb {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    composed_of: Vec<String>,
+    #[serde(default)]
+    examples: Vec<String>,
+}
+
+impl Ontology {
+    /// La ontología incluida en el crate (parseada una sola vez).
+    pub fn builtin() -> Arc<Ontology> {
+        static BUILTIN: OnceLock<Arc<Ontology>> = OnceLock::new();
+        BUILTIN
+            .get_or_init(|| Arc::new(Ontology::from_json(BUILTIN_ONTOLOGY).expect("built-in ontology is valid")))
+            .clone()
+    }
+
+    /// Parsea y valida una ontología con el formato de `ontology.hsib.json`.
+    pub fn from_json(json: &str) -> Result<Self, OntologyError> {
+        let raw: RawFile = serde_json::from_str(json)?;
+
+        let mut primaries = BTreeMap::new();
+        for (name, raw_primary) in raw.ontology.primaries {
+            let verb: Verb = name.parse().map_err(|_| OntologyError::UnknownPrimary(name.clone()))?;
+            primaries.insert(verb, Primary { verb, description: raw_primary.description, examples: raw_primary.examples });
+        }
+
+        let mut secondaries = BTreeMap::new();
+        for (name, raw_secondary) in raw.ontology.secondaries {
+            // `:` separa el nombre del id en `correlation_id`
+            if name.is_empty() || name.contains(':') || name.chars().any(char::is_whitespace) {
+                return Err(OntologyError::InvalidName(name));
+            }
+            if name.parse::<Verb>().is_ok() {
+                return Err(OntologyError::NameClash(name));
+            }
+            if raw_secondary.composed_of.is_empty() {
+                return Err(OntologyError::EmptySecondary(name));
+            }
+            let mut composed_of = Vec::with_capacity(raw_secondary.composed_of.len());
+            for component in &raw_secondary.composed_of {
+                let verb = component
+                    .parse::<Verb>()
+                    .ok()
+                    .filter(|verb| primaries.contains_key(verb))
+                    .ok_or_else(|| OntologyError::UnknownComponent { secondary: name.clone(), verb: component.clone() })?;
+                if composed_of.contains(&verb) {
+                    return Err(OntologyError::DuplicateComponent { secondary: name.clone(), verb: component.clone() });
+                }
+                composed_of.push(verb);
+            }
+            secondaries.insert(
+                name.clone(),
+                Secondary { name, description: raw_secondary.description, composed_of, examples: raw_secondary.examples },
+            );
+        }
+
+        Ok(Self { primaries, secondaries })
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, MeError> {
+        Ok(Self::from_json(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn primary(&self, verb: Verb) -> Option<&Primary> {
+        self.primaries.get(&verb)
+    }
+
+    pub fn secondary(&self, name: &str) -> Option<&Secondary> {
+        self.secondaries.get(name)
+    }
+
+    pub fn primaries(&self) -> impl Iterator<Item = &Primary> {
+        self.primaries.values()
+    }
+
+    /// Secundarios en orden alfabético.
+    pub fn secondaries(&self) -> impl Iterator<Item = &Secondary> {
+        self.secondaries.values()
+    }
+
+    /// Primarios que componen `name`.
+    pub fn expand(&self, name: &str) -> Result<&[Verb], OntologyError> {
+        self.secondary(name)
+            .map(|s| s.composed_of.as_slice())
+            .ok_or_else(|| OntologyError::UnknownSecondary(name.to_string()))
+    }
+}
+
+/// Nuevo `correlation_id` para una declaración de `secondary`: `travel:9f86d081884c7d65`.
+pub fn new_correlation_id(secondary: &str) -> String {
+    let mut id = [0u8; 8];
+    OsRng.fill_bytes(&mut id);
+    format!("{}:{}", secondary, hex::encode(id))
+}
+
+/// Un verbo secundario reensamblado a partir de sus filas primarias.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub secondary: String,
+    pub correlation_id: String,
+    pub key: String,
+    pub value: String,
+    /// Timestamp de la primera fila.
+    pub timestamp: String,
+    pub parts: Vec<Entry>,
+}
+
+impl Declaration {
+    /// Todas las filas que pide la ontología están presentes (una escritura interrumpida deja menos).
+    pub fn is_complete(&self, secondary: &Secondary) -> bool {
+        secondary.composed_of.iter().all(|verb| self.parts.iter().any(|p| p.verb == *verb))
+    }
+}
+
+/// Agrupa por `correlation_id` las filas de `entries` (en orden cronológico) que pertenecen a
+/// `secondary`. Devuelve las declaraciones de la más reciente a la más antigua, como `get()`.
+pub fn assemble(secondary: &Secondary, entries: &[Entry]) -> Vec<Declaration> {
+    let prefix = format!("{}:", secondary.name);
+    let mut out: Vec<Declaration> = Vec::new();
+    for entry in entries {
+        let Some(correlation_id) = entry.correlation_id.as_deref().filter(|id| id.starts_with(&prefix)) else { continue };
+        if !secondary.composed_of.contains(&entry.verb) {
+            continue;
+        }
+        match out.iter_mut().find(|d| d.correlation_id == correlation_id) {
+            Some(declaration) => declaration.parts.push(entry.clone()),
+            None => out.push(Declaration {
+                secondary: secondary.name.clone(),
+                correlation_id: correlation_id.to_string(),
+                key: entry.key.clone(),
+                value: entry.value.clone(),
+                timestamp: entry.timestamp.clone(),
+                parts: vec![entry.clone()],
+            }),
+        }
+    }
+    out.reverse();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Me;
+    use crate::db::memory::MemoryStore;
+
+    #[test]
+    fn builtin_ontology_is_valid() {
+        let ontology = Ontology::builtin();
+        assert_eq!(ontology.primaries().count(), Verb::ALL.len());
+        assert_eq!(ontology.expand("travel").unwrap(), [Verb::At, Verb::Do]);
+        assert_eq!(ontology.expand("work").unwrap(), [Verb::Relate, Verb::Communicate, Verb::Do]);
+        assert!(matches!(ontology.expand("fly"), Err(OntologyError::UnknownSecondary(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_ontologies() {
+        let file = |primaries: &str, secondaries: &str| {
+            Ontology::from_json(&format!(r#"{{"ontology":{{"primaries":{{{}}},"secondaries":{{{}}}}}}}"#, primaries, secondaries))
+        };
+        assert!(file(r#""be":{}"#, r#""exist":{"composed_of":["be"]}"#).is_ok());
+        assert!(matches!(file(r#""fly":{}"#, ""), Err(OntologyError::UnknownPrimary(_))));
+        assert!(matches!(file(r#""be":{}"#, r#""exist":{"composed_of":["have"]}"#), Err(OntologyError::UnknownComponent { .. })));
+        assert!(matches!(file(r#""be":{}"#, r#""exist":{"composed_of":[]}"#), Err(OntologyError::EmptySecondary(_))));
+        assert!(matches!(file(r#""be":{}"#, r#""exist":{"composed_of":["be","be"]}"#), Err(OntologyError::DuplicateComponent { .. })));
+        assert!(matches!(file(r#""be":{}"#, r#""say":{"composed_of":["be"]}"#), Err(OntologyError::NameClash(_))));
+        assert!(matches!(file(r#""be":{}"#, r#""a:b":{"composed_of":["be"]}"#), Err(OntologyError::InvalidName(_))));
+        assert!(matches!(Ontology::from_json("{}"), Err(OntologyError::Json(_))));
+    }
+
+    #[tokio::test]
+    async fn declare_expands_and_reassembles_secondaries() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create(store, "suign", "123456").await.unwrap();
+        let cid = me.context_id.clone();
+
+        let first = me.declare(&cid, "travel", "to", "Berlin").await.unwrap();
+        me.at(&cid, "location", "Cancún").await.unwrap();
+        let second = me.declare(&cid, "travel", "when", "2025-08-15").await.unwrap();
+        me.declare(&cid, "think", "_", "What if this works?").await.unwrap();
+        assert!(first.starts_with("travel:") && first != second);
+
+        let travels = me.declared(&cid, "travel").await.unwrap();
+        assert_eq!(travels.len(), 2);
+        assert_eq!((travels[0].key.as_str(), travels[0].correlation_id.as_str()), ("when", second.as_str()));
+        let verbs: Vec<Verb> = travels[1].parts.iter().map(|p| p.verb).collect();
+        assert_eq!(verbs, [Verb::At, Verb::Do]);
+        assert!(travels.iter().all(|d| d.is_complete(me.ontology().secondary("travel").unwrap())));
+        assert!(me.verify_log(&cid).await.unwrap().is_intact());
+        assert!(me.declare(&cid, "fly", "to", "Mars").await.is_err());
+
+        // ontología propia del equipo
+        let custom = Ontology::from_json(r#"{"ontology":{"primaries":{"be":{},"have":{}},"secondaries":{"own":{"composed_of":["have","be"]}}}}"#).unwrap();
+        me.set_ontology(custom);
+        assert!(me.declare(&cid, "travel", "to", "Paris").await.is_err());
+        me.declare(&cid, "own", "bike", "blue").await.unwrap();
+        assert_eq!(me.declared(&cid, "own").await.unwrap()[0].parts.len(), 2);
+    }
+}
@@ -0,0 +1,1122 @@
+//this.me/crate/src/core/query.rs
+// Lenguaje de consulta para leer verbos:
+//
+//     have where key ~ "wallet%" and value.$.chain = "eth" since 2025-01 limit 20
+//     be,have where not (key = "age" or value.$.score >= 10) in "c:public"
+//     where value.$.tags[0] = "rust"                 (sin verbo = todos)
+//
+// Gramática (palabras clave sin distinguir mayúsculas):
+//
+//     query  := verbs? ("where" expr)? clause*
+//     verbs  := "all" | verb ("," verb)*
+//     clause := "in" text | "since" text | "until" text | "limit" n | "offset" n
+//     expr   := and ("or" and)*
+//     and    := unary ("and" unary)*
+//     unary  := "not" unary | "(" expr ")" | field op literal
+//     field  := "key" | "value" | "value" "." "$" ("." ident | "[" n "]")*
+//     op     := "=" | "!=" | "~" | "!~" | "<" | "<=" | ">" | ">="
+//
+// `~` es LIKE (`%`, `_`, `\` escapa) sin distinguir mayúsculas ASCII. `key`/`value` son texto;
+// `value.$...` mira dentro del JSON del valor y solo compara valores del mismo tipo que el literal
+// (`value.$.age > 30` no toca `"age": "30"`). Filas cuyo valor no es JSON nunca cumplen un
+// `value.$`, ni siquiera negado con `!=`.
+//
+// `Query::to_sql` compila la condición para SQLite o Postgres con todos los literales como
+// parámetros (`?N` / `$N`); `Query::matches` la evalúa en memoria con la misma semántica.
+// `limit`/`offset` son globales (sobre todas las tablas), no por tabla como en `GetFilter`.
+use std::fmt;
+use serde_json::Value;
+use super::model::{Entry, GetFilter};
+use super::verbs::{Verb, VerbSelector};
+
+#[cfg(test)]
+pub(crate) mod fuzz;
+
+/// Filas que devuelve una consulta sin `limit`.
+pub const DEFAULT_LIMIT: usize = 100;
+/// Profundidad máxima de un path JSON.
+pub const MAX_PATH_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Query error at {position}: {message}")]
+pub struct QueryError {
+    /// Byte de la consulta donde empieza el token problemático.
+    pub position: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+}
+
+/// Consulta ya parseada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub verbs: VerbSelector,
+    pub filter: Option<Expr>,
+    pub context_id: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparison),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub field: Field,
+    pub op: Op,
+    pub literal: Literal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    Key,
+    Value,
+    Json(JsonPath),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Like,
+    NotLike,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+/// Path dentro de un valor JSON: claves (identificadores) e índices de arrays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl JsonPath {
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// `[A-Za-z_][A-Za-z0-9_-]*`: sin dígitos al inicio, así una clave nunca se confunde con un
+    /// índice (Postgres interpreta `{0}` como índice en arrays y como clave en objetos).
+    fn is_key(s: &str) -> bool {
+        let mut chars = s.chars();
+        chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    /// Path de SQLite (`$.a.b[0]`).
+    pub fn sqlite(&self) -> String {
+        self.to_string()
+    }
+
+    /// Path de Postgres para `#>` / `#>>` (`{a,b,0}`), como array de texto.
+    pub fn pg(&self) -> Vec<String> {
+        self.segments
+            .iter()
+            .map(|s| match s {
+                Segment::Key(k) => k.clone(),
+                Segment::Index(i) => i.to_string(),
+            })
+            .collect()
+    }
+
+    /// Documento mínimo que contiene `leaf` en el path (`$.a.b` → `{"a":{"b":leaf}}`), para
+    /// prefiltrar con `@>` e índices GIN. `None` con índices de array o sin segmentos.
+    pub fn containing(&self, leaf: Value) -> Option<Value> {
+        if self.segments.is_empty() {
+            return None;
+        }
+        self.segments.iter().rev().try_fold(leaf, |inner, segment| match segment {
+            Segment::Key(k) => Some(serde_json::json!({ k: inner })),
+            Segment::Index(_) => None,
+        })
+    }
+
+    /// Valor en el path, como json_extract (una clave sobre un array o un índice sobre un objeto no existen).
+    pub fn lookup<'a>(&self, mut value: &'a Value) -> Option<&'a Value> {
+        for segment in &self.segments {
+            value = match (segment, value) {
+                (Segment::Key(k), Value::Object(map)) => map.get(k)?,
+                (Segment::Index(i), Value::Array(items)) => items.get(*i)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.segments {
+            match segment {
+                Segment::Key(k) => write!(f, ".{}", k)?,
+                Segment::Index(i) => write!(f, "[{}]", i)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lexer
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    /// Palabra suelta: keywords, verbos, identificadores, fechas (`2025-01-01T00:00:00+00:00`).
+    Word(String),
+    /// Texto entre comillas.
+    Text(String),
+    Sym(&'static str),
+}
+
+const SYMBOLS: [&str; 13] = ["!=", "!~", "<=", ">=", "(", ")", ",", ".", "$", "[", "]", "=", "~"];
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':' | '+')
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Tok)>, QueryError> {
+    let mut out = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some(&(pos, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(QueryError::new(pos, "unterminated string")),
+                    Some((_, ch)) if ch == c => break,
+                    // \" y \\ se resuelven aquí; el resto (\%, \_) llega tal cual al LIKE
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, next)) if next == c || next == '\\' => text.push(next),
+                        Some((_, next)) => {
+                            text.push('\\');
+                            text.push(next);
+                        }
+                        None => return Err(QueryError::new(pos, "unterminated string")),
+                    },
+                    Some((_, ch)) => text.push(ch),
+                }
+            }
+            out.push((pos, Tok::Text(text)));
+        } else if is_word_char(c) {
+            let mut word = String::new();
+            while let Some(&(_, ch)) = chars.peek() {
+                if is_word_char(ch) {
+                    word.push(ch);
+                    chars.next();
+                    continue;
+                }
+                // decimales y fracciones de segundo: `3.5`, `00:00:00.123`
+                let mut ahead = chars.clone();
+                ahead.next();
+                let digit_next = ahead.peek().is_some_and(|&(_, n)| n.is_ascii_digit());
+                if ch == '.' && word.ends_with(|p: char| p.is_ascii_digit()) && digit_next {
+                    word.push(ch);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            out.push((pos, Tok::Word(word)));
+        } else if c == '<' || c == '>' {
+            chars.next();
+            let sym = match (c, chars.peek()) {
+                ('<', Some((_, '='))) => "<=",
+                ('>', Some((_, '='))) => ">=",
+                ('<', _) => "<",
+                _ => ">",
+            };
+            if sym.len() == 2 {
+                chars.next();
+            }
+            out.push((pos, Tok::Sym(sym)));
+        } else {
+            let rest = &input[pos..];
+            let sym = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(|| QueryError::new(pos, format!("unexpected character '{}'", c)))?;
+            for _ in 0..sym.len() {
+                chars.next();
+            }
+            out.push((pos, Tok::Sym(sym)));
+        }
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+
+const KEYWORDS: [&str; 13] = [
+    "where", "and", "or", "not", "in", "since", "until", "limit", "offset", "all", "true", "false", "null",
+];
+
+struct Parser {
+    tokens: Vec<(usize, Tok)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    /// Posición (byte) del token actual, o el final de la consulta.
+    fn at(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(p, _)| *p)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let tok = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        tok
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, QueryError> {
+        Err(QueryError::new(self.at(), message))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_sym(&mut self, sym: &str) -> bool {
+        let found = matches!(self.peek(), Some(Tok::Sym(s)) if *s == sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<(), QueryError> {
+        if self.eat_sym(sym) { Ok(()) } else { self.error(format!("expected '{}'", sym)) }
+    }
+
+    fn query(&mut self) -> Result<Query, QueryError> {
+        let mut query = Query {
+            verbs: self.verbs()?,
+            filter: None,
+            context_id: None,
+            since: None,
+            until: None,
+            limit: None,
+            offset: None,
+        };
+        if self.eat_keyword("where") {
+            query.filter = Some(self.or()?);
+        }
+        while self.peek().is_some() {
+            let at = self.at();
+            let keyword = match self.next() {
+                Some(Tok::Word(w)) => w.to_ascii_lowercase(),
+                _ => return Err(QueryError::new(at, "expected in, since, until, limit or offset")),
+            };
+            match keyword.as_str() {
+                "in" | "since" | "until" | "limit" | "offset" => {}
+                "where" => return Err(QueryError::new(at, "'where' must come before in/since/until/limit/offset")),
+                other => return Err(QueryError::new(at, format!("unexpected '{}'", other))),
+            }
+            let arg_at = self.at();
+            let arg = match self.next() {
+                Some(Tok::Word(w)) | Some(Tok::Text(w)) if !w.is_empty() => w,
+                _ => return Err(QueryError::new(arg_at, format!("'{}' needs a value", keyword))),
+            };
+            let repeated = match keyword.as_str() {
+                "limit" | "offset" => {
+                    let n = arg
+                        .parse()
+                        .map_err(|_| QueryError::new(arg_at, format!("'{}' needs a whole number", keyword)))?;
+                    let slot = if keyword == "limit" { &mut query.limit } else { &mut query.offset };
+                    slot.replace(n).is_some()
+                }
+                "in" => query.context_id.replace(arg).is_some(),
+                "since" => query.since.replace(arg).is_some(),
+                _ => query.until.replace(arg).is_some(),
+            };
+            if repeated {
+                return Err(QueryError::new(at, format!("'{}' given twice", keyword)));
+            }
+        }
+        Ok(query)
+    }
+
+    fn verbs(&mut self) -> Result<VerbSelector, QueryError> {
+        match self.peek() {
+            Some(Tok::Word(w)) if KEYWORDS.contains(&w.to_ascii_lowercase().as_str()) => {
+                self.eat_keyword("all");
+                Ok(VerbSelector::All)
+            }
+            Some(Tok::Word(_)) => {
+                let mut verbs = Vec::new();
+                loop {
+                    let at = self.at();
+                    let verb = match self.next() {
+                        Some(Tok::Word(w)) => w.to_ascii_lowercase().parse::<Verb>().map_err(|e| QueryError::new(at, e.to_string()))?,
+                        _ => return Err(QueryError::new(at, "expected a verb")),
+                    };
+                    verbs.push(verb);
+                    if !self.eat_sym(",") {
+                        break;
+                    }
+                }
+                Ok(match verbs.as_slice() {
+                    [verb] => VerbSelector::One(*verb),
+                    _ => VerbSelector::Many(verbs),
+                })
+            }
+            _ => Ok(VerbSelector::All),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.unary()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat_sym("(") {
+            let expr = self.or()?;
+            self.expect_sym(")")?;
+            return Ok(expr);
+        }
+        self.comparison().map(Expr::Compare)
+    }
+
+    fn comparison(&mut self) -> Result<Comparison, QueryError> {
+        let field = self.field()?;
+        let op_at = self.at();
+        let op = match self.next() {
+            Some(Tok::Sym("=")) => Op::Eq,
+            Some(Tok::Sym("!=")) => Op::Ne,
+            Some(Tok::Sym("~")) => Op::Like,
+            Some(Tok::Sym("!~")) => Op::NotLike,
+            Some(Tok::Sym("<")) => Op::Lt,
+            Some(Tok::Sym("<=")) => Op::Le,
+            Some(Tok::Sym(">")) => Op::Gt,
+            Some(Tok::Sym(">=")) => Op::Ge,
+            _ => return Err(QueryError::new(op_at, "expected =, !=, ~, !~, <, <=, > or >=")),
+        };
+        let literal_at = self.at();
+        let literal = match self.next() {
+            Some(Tok::Text(s)) => Literal::Text(s),
+            Some(Tok::Word(w)) => match w.to_ascii_lowercase().as_str() {
+                "true" => Literal::Bool(true),
+                "false" => Literal::Bool(false),
+                "null" => Literal::Null,
+                _ => match w.parse::<f64>() {
+                    Ok(n) if n.is_finite() => Literal::Number(n),
+                    _ => return Err(QueryError::new(literal_at, format!("'{}' is not a literal; quote text", w))),
+                },
+            },
+            _ => return Err(QueryError::new(literal_at, "expected a literal")),
+        };
+
+        let ordering = matches!(op, Op::Lt | Op::Le | Op::Gt | Op::Ge);
+        let like = matches!(op, Op::Like | Op::NotLike);
+        match (&field, &literal) {
+            (Field::Key | Field::Value, Literal::Text(_)) => {}
+            (Field::Key | Field::Value, _) => {
+                return Err(QueryError::new(literal_at, "key and value are text: quote the literal or use value.$"));
+            }
+            (Field::Json(_), Literal::Text(_)) => {}
+            (Field::Json(_), Literal::Number(_)) if !like => {}
+            (Field::Json(_), Literal::Bool(_) | Literal::Null) if !like && !ordering => {}
+            (Field::Json(_), _) => return Err(QueryError::new(op_at, "operator does not apply to this literal")),
+        }
+        // Postgres rechaza un patrón que termina en el escape; SQLite no: se rechaza para ambos
+        if let (true, Literal::Text(pattern)) = (like, &literal) {
+            if pattern.chars().fold(false, |escaped, c| !escaped && c == '\\') {
+                return Err(QueryError::new(literal_at, "LIKE pattern ends with an escape character"));
+            }
+        }
+        Ok(Comparison { field, op, literal })
+    }
+
+    fn field(&mut self) -> Result<Field, QueryError> {
+        let at = self.at();
+        let name = match self.next() {
+            Some(Tok::Word(w)) => w.to_ascii_lowercase(),
+            _ => return Err(QueryError::new(at, "expected key, value or value.$path")),
+        };
+        match name.as_str() {
+            "key" => Ok(Field::Key),
+            "value" if self.eat_sym(".") => {
+                self.expect_sym("$")?;
+                self.json_path().map(Field::Json)
+            }
+            "value" => Ok(Field::Value),
+            _ => Err(QueryError::new(at, format!("unknown field '{}' (use key, value or value.$path)", name))),
+        }
+    }
+
+    fn json_path(&mut self) -> Result<JsonPath, QueryError> {
+        let mut path = JsonPath::default();
+        loop {
+            let at = self.at();
+            if self.eat_sym(".") {
+                match self.next() {
+                    Some(Tok::Word(w)) if JsonPath::is_key(&w) => path.segments.push(Segment::Key(w)),
+                    _ => return Err(QueryError::new(at + 1, "expected a JSON key ([A-Za-z_][A-Za-z0-9_-]*)")),
+                }
+            } else if self.eat_sym("[") {
+                let index_at = self.at();
+                match self.next() {
+                    Some(Tok::Word(w)) if w.bytes().all(|b| b.is_ascii_digit()) => {
+                        let index = w.parse().map_err(|_| QueryError::new(index_at, "array index too large"))?;
+                        path.segments.push(Segment::Index(index));
+                    }
+                    _ => return Err(QueryError::new(index_at, "expected an array index")),
+                }
+                self.expect_sym("]")?;
+            } else {
+                return Ok(path);
+            }
+            if path.segments.len() > MAX_PATH_DEPTH {
+                return Err(QueryError::new(at, format!("JSON path deeper than {}", MAX_PATH_DEPTH)));
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for JsonPath {
+    type Err = QueryError;
+
+    /// `type`, `meta.chain`, `tags[0].name`, o con `$` explícito (`$.meta.chain`, `$` = todo el valor).
+    /// Es la única forma de construir un path desde texto: lo que no cumple la gramática no llega al SQL.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(at) = s.find(char::is_whitespace) {
+            return Err(QueryError::new(at, "JSON path cannot contain whitespace"));
+        }
+        let (text, shift) = if s.starts_with('$') { (s.to_string(), 0) } else { (format!("$.{}", s), 2) };
+        let unshift = |e: QueryError| QueryError::new(e.position.saturating_sub(shift), e.message);
+        let mut parser = Parser { tokens: tokenize(&text).map_err(unshift)?, pos: 0, end: text.len() };
+        parser.expect_sym("$").map_err(unshift)?;
+        let path = parser.json_path().map_err(unshift)?;
+        if parser.peek().is_some() {
+            return Err(unshift(QueryError::new(parser.at(), "unexpected text after JSON path")));
+        }
+        Ok(path)
+    }
+}
+
+impl TryFrom<&GetFilter> for Query {
+    type Error = QueryError;
+
+    /// El mini-lenguaje de `GetFilter` como consulta: `key`/`value` exactos, `like:<subcadena>`
+    /// (LIKE `%subcadena%`, `%` y `_` son comodines, `\` es literal) y `json:<path>=<texto>`
+    /// (el string en `path` es igual a `texto`). Un path inválido es un error, no una consulta vacía.
+    fn try_from(filter: &GetFilter) -> Result<Self, Self::Error> {
+        let compare = |field: Field, op: Op, text: String| Expr::Compare(Comparison { field, op, literal: Literal::Text(text) });
+        // like: no tiene escape; en la consulta `\` sí escapa, así que se duplica
+        let contains = |pattern: &str| format!("%{}%", pattern.replace('\\', "\\\\"));
+        let key = filter.key.as_deref().map(|k| match k.strip_prefix("like:") {
+            Some(pattern) => compare(Field::Key, Op::Like, contains(pattern)),
+            None => compare(Field::Key, Op::Eq, k.to_string()),
+        });
+        let value = match filter.value.as_deref() {
+            None => None,
+            Some(v) => Some(if let Some((path, text)) = v.strip_prefix("json:").and_then(|s| s.split_once('=')) {
+                let path: JsonPath = path
+                    .parse()
+                    .map_err(|e: QueryError| QueryError::new(e.position, format!("invalid JSON path '{}': {}", path, e.message)))?;
+                compare(Field::Json(path), Op::Eq, text.to_string())
+            } else if let Some(pattern) = v.strip_prefix("like:") {
+                compare(Field::Value, Op::Like, contains(pattern))
+            } else {
+                compare(Field::Value, Op::Eq, v.to_string())
+            }),
+        };
+        let filter_expr = match (key, value) {
+            (Some(k), Some(v)) => Some(Expr::And(Box::new(k), Box::new(v))),
+            (k, v) => k.or(v),
+        };
+        Ok(Query {
+            verbs: filter.verb.clone(),
+            filter: filter_expr,
+            context_id: filter.context_id.clone(),
+            since: filter.since.clone(),
+            until: filter.until.clone(),
+            limit: filter.limit,
+            offset: filter.offset,
+        })
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(s)?, pos: 0, end: s.len() };
+        parser.query()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SQL
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Sqlite,
+    Postgres,
+}
+
+/// Parámetro ligado de una consulta compilada, en orden (`?1`/`$1` es el primero).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    Text(String),
+    Number(f64),
+    /// Path de Postgres (`text[]`).
+    TextArray(Vec<String>),
+}
+
+/// Condición SQL (sin `WHERE`) y sus parámetros.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlCondition {
+    pub sql: String,
+    pub params: Vec<Param>,
+}
+
+struct SqlBuilder {
+    dialect: Dialect,
+    params: Vec<Param>,
+}
+
+impl SqlBuilder {
+    fn bind(&mut self, param: Param) -> String {
+        self.params.push(param);
+        match self.dialect {
+            Dialect::Sqlite => format!("?{}", self.params.len()),
+            Dialect::Postgres => format!("${}", self.params.len()),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, key_col: &str, value_col: &str) -> String {
+        match expr {
+            Expr::And(a, b) => format!("({} AND {})", self.expr(a, key_col, value_col), self.expr(b, key_col, value_col)),
+            Expr::Or(a, b) => format!("({} OR {})", self.expr(a, key_col, value_col), self.expr(b, key_col, value_col)),
+            Expr::Not(e) => format!("(NOT {})", self.expr(e, key_col, value_col)),
+            // COALESCE: sin NULLs la lógica es de dos valores y NOT se comporta como en memoria
+            Expr::Compare(c) => {
+                let falsy = if self.dialect == Dialect::Sqlite { "0" } else { "FALSE" };
+                format!("COALESCE({}, {})", self.comparison(c, key_col, value_col), falsy)
+            }
+        }
+    }
+
+    /// `lhs <op> rhs` sobre texto: LIKE sin distinguir mayúsculas y orden por bytes en ambos motores.
+    fn text_op(&self, lhs: &str, op: Op, rhs: &str) -> String {
+        let pg = self.dialect == Dialect::Postgres;
+        let like = if pg { "ILIKE" } else { "LIKE" };
+        let collate = if pg { " COLLATE \"C\"" } else { "" };
+        match op {
+            Op::Eq => format!("{lhs} = {rhs}"),
+            Op::Ne => format!("{lhs} <> {rhs}"),
+            Op::Like => format!("{lhs} {like} {rhs} ESCAPE '\\'"),
+            Op::NotLike => format!("{lhs} NOT {like} {rhs} ESCAPE '\\'"),
+            Op::Lt => format!("{lhs}{collate} < {rhs}"),
+            Op::Le => format!("{lhs}{collate} <= {rhs}"),
+            Op::Gt => format!("{lhs}{collate} > {rhs}"),
+            Op::Ge => format!("{lhs}{collate} >= {rhs}"),
+        }
+    }
+
+    fn comparison(&mut self, c: &Comparison, key_col: &str, value_col: &str) -> String {
+        let path = match (&c.field, &c.literal) {
+            (Field::Key, Literal::Text(s)) => {
+                let rhs = self.bind(Param::Text(s.clone()));
+                return self.text_op(key_col, c.op, &rhs);
+            }
+            (Field::Value, Literal::Text(s)) => {
+                let rhs = self.bind(Param::Text(s.clone()));
+                return self.text_op(value_col, c.op, &rhs);
+            }
+            (Field::Json(path), _) => path,
+            // el parser no produce otras combinaciones
+            _ => return "1 = 0".to_string(),
+        };
+        match self.dialect {
+            Dialect::Sqlite => self.sqlite_json(value_col, path, c.op, &c.literal),
+            Dialect::Postgres => self.pg_json(value_col, path, c.op, &c.literal),
+        }
+    }
+
+    fn sqlite_json(&mut self, col: &str, path: &JsonPath, op: Op, literal: &Literal) -> String {
+        let p = self.bind(Param::Text(path.sqlite()));
+        let kind = format!("json_type({col}, {p})");
+        let extract = format!("json_extract({col}, {p})");
+        let test = match literal {
+            Literal::Text(s) => {
+                let rhs = self.bind(Param::Text(s.clone()));
+                format!("{kind} = 'text' AND {}", self.text_op(&extract, op, &rhs))
+            }
+            Literal::Number(n) => {
+                let rhs = self.bind(Param::Number(*n));
+                format!("{kind} IN ('integer', 'real') AND {}", numeric_op(&extract, op, &rhs))
+            }
+            // json_type ya es 'true'/'false'
+            Literal::Bool(b) => format!("{kind} IN ('true', 'false') AND {}", self.text_op(&kind, op, &format!("'{}'", b))),
+            Literal::Null if op == Op::Eq => format!("{kind} = 'null'"),
+            Literal::Null => format!("{kind} <> 'null'"),
+        };
+        // json_type falla con JSON inválido: el CASE evita evaluarlo
+        format!("CASE WHEN json_valid({col}) THEN {test} END")
+    }
+
+    fn pg_json(&mut self, col: &str, path: &JsonPath, op: Op, literal: &Literal) -> String {
+        // me.try_jsonb (pg/migrate.rs) devuelve NULL si el valor no es JSON
+        let doc = format!("me.try_jsonb({col})");
+        let p = self.bind(Param::TextArray(path.pg()));
+        let kind = format!("jsonb_typeof({doc} #> {p}::text[])");
+        let text = format!("({doc} #>> {p}::text[])");
+        // `#>` con un índice también busca la clave "0" en objetos: se exige que el padre sea un array
+        let mut guards = Vec::new();
+        for (depth, segment) in path.segments().iter().enumerate() {
+            if let Segment::Index(_) = segment {
+                let parent = self.bind(Param::TextArray(path.pg()[..depth].to_vec()));
+                guards.push(format!("jsonb_typeof({doc} #> {parent}::text[]) = 'array'"));
+            }
+        }
+        let test = match literal {
+            Literal::Text(s) => {
+                let rhs = self.bind(Param::Text(s.clone()));
+                format!("{kind} = 'string' AND {}", self.text_op(&text, op, &rhs))
+            }
+            Literal::Number(n) => {
+                let rhs = self.bind(Param::Number(*n));
+                // Postgres no garantiza el orden del AND: el CASE protege el cast
+                format!(
+                    "CASE WHEN {kind} = 'number' THEN {} END",
+                    numeric_op(&format!("{text}::float8"), op, &format!("{rhs}::float8"))
+                )
+            }
+            Literal::Bool(b) => format!("{kind} = 'boolean' AND {}", self.text_op(&text, op, &format!("'{}'", b))),
+            Literal::Null if op == Op::Eq => format!("{kind} = 'null'"),
+            Literal::Null => format!("{kind} <> 'null'"),
+        };
+        guards.push(test);
+        // `=` sobre claves equivale a estar contenido: el `@>` extra deja usar el índice GIN
+        let leaf = match literal {
+            Literal::Text(s) => Some(Value::String(s.clone())),
+            Literal::Number(n) => serde_json::Number::from_f64(*n).map(Value::Number),
+            Literal::Bool(b) => Some(Value::Bool(*b)),
+            Literal::Null => Some(Value::Null),
+        };
+        if let Some(doc_value) = leaf.filter(|_| op == Op::Eq).and_then(|leaf| path.containing(leaf)) {
+            let contained = self.bind(Param::Text(doc_value.to_string()));
+            guards.insert(0, format!("{doc} @> {contained}::jsonb"));
+        }
+        guards.join(" AND ")
+    }
+}
+
+fn numeric_op(lhs: &str, op: Op, rhs: &str) -> String {
+    let sql_op = match op {
+        Op::Eq => "=",
+        Op::Ne => "<>",
+        Op::Lt => "<",
+        Op::Le => "<=",
+        Op::Gt => ">",
+        // LIKE sobre números lo rechaza el parser
+        Op::Ge | Op::Like | Op::NotLike => ">=",
+    };
+    format!("{lhs} {sql_op} {rhs}")
+}
+
+impl Query {
+    /// Condición para la tabla de `verb`: contexto, since/until y el `where`, con parámetros ligados.
+    pub fn to_sql(&self, verb: Verb, dialect: Dialect) -> SqlCondition {
+        let (key_col, value_col) = verb.columns();
+        let mut b = SqlBuilder { dialect, params: Vec::new() };
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(cid) = &self.context_id {
+            parts.push(format!("context_id = {}", b.bind(Param::Text(cid.clone()))));
+        }
+        if let Some(since) = &self.since {
+            parts.push(format!("timestamp >= {}", b.bind(Param::Text(since.clone()))));
+        }
+        if let Some(until) = &self.until {
+            parts.push(format!("timestamp <= {}", b.bind(Param::Text(until.clone()))));
+        }
+        if let Some(filter) = &self.filter {
+            parts.push(b.expr(filter, key_col, value_col));
+        }
+        let sql = if parts.is_empty() { "1 = 1".to_string() } else { parts.join(" AND ") };
+        SqlCondition { sql, params: b.params }
+    }
+
+    /// Filas a pedir por tabla: con `limit`/`offset` globales, cada tabla aporta como mucho `limit + offset`.
+    pub fn fetch_limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).saturating_add(self.offset.unwrap_or(0))
+    }
+
+    /// Mezcla las filas de todas las tablas: orden cronológico inverso, `offset` y `limit`.
+    pub fn finish(&self, mut entries: Vec<Entry>) -> Vec<Entry> {
+        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        entries
+            .into_iter()
+            .skip(self.offset.unwrap_or(0))
+            .take(self.limit.unwrap_or(DEFAULT_LIMIT))
+            .collect()
+    }
+
+    /// Evalúa la consulta (salvo limit/offset) sobre una fila, con la semántica de `to_sql`.
+    pub fn matches(&self, context_id: &str, key: &str, value: &str, timestamp: &str) -> bool {
+        self.context_id.as_deref().is_none_or(|cid| cid == context_id)
+            && self.since.as_deref().is_none_or(|since| timestamp >= since)
+            && self.until.as_deref().is_none_or(|until| timestamp <= until)
+            && self.filter.as_ref().is_none_or(|f| f.matches(key, value))
+    }
+}
+
+impl Expr {
+    pub fn matches(&self, key: &str, value: &str) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(key, value) && b.matches(key, value),
+            Expr::Or(a, b) => a.matches(key, value) || b.matches(key, value),
+            Expr::Not(e) => !e.matches(key, value),
+            Expr::Compare(c) => c.matches(key, value),
+        }
+    }
+}
+
+impl Comparison {
+    pub fn matches(&self, key: &str, value: &str) -> bool {
+        let path = match (&self.field, &self.literal) {
+            (Field::Key, Literal::Text(s)) => return text_matches(key, self.op, s),
+            (Field::Value, Literal::Text(s)) => return text_matches(value, self.op, s),
+            (Field::Json(path), _) => path,
+            _ => return false,
+        };
+        let Ok(doc) = serde_json::from_str::<Value>(value) else { return false };
+        let Some(found) = path.lookup(&doc) else { return false };
+        match (&self.literal, found) {
+            (Literal::Text(s), Value::String(found)) => text_matches(found, self.op, s),
+            (Literal::Number(n), Value::Number(found)) => found.as_f64().is_some_and(|f| match self.op {
+                Op::Eq => f == *n,
+                Op::Ne => f != *n,
+                Op::Lt => f < *n,
+                Op::Le => f <= *n,
+                Op::Gt => f > *n,
+                _ => f >= *n,
+            }),
+            (Literal::Bool(b), Value::Bool(found)) => (found == b) == (self.op == Op::Eq),
+            (Literal::Null, found) => found.is_null() == (self.op == Op::Eq),
+            _ => false,
+        }
+    }
+}
+
+fn text_matches(text: &str, op: Op, literal: &str) -> bool {
+    match op {
+        Op::Eq => text == literal,
+        Op::Ne => text != literal,
+        Op::Like => like(literal, text),
+        Op::NotLike => !like(literal, text),
+        Op::Lt => text < literal,
+        Op::Le => text <= literal,
+        Op::Gt => text > literal,
+        Op::Ge => text >= literal,
+    }
+}
+
+/// `LIKE pattern ESCAPE '\'`: `%` = cualquier secuencia, `_` = un carácter, `\x` = `x` literal,
+/// sin distinguir mayúsculas ASCII (LIKE de SQLite, ILIKE de Postgres sobre ASCII).
+pub fn like(pattern: &str, text: &str) -> bool {
+    #[derive(PartialEq)]
+    enum P {
+        Any,
+        One,
+        Char(char),
+    }
+    let mut p = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        p.push(match c {
+            '%' => P::Any,
+            '_' => P::One,
+            '\\' => P::Char(chars.next().unwrap_or('\\').to_ascii_lowercase()),
+            c => P::Char(c.to_ascii_lowercase()),
+        });
+    }
+    let t: Vec<char> = text.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let (mut pi, mut ti) = (0, 0);
+    let (mut star, mut mark) = (None, 0);
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == P::One || p[pi] == P::Char(t[ti])) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == P::Any {
+            star = Some(pi);
+            mark = ti;
+            pi += 1;
+        } else if let Some(s) = star {
+            pi = s + 1;
+            mark += 1;
+            ti = mark;
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == P::Any)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(q: &str) -> Query {
+        q.parse().unwrap_or_else(|e| panic!("{}: {}", q, e))
+    }
+
+    #[test]
+    fn parses_the_full_grammar() {
+        let q = parse(r#"have where key ~ "wallet%" and value.$.chain = "eth" since 2025-01 limit 20"#);
+        assert_eq!(q.verbs, VerbSelector::One(Verb::Have));
+        assert_eq!((q.since.as_deref(), q.limit, q.offset), (Some("2025-01"), Some(20), None));
+        let Some(Expr::And(a, b)) = &q.filter else { panic!("{:?}", q.filter) };
+        assert_eq!(**a, Expr::Compare(Comparison { field: Field::Key, op: Op::Like, literal: Literal::Text("wallet%".into()) }));
+        let Expr::Compare(Comparison { field: Field::Json(path), .. }) = &**b else { panic!() };
+        assert_eq!(path.to_string(), "$.chain");
+
+        let q = parse(r#"BE, have WHERE NOT (key = 'a' OR value.$.tags[0].n >= -1.5) in "c:x" until "2025-01-01T00:00:00.5+00:00" offset 3"#);
+        assert_eq!(q.verbs.verbs(), [Verb::Be, Verb::Have]);
+        assert_eq!((q.context_id.as_deref(), q.until.as_deref()), (Some("c:x"), Some("2025-01-01T00:00:00.5+00:00")));
+        let Some(Expr::Not(inner)) = &q.filter else { panic!() };
+        let Expr::Or(_, right) = &**inner else { panic!() };
+        let Expr::Compare(c) = &**right else { panic!() };
+        assert_eq!((c.op, &c.literal), (Op::Ge, &Literal::Number(-1.5)));
+        assert_eq!(c.field, Field::Json(JsonPath { segments: vec![Segment::Key("tags".into()), Segment::Index(0), Segment::Key("n".into())] }));
+
+        assert_eq!(parse("").verbs, VerbSelector::All);
+        assert_eq!(parse("all limit 5").limit, Some(5));
+        assert!(parse("where value.$ = null").filter.is_some());
+        assert_eq!(parse(r#"where key = "say \"hi\" 100\%""#).filter, parse(r#"where key = 'say "hi" 100\%'"#).filter);
+    }
+
+    #[test]
+    fn rejects_malformed_queries_with_a_position() {
+        for (q, position) in [
+            ("dance where key = 'x'", 0),
+            ("have where key = ", 17),
+            ("have where key = 34", 17),
+            ("have where value ~ 3", 19),
+            ("have where value.$.a ~ 3", 21),
+            ("have where value.$.a > true", 21),
+            ("have where value.$.0a = 1", 19),
+            ("have where value.$[x] = 1", 19),
+            ("have where (key = 'a'", 21),
+            ("have where key = 'a' limit x", 27),
+            ("have limit 1 limit 2", 13),
+            ("have where key = 'a", 17),
+            ("have where key == 'a'", 16),
+            ("have where key = 'a' where key = 'b'", 21),
+            ("have where name = 'a'", 11),
+            ("have where key = 'a' ; drop table me", 21),
+            (r"have where key ~ 'a\\'", 17),
+        ] {
+            let err = q.parse::<Query>().expect_err(q);
+            assert_eq!(err.position, position, "{}: {}", q, err);
+        }
+        let deep = format!("where value.${} = 1", ".a".repeat(MAX_PATH_DEPTH + 1));
+        assert!(deep.parse::<Query>().is_err());
+    }
+
+    #[test]
+    fn compiles_with_bound_parameters() {
+        let q = parse(r#"react where key ~ "x'; drop table me; --" and value.$.a[1] > 2 in "c""#);
+        let sqlite = q.to_sql(Verb::React, Dialect::Sqlite);
+        assert!(!sqlite.sql.contains("drop"));
+        assert!(sqlite.sql.starts_with("context_id = ?1 AND "), "{}", sqlite.sql);
+        assert!(sqlite.sql.contains("COALESCE(target LIKE ?2 ESCAPE '\\', 0)"), "{}", sqlite.sql);
+        assert!(sqlite.sql.contains("json_type(emoji, ?3)"), "{}", sqlite.sql);
+        assert_eq!(
+            sqlite.params,
+            [Param::Text("c".into()), Param::Text("x'; drop table me; --".into()), Param::Text("$.a[1]".into()), Param::Number(2.0)]
+        );
+
+        let pg = q.to_sql(Verb::React, Dialect::Postgres);
+        assert!(pg.sql.contains("target ILIKE $2 ESCAPE '\\'"), "{}", pg.sql);
+        assert!(pg.sql.contains("jsonb_typeof(me.try_jsonb(emoji) #> $4::text[]) = 'array'"), "{}", pg.sql);
+        assert_eq!(pg.params[2], Param::TextArray(vec!["a".into(), "1".into()]));
+        assert_eq!(pg.params[3], Param::TextArray(vec!["a".into()]));
+
+        let pg = parse(r#"where value.$.a.b = "x" or value.$.n = 3"#).to_sql(Verb::Have, Dialect::Postgres);
+        assert!(pg.sql.contains("me.try_jsonb(value) @> $3::jsonb"), "{}", pg.sql);
+        assert_eq!(pg.params[2], Param::Text(r#"{"a":{"b":"x"}}"#.into()));
+        assert_eq!(pg.params[5], Param::Text(r#"{"n":3.0}"#.into()));
+
+        assert_eq!(parse("have").to_sql(Verb::Have, Dialect::Sqlite).sql, "1 = 1");
+    }
+
+    #[test]
+    fn evaluates_like_the_sql_backends() {
+        let wallet = r#"{"chain":"eth","balance":12.5,"tags":["a","b"],"cold":false,"note":null,"0":"zero"}"#;
+        let cases = [
+            (r#"key ~ "wallet%""#, true),
+            (r#"key ~ "WALLET:_ain""#, true),
+            (r#"key !~ "%main""#, false),
+            (r#"key ~ "wallet\%""#, false),
+            (r#"value.$.chain = "eth" and value.$.balance > 10"#, true),
+            (r#"value.$.balance = "12.5""#, false),
+            (r#"value.$.chain != "btc""#, true),
+            (r#"value.$.missing != "btc""#, false),
+            (r#"not value.$.missing = "btc""#, true),
+            (r#"value.$.tags[1] = "b""#, true),
+            (r#"value.$.tags[2] = "b" or value.$.cold = false"#, true),
+            (r#"value.$.note = null and value.$.chain != null"#, true),
+            (r#"value.$[0] = "zero""#, false),
+            (r#"value > "{""#, true),
+        ];
+        for (filter, expected) in cases {
+            let q = parse(&format!("have where {}", filter));
+            assert_eq!(q.matches("c", "wallet:main", wallet, "t"), expected, "{}", filter);
+        }
+        assert!(!parse("where value.$.a = 1").matches("c", "k", "not json", "t"));
+        assert!(parse("where not value.$.a = 1").matches("c", "k", "not json", "t"));
+        assert!(!parse("in c2").matches("c", "k", "v", "t"));
+        assert!(!parse("since 2025-02").matches("c", "k", "v", "2025-01-31T00:00:00+00:00"));
+
+        assert!(like("a\\_c", "A_C") && !like("a\\_c", "abc"));
+        assert!(like("%", "") && like("_é%", "xé") && !like("_", ""));
+    }
+
+    /// Números que siguen a `marker` (`?` o `$`) en el SQL.
+    fn placeholders(sql: &str, marker: char) -> std::collections::BTreeSet<usize> {
+        sql.split(marker)
+            .skip(1)
+            .filter_map(|rest| rest.split(|c: char| !c.is_ascii_digit()).next()?.parse().ok())
+            .collect()
+    }
+
+    #[test]
+    fn get_filter_sql_depends_only_on_its_shape() {
+        let mut rng = fuzz::rng(18);
+        for _ in 0..fuzz::CASES {
+            let filter = fuzz::filter(&mut rng, true);
+            let Some(shape) = fuzz::skeleton(&filter) else {
+                // solo un path de `json:` inválido puede hacer fallar la conversión
+                assert!(Query::try_from(&filter).is_err(), "{:?}", filter);
+                continue;
+            };
+            let query = Query::try_from(&filter).unwrap_or_else(|e| panic!("{:?}: {}", filter, e));
+            let shape = Query::try_from(&shape).unwrap();
+            for verb in Verb::ALL {
+                for dialect in [Dialect::Sqlite, Dialect::Postgres] {
+                    let (got, expected) = (query.to_sql(verb, dialect), shape.to_sql(verb, dialect));
+                    assert_eq!(got.sql, expected.sql, "{:?}", filter);
+                    assert_eq!(got.params.len(), expected.params.len(), "{:?}", filter);
+                }
+            }
+            // el texto del usuario viaja tal cual como parámetro
+            let params = query.to_sql(Verb::Have, Dialect::Sqlite).params;
+            for text in [&filter.context_id, &filter.since, &filter.until].into_iter().flatten() {
+                assert!(params.contains(&Param::Text(text.clone())), "{:?}", filter);
+            }
+        }
+    }
+
+    #[test]
+    fn every_parsed_query_binds_all_of_its_literals() {
+        let mut rng = fuzz::rng(16);
+        let mut parsed = 0;
+        for case in 0..fuzz::CASES {
+            let text = if case % 2 == 0 { fuzz::soup(&mut rng) } else { fuzz::query(&mut rng, true) };
+            let Ok(query) = text.parse::<Query>() else { continue };
+            parsed += 1;
+            for verb in Verb::ALL {
+                for (dialect, marker) in [(Dialect::Sqlite, '?'), (Dialect::Postgres, '$')] {
+                    let c = query.to_sql(verb, dialect);
+                    let expected: std::collections::BTreeSet<usize> = (1..=c.params.len()).collect();
+                    assert_eq!(placeholders(&c.sql, marker), expected, "{} → {}", text, c.sql);
+                    // lo único entre comillas simples son constantes del compilador
+                    let quoted: Vec<&str> = c.sql.split('\'').skip(1).step_by(2).collect();
+                    assert!(
+                        quoted.iter().all(|q| ["\\", "text", "integer", "real", "true", "false", "null", "string", "number", "boolean", "array"].contains(q)),
+                        "{} → {}", text, c.sql
+                    );
+                }
+            }
+        }
+        assert!(parsed > fuzz::CASES / 4, "only {} queries parsed", parsed);
+    }
+
+    #[test]
+    fn json_paths_round_trip_or_fail() {
+        for (input, expected) in [
+            ("type", Some("$.type")),
+            ("$.meta.chain", Some("$.meta.chain")),
+            ("tags[0].name", Some("$.tags[0].name")),
+            ("$", Some("$")),
+            ("", None),
+            ("a b", None),
+            ("0", None),
+            ("a..b", None),
+            ("a[-1]", None),
+            ("a') || 1=1 --", None),
+            ("$.a'", None),
+        ] {
+            assert_eq!(input.parse::<JsonPath>().ok().map(|p| p.to_string()).as_deref(), expected, "{}", input);
+        }
+        let mut rng = fuzz::rng(19);
+        for _ in 0..fuzz::CASES {
+            if let Ok(path) = fuzz::text(&mut rng, true).parse::<JsonPath>() {
+                assert_eq!(path.to_string().parse::<JsonPath>().unwrap(), path);
+                assert!(path.sqlite().chars().all(|c| c.is_ascii_alphanumeric() || "$._-[]".contains(c)), "{}", path);
+            }
+        }
+    }
+}
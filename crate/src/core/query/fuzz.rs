@@ -0,0 +1,209 @@
+//this.me/crate/src/core/query/fuzz.rs
+// Generadores para las pruebas de propiedades de core::query: filtros de `GetFilter` con su
+// mini-sintaxis (`like:`, `json:<path>=`), consultas del lenguaje y ruido de tokens.
+// Las entradas se arman con metacaracteres de SQL, LIKE, paths y comillas, más trozos de `ROWS`
+// para que los filtros también encuentren filas. Todo sale de una semilla fija: un fallo se
+// reproduce corriendo la prueba otra vez.
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::core::model::GetFilter;
+use crate::core::verbs::{Verb, VerbSelector};
+use super::{JsonPath, Segment};
+
+/// Casos por prueba.
+pub const CASES: usize = 2000;
+
+/// Filas de ejemplo: (verbo, contexto, key, value). Valores JSON de todos los tipos y texto que no lo es.
+pub const ROWS: [(Verb, &str, &str, &str); 12] = [
+    (Verb::Have, "c1", "wallet:main", r#"{"type":"eth","n":3,"tags":["a","b"],"meta":{"chain":"main"},"flag":true}"#),
+    (Verb::Have, "c1", "Wallet:cold", r#"{"type":"btc","n":12.5,"tags":["b"],"flag":false,"meta":null}"#),
+    (Verb::Have, "c2", "wallet:main", r#"{"type":"eth","n":"3"}"#),
+    (Verb::Be, "c1", "100%", "100%"),
+    (Verb::Be, "c1", "a_b", r"C:\tmp"),
+    (Verb::Be, "c2", "x'y", "'; DROP TABLE have; --"),
+    (Verb::React, "c1", "post:1", "🔥"),
+    (Verb::Communicate, "c1", "group", "É é"),
+    (Verb::Do, "c1", "list", "[1,2]"),
+    (Verb::At, "c1", "nothing", "null"),
+    (Verb::Relate, "c1", "quoted", r#""str""#),
+    (Verb::Relate, "c2", "zero", r#"{"0":"zero"}"#),
+];
+
+const PIECES: [&str; 32] = [
+    "'", "\"", ";", "--", "%", "_", "\\", "$", "?1", "$1", ".", "[", "]", "=", " ", "json:", "like:",
+    "DROP TABLE have", "é", "É", "🔥", "wallet", "main", "eth", "type", "a", "b", "1", "0", ":", "c1", "2025-01-01T00:00:0",
+];
+
+const PATHS: [&str; 17] = [
+    "type", "meta.chain", "tags[0]", "tags[1].x", "$.type", "$", "$[0]", "a b", "0", "a..b", "a[", "a[-1]",
+    "x'); DROP TABLE have; --", "", "é", "a-b", "_a",
+];
+
+const FIELDS: [&str; 10] = [
+    "key", "value", "value.$", "value.$.type", "value.$.n", "value.$.meta.chain", "value.$.tags[0]", "value.$.tags[1]",
+    "value.$.flag", "value.$[0]",
+];
+
+const OPS: [&str; 8] = ["=", "!=", "~", "!~", "<", "<=", ">", ">="];
+
+const SOUP: [&str; 30] = [
+    "have", "be,have", "all", "where", "key", "value", "value.$.a", "value.$.tags[0]", "value.$", "=", "!=", "~", "<",
+    ">=", "and", "or", "not", "(", ")", "\"x'y\"", "'a\\%'", "34", "-1.5", "true", "null", "in", "since", "limit", "5", ";",
+];
+
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+fn pick<'a>(rng: &mut StdRng, items: &[&'a str]) -> &'a str {
+    items[rng.gen_range(0..items.len())]
+}
+
+fn maybe(rng: &mut StdRng, make: impl FnOnce(&mut StdRng) -> String) -> Option<String> {
+    if rng.gen_bool(0.5) { Some(make(rng)) } else { None }
+}
+
+/// Texto hostil. `nul` agrega `\0`, que el LIKE de SQLite trata como fin de cadena
+/// (solo sirve donde no se compara contra la evaluación en memoria).
+pub fn text(rng: &mut StdRng, nul: bool) -> String {
+    (0..rng.gen_range(0..6))
+        .map(|_| if nul && rng.gen_bool(0.05) { "\0" } else { pick(rng, &PIECES) })
+        .collect()
+}
+
+fn mini(rng: &mut StdRng, nul: bool, json: bool) -> String {
+    match rng.gen_range(0..4) {
+        0 => format!("like:{}", text(rng, nul)),
+        1 if json => {
+            let path = if rng.gen_bool(0.7) { pick(rng, &PATHS).to_string() } else { text(rng, nul) };
+            format!("json:{}={}", path, text(rng, nul))
+        }
+        2 => pick(rng, &ROWS.map(|r| if json { r.3 } else { r.2 })).to_string(),
+        _ => text(rng, nul),
+    }
+}
+
+fn selector(rng: &mut StdRng) -> VerbSelector {
+    match rng.gen_range(0..3) {
+        0 => VerbSelector::All,
+        1 => VerbSelector::One(Verb::ALL[rng.gen_range(0..Verb::ALL.len())]),
+        _ => VerbSelector::Many((0..rng.gen_range(1..4)).map(|_| Verb::ALL[rng.gen_range(0..Verb::ALL.len())]).collect()),
+    }
+}
+
+pub fn filter(rng: &mut StdRng, nul: bool) -> GetFilter {
+    GetFilter {
+        verb: selector(rng),
+        key: maybe(rng, |rng| mini(rng, nul, false)),
+        value: maybe(rng, |rng| mini(rng, nul, true)),
+        context_id: maybe(rng, |rng| if rng.gen_bool(0.5) { pick(rng, &["c1", "c2"]).to_string() } else { text(rng, nul) }),
+        limit: if rng.gen_bool(0.3) { Some(rng.gen_range(0..5)) } else { None },
+        offset: if rng.gen_bool(0.3) { Some(rng.gen_range(0..3)) } else { None },
+        since: maybe(rng, |rng| text(rng, nul)),
+        until: maybe(rng, |rng| text(rng, nul)),
+    }
+}
+
+/// `filter` con todo el texto cambiado por `x` y las claves de los paths por `k` (los índices quedan):
+/// la misma forma con otro contenido. `None` si el path de `json:` no es válido.
+pub fn skeleton(filter: &GetFilter) -> Option<GetFilter> {
+    let x = |s: &Option<String>| s.as_ref().map(|_| "x".to_string());
+    let key = filter.key.as_deref().map(|k| if k.starts_with("like:") { "like:x".to_string() } else { "x".to_string() });
+    let value = match filter.value.as_deref() {
+        None => None,
+        Some(v) => Some(match v.strip_prefix("json:").and_then(|s| s.split_once('=')) {
+            Some((path, _)) => {
+                let path: JsonPath = path.parse().ok()?;
+                let segments = path
+                    .segments
+                    .iter()
+                    .map(|s| match s {
+                        Segment::Key(_) => Segment::Key("k".into()),
+                        Segment::Index(i) => Segment::Index(*i),
+                    })
+                    .collect();
+                format!("json:{}=x", JsonPath { segments })
+            }
+            None if v.starts_with("like:") => "like:x".to_string(),
+            None => "x".to_string(),
+        }),
+    };
+    Some(GetFilter {
+        verb: filter.verb.clone(),
+        key,
+        value,
+        context_id: x(&filter.context_id),
+        limit: filter.limit,
+        offset: filter.offset,
+        since: x(&filter.since),
+        until: x(&filter.until),
+    })
+}
+
+/// Literal de texto del lenguaje que, tras el lexer, vuelve a ser `s`.
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn comparison(rng: &mut StdRng, nul: bool) -> String {
+    let field = pick(rng, &FIELDS);
+    let op = pick(rng, &OPS);
+    let json = field.starts_with("value.$");
+    let literal = match rng.gen_range(0..6) {
+        0 if json => pick(rng, &["3", "12.5", "-1", "0"]).to_string(),
+        1 if json => pick(rng, &["true", "false", "null"]).to_string(),
+        2 => quoted(pick(rng, &["eth", "main", "a", "wallet%", "%a%", "W_llet:%", "100\\%", "str", "zero", "🔥"])),
+        _ => quoted(&text(rng, nul)),
+    };
+    format!("{} {} {}", field, op, literal)
+}
+
+fn expr(rng: &mut StdRng, nul: bool, depth: usize) -> String {
+    match if depth > 2 { 0 } else { rng.gen_range(0..6) } {
+        0..=2 => comparison(rng, nul),
+        3 => format!("{} and {}", expr(rng, nul, depth + 1), expr(rng, nul, depth + 1)),
+        4 => format!("{} or {}", expr(rng, nul, depth + 1), expr(rng, nul, depth + 1)),
+        _ if rng.gen_bool(0.5) => format!("not {}", expr(rng, nul, depth + 1)),
+        _ => format!("({})", expr(rng, nul, depth + 1)),
+    }
+}
+
+/// Consulta bien formada según la gramática (salvo combinaciones que el parser rechaza a propósito).
+pub fn query(rng: &mut StdRng, nul: bool) -> String {
+    let mut q = match rng.gen_range(0..4) {
+        0 => String::new(),
+        1 => "all ".to_string(),
+        2 => format!("{} ", Verb::ALL[rng.gen_range(0..Verb::ALL.len())]),
+        _ => "have,be,relate ".to_string(),
+    };
+    if rng.gen_bool(0.8) {
+        q.push_str("where ");
+        q.push_str(&expr(rng, nul, 0));
+    }
+    if rng.gen_bool(0.3) {
+        q.push_str(&format!(" in {}", quoted(pick(rng, &["c1", "c2", "x'y"]))));
+    }
+    if rng.gen_bool(0.2) {
+        q.push_str(&format!(" since {}", quoted(&text(rng, nul))));
+    }
+    if rng.gen_bool(0.2) {
+        q.push_str(&format!(" until {}", quoted(&text(rng, nul))));
+    }
+    if rng.gen_bool(0.3) {
+        q.push_str(&format!(" limit {}", rng.gen_range(0..5)));
+    }
+    if rng.gen_bool(0.2) {
+        q.push_str(&format!(" offset {}", rng.gen_range(0..3)));
+    }
+    q
+}
+
+/// Tokens y caracteres sueltos sin gramática, para el parser.
+pub fn soup(rng: &mut StdRng) -> String {
+    (0..rng.gen_range(0..12))
+        .map(|_| {
+            let token = if rng.gen_bool(0.7) { pick(rng, &SOUP) } else { pick(rng, &PIECES) };
+            if rng.gen_bool(0.7) { format!("{} ", token) } else { token.to_string() }
+        })
+        .collect()
+}
@@ -0,0 +1,355 @@
+//this.me/crate/src/core/recovery.rs
+// Recuperación social: el seed ed25519 de la llave `main` se parte en n shares de Shamir sobre GF(256)
+// (crate `sharks`), de los que bastan k para reconstruirlo.
+// - A un guardián con identidad .me, el share va cifrado a su public key: X25519 entre una llave
+//   efímera y la forma Montgomery de su llave ed25519, SHA-256 del secreto compartido como llave del
+//   `Envelope` (ChaCha20-Poly1305). Solo su llave `main` lo abre (`Me::open_recovery_share`).
+// - A un guardián sin identidad, el share se entrega en claro para imprimirlo como sello QR con
+//   passphrase (`qrcode::render_share_qr`).
+// Quién tiene cada share queda en el log como entradas `relate` `recovery:<guardián>`
+// (ver `recovery_set`), para que el dueño pueda auditar su conjunto de recuperación.
+use std::collections::BTreeSet;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use ed25519_dalek::VerifyingKey;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sharks::{Share, Sharks};
+use super::keys::KeyPair;
+use super::model::Entry;
+use super::verbs::Verb;
+use crate::utils::crypto::CryptoError;
+use crate::utils::envelope::{AeadId, Envelope};
+use crate::utils::me_error::MeError;
+
+/// Versión del formato JSON de `RecoveryShare` y `SealedShare`.
+pub const SHARE_VERSION: u32 = 1;
+/// Prefijo del key de las entradas `relate` que registran a los guardianes.
+pub const RECOVERY_KEY_PREFIX: &str = "recovery:";
+const SHARE_KDF_DOMAIN: &[u8] = b"this.me/recovery-share/v1";
+
+/// Un share del seed. Lleva lo necesario para validar la combinación sin el store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryShare {
+    pub version: u32,
+    /// Identifica el reparto: shares de repartos distintos no se combinan.
+    pub set_id: String,
+    /// Public key de la identidad; `combine` comprueba que el seed reconstruido la produce.
+    pub public_key: String,
+    pub threshold: u8,
+    pub total: u8,
+    /// x del polinomio (1..=total).
+    pub index: u8,
+    /// Share de `sharks` (x ‖ y) en base64.
+    pub share: String,
+}
+
+impl RecoveryShare {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Share cifrado a la llave `main` de un guardián.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedShare {
+    pub version: u32,
+    pub guardian: String,
+    pub guardian_public_key: String,
+    /// Public key X25519 efímera (base64).
+    pub ephemeral: String,
+    /// `Envelope` con el JSON del share (base64).
+    pub envelope: String,
+}
+
+impl SealedShare {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// A quién se entrega un share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Guardian {
+    /// Otra identidad .me (username y public key `main` en base64).
+    Identity { username: String, public_key: String },
+    /// Sin identidad: el share se imprime (`label` lo nombra en el log, p. ej. "safe-box").
+    Printed { label: String },
+}
+
+impl Guardian {
+    pub fn name(&self) -> &str {
+        match self {
+            Guardian::Identity { username, .. } => username,
+            Guardian::Printed { label } => label,
+        }
+    }
+}
+
+/// Resultado de repartir un share a un guardián.
+#[derive(Debug, Clone)]
+pub enum Delivery {
+    Sealed(SealedShare),
+    /// Para imprimir con `qrcode::render_share_qr`; no guardarlo en claro.
+    Printed { label: String, share: RecoveryShare },
+}
+
+/// Registro de un guardián, tal como quedó en el log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardianRecord {
+    #[serde(default)]
+    pub guardian: String,
+    pub set_id: String,
+    pub index: u8,
+    pub threshold: u8,
+    pub total: u8,
+    /// `"identity"` o `"printed"`.
+    pub kind: String,
+    /// Public key del guardián (solo `identity`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+impl GuardianRecord {
+    pub fn new(guardian: &Guardian, share: &RecoveryShare) -> Self {
+        let (kind, public_key) = match guardian {
+            Guardian::Identity { public_key, .. } => ("identity", Some(public_key.clone())),
+            Guardian::Printed { .. } => ("printed", None),
+        };
+        Self {
+            guardian: guardian.name().to_string(),
+            set_id: share.set_id.clone(),
+            index: share.index,
+            threshold: share.threshold,
+            total: share.total,
+            kind: kind.to_string(),
+            public_key,
+            timestamp: String::new(),
+        }
+    }
+
+    /// Valor de la entrada `relate` (guardián y timestamp ya están en key y fila).
+    pub fn to_value(&self) -> Result<String, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("guardian");
+            object.remove("timestamp");
+        }
+        Ok(value.to_string())
+    }
+}
+
+/// Parte `seed` en `total` shares de los que bastan `threshold`.
+pub fn split(seed: &[u8; 32], public_key: &str, threshold: u8, total: u8) -> Result<Vec<RecoveryShare>, MeError> {
+    if threshold < 2 || threshold > total {
+        return Err(MeError::Validation(format!(
+            "A recovery set needs 2 <= threshold <= guardians (got {} of {})",
+            threshold, total
+        )));
+    }
+    let mut set_id = [0u8; 8];
+    OsRng.fill_bytes(&mut set_id);
+    let set_id = hex::encode(set_id);
+    Ok(Sharks(threshold)
+        .dealer(seed)
+        .take(total as usize)
+        .map(|share| RecoveryShare {
+            version: SHARE_VERSION,
+            set_id: set_id.clone(),
+            public_key: public_key.to_string(),
+            threshold,
+            total,
+            index: share.x.0,
+            share: STANDARD.encode(Vec::from(&share)),
+        })
+        .collect())
+}
+
+/// Reconstruye el seed con al menos `threshold` shares distintos del mismo reparto.
+pub fn combine(shares: &[RecoveryShare]) -> Result<[u8; 32], MeError> {
+    let first = shares.first().ok_or_else(|| MeError::Validation("No recovery shares given".into()))?;
+    if let Some(other) = shares.iter().find(|s| {
+        (s.version, &s.set_id, &s.public_key, s.threshold, s.total) != (first.version, &first.set_id, &first.public_key, first.threshold, first.total)
+    }) {
+        return Err(MeError::Validation(format!("Share {} belongs to another recovery set", other.index)));
+    }
+    let distinct: BTreeSet<u8> = shares.iter().map(|s| s.index).collect();
+    if distinct.len() < first.threshold as usize {
+        return Err(MeError::Validation(format!(
+            "{} distinct shares given, {} needed",
+            distinct.len(),
+            first.threshold
+        )));
+    }
+    let decoded = shares
+        .iter()
+        .map(|s| {
+            let bytes = STANDARD.decode(&s.share)?;
+            let share = Share::try_from(bytes.as_slice()).map_err(|e| MeError::CorruptData(format!("share {}: {}", s.index, e)))?;
+            if share.x.0 != s.index || share.y.len() != 32 {
+                return Err(MeError::CorruptData(format!("share {} is malformed", s.index)));
+            }
+            Ok(share)
+        })
+        .collect::<Result<Vec<Share>, MeError>>()?;
+    let secret = Sharks(first.threshold)
+        .recover(&decoded)
+        .map_err(|e| MeError::CorruptData(e.to_string()))?;
+    let seed: [u8; 32] = secret
+        .try_into()
+        .map_err(|_| MeError::CorruptData("recovered secret is not 32 bytes".into()))?;
+    if KeyPair::from_secret(&seed).public_key_base64() != first.public_key {
+        return Err(MeError::CorruptData("the shares do not rebuild the identity key".into()));
+    }
+    Ok(seed)
+}
+
+/// Cifra `share` a la public key ed25519 (base64) de `guardian`.
+pub fn seal_share(share: &RecoveryShare, guardian: &str, guardian_public_key: &str) -> Result<SealedShare, MeError> {
+    let recipient = montgomery(guardian_public_key)?;
+    let mut ephemeral_secret = [0u8; 32];
+    OsRng.fill_bytes(&mut ephemeral_secret);
+    let ephemeral = MontgomeryPoint::mul_base_clamped(ephemeral_secret);
+    let key = share_key(&recipient.mul_clamped(ephemeral_secret), &ephemeral, &recipient)?;
+    let envelope = Envelope::seal_with_key(share.to_json()?.as_bytes(), &key, AeadId::ChaCha20Poly1305, ephemeral.as_bytes())?;
+    Ok(SealedShare {
+        version: SHARE_VERSION,
+        guardian: guardian.to_string(),
+        guardian_public_key: guardian_public_key.to_string(),
+        ephemeral: STANDARD.encode(ephemeral.as_bytes()),
+        envelope: envelope.to_base64()?,
+    })
+}
+
+/// Abre un `SealedShare` con la llave `main` del guardián.
+pub fn open_share(sealed: &SealedShare, guardian_key: &KeyPair) -> Result<RecoveryShare, MeError> {
+    if sealed.version != SHARE_VERSION {
+        return Err(MeError::Validation(format!("Unsupported share version {}", sealed.version)));
+    }
+    let recipient = guardian_key.verifying_key().to_montgomery();
+    if STANDARD.encode(guardian_key.verifying_key().to_bytes()) != sealed.guardian_public_key {
+        return Err(MeError::Validation(format!("This share is sealed to '{}', not to this key", sealed.guardian)));
+    }
+    let ephemeral: [u8; 32] = STANDARD
+        .decode(&sealed.ephemeral)?
+        .try_into()
+        .map_err(|_| MeError::CorruptData("invalid ephemeral key".into()))?;
+    let ephemeral = MontgomeryPoint(ephemeral);
+    let envelope = Envelope::from_base64(&sealed.envelope).map_err(|e| MeError::CorruptData(e.to_string()))?;
+    if envelope.aad != ephemeral.as_bytes() {
+        return Err(MeError::CorruptData("share envelope does not match its ephemeral key".into()));
+    }
+    let key = share_key(&ephemeral.mul_clamped(guardian_key.signing_key.to_scalar_bytes()), &ephemeral, &recipient)?;
+    let json = envelope.open_with_key(&key).map_err(|e| match e {
+        CryptoError::DecryptionFailed => MeError::CorruptData("share cannot be opened with this key".into()),
+        other => MeError::CorruptData(other.to_string()),
+    })?;
+    RecoveryShare::from_json(&String::from_utf8_lossy(&json)).map_err(|e| MeError::CorruptData(e.to_string()))
+}
+
+/// Guardianes del reparto más reciente, a partir del log del contexto de la identidad.
+pub fn recovery_set(entries: &[Entry]) -> Vec<GuardianRecord> {
+    let records: Vec<GuardianRecord> = entries
+        .iter()
+        .filter(|e| e.verb == Verb::Relate)
+        .filter_map(|e| {
+            let guardian = e.key.strip_prefix(RECOVERY_KEY_PREFIX)?;
+            let mut record: GuardianRecord = serde_json::from_str(&e.value).ok()?;
+            record.guardian = guardian.to_string();
+            record.timestamp = e.timestamp.clone();
+            Some(record)
+        })
+        .collect();
+    let Some(latest) = records.last().map(|r| r.set_id.clone()) else {
+        return Vec::new();
+    };
+    let mut latest: Vec<GuardianRecord> = records.into_iter().filter(|r| r.set_id == latest).collect();
+    latest.sort_by_key(|r| r.index);
+    latest
+}
+
+fn montgomery(public_key_b64: &str) -> Result<MontgomeryPoint, MeError> {
+    let bytes: [u8; 32] = STANDARD
+        .decode(public_key_b64)
+        .map_err(|e| MeError::Validation(format!("invalid guardian public key: {}", e)))?
+        .try_into()
+        .map_err(|_| MeError::Validation("invalid guardian public key length".into()))?;
+    let key = VerifyingKey::from_bytes(&bytes).map_err(|e| MeError::Validation(format!("invalid guardian public key: {}", e)))?;
+    if key.is_weak() {
+        return Err(MeError::Validation("guardian public key is a small-order point".into()));
+    }
+    Ok(key.to_montgomery())
+}
+
+fn share_key(shared: &MontgomeryPoint, ephemeral: &MontgomeryPoint, recipient: &MontgomeryPoint) -> Result<[u8; 32], MeError> {
+    if shared.as_bytes() == &[0u8; 32] {
+        return Err(MeError::CorruptData("degenerate shared secret".into()));
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(SHARE_KDF_DOMAIN);
+    hasher.update(shared.as_bytes());
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(recipient.as_bytes());
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_subset_rebuilds_the_seed() {
+        let owner = KeyPair::generate();
+        let seed = owner.signing_key.to_bytes();
+        let shares = split(&seed, &owner.public_key_base64(), 3, 5).unwrap();
+        assert_eq!(shares.iter().map(|s| s.index).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+        assert_eq!(combine(&shares[..3]).unwrap(), seed);
+        assert_eq!(combine(&[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap(), seed);
+        assert!(matches!(combine(&shares[..2]), Err(MeError::Validation(_))));
+        // repetir un share no cuenta dos veces
+        assert!(combine(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err());
+
+        let other = split(&seed, &owner.public_key_base64(), 3, 5).unwrap();
+        assert!(combine(&[shares[0].clone(), shares[1].clone(), other[2].clone()]).is_err());
+
+        let mut tampered = shares[..3].to_vec();
+        let mut bytes = STANDARD.decode(&tampered[1].share).unwrap();
+        bytes[5] ^= 1;
+        tampered[1].share = STANDARD.encode(bytes);
+        assert!(matches!(combine(&tampered), Err(MeError::CorruptData(_))));
+
+        assert!(split(&seed, "pk", 1, 3).is_err());
+        assert!(split(&seed, "pk", 4, 3).is_err());
+    }
+
+    #[test]
+    fn sealed_shares_open_only_with_the_guardian_key() {
+        let owner = KeyPair::generate();
+        let guardian = KeyPair::generate();
+        let share = split(&owner.signing_key.to_bytes(), &owner.public_key_base64(), 2, 2).unwrap().remove(0);
+
+        let sealed = seal_share(&share, "alice", &guardian.public_key_base64()).unwrap();
+        assert!(!sealed.envelope.contains(&share.share));
+        let sealed = SealedShare::from_json(&sealed.to_json().unwrap()).unwrap();
+        assert_eq!(open_share(&sealed, &guardian).unwrap(), share);
+
+        assert!(open_share(&sealed, &KeyPair::generate()).is_err());
+        let mut forged = sealed.clone();
+        forged.guardian_public_key = owner.public_key_base64();
+        assert!(open_share(&forged, &owner).is_err());
+        assert!(seal_share(&share, "bad", "AAAA").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//this.me/crate/src/core/sign.rs
+// Firmas ed25519 de una identidad .me y firmas "detached" (archivo .signature junto al mensaje).
+use std::path::{Path, PathBuf};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Serialize, Deserialize};
+use crate::utils::me_error::MeError;
+
+/// Versión del formato JSON de `DetachedSignature`.
+pub const DETACHED_SIGNATURE_VERSION: u32 = 1;
+
+/// Verifica `signature` sobre `message` con una public key en base64 (el formato de `Me::public_key`).
+pub fn verify(public_key_b64: &str, message: &[u8], signature: &Signature) -> Result<(), MeError> {
+    let bytes = STANDARD.decode(public_key_b64)?;
+    let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| MeError::InvalidSignature("invalid public key length".into()))?;
+    let key = VerifyingKey::from_bytes(&bytes)?;
+    key.verify_strict(message, signature)?;
+    Ok(())
+}
+
+/// Firma separada del mensaje: quién firmó, con qué llave y la firma en base64.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    pub version: u32,
+    pub username: String,
+    pub authority: String,
+    pub public_key: String,
+    pub signature: String,
+    pub created_at: String,
+}
+
+impl DetachedSignature {
+    pub fn new(username: &str, authority: &str, public_key: &str, signature: &Signature) -> Self {
+        Self {
+            version: DETACHED_SIGNATURE_VERSION,
+            username: username.to_string(),
+            authority: authority.to_string(),
+            public_key: public_key.to_string(),
+            signature: STANDARD.encode(signature.to_bytes()),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn signature(&self) -> Result<Signature, MeError> {
+        let bytes = STANDARD.decode(&self.signature)?;
+        Ok(Signature::from_slice(&bytes)?)
+    }
+
+    /// Verifica contra la public key incluida. Quien verifica decide si confía en esa llave
+    /// (p. ej. comparándola con `get_public_key` de la identidad).
+    pub fn verify(&self, message: &[u8]) -> Result<(), MeError> {
+        if self.version != DETACHED_SIGNATURE_VERSION {
+            return Err(MeError::InvalidSignature(format!("unsupported signature version {}", self.version)));
+        }
+        verify(&self.public_key, message, &self.signature()?)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), MeError> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, MeError> {
+        Ok(Self::from_json(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// `message.txt` → `message.txt.signature`
+pub fn signature_path_for(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".signature");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::core::{KeyPair, Me};
+    use crate::db::memory::MemoryStore;
+
+    #[tokio::test]
+    async fn detached_signatures_round_trip_through_files() {
+        let store = Arc::new(MemoryStore::new());
+        let mut me = Me::create(store, "suign", "123456").await.unwrap();
+        me.add_key("cleaker", KeyPair::generate(), "123456").await.unwrap();
+
+        let sig = me.sign(b"statement").unwrap();
+        assert!(verify(&me.public_key, b"statement", &sig).is_ok());
+        assert!(verify(&me.public_key, b"other statement", &sig).is_err());
+
+        let detached = me.sign_detached(Some("cleaker"), b"statement").unwrap();
+        assert_eq!(detached.username, "suign");
+        assert_eq!(detached.public_key, me.get_public_key(Some("cleaker")).unwrap());
+
+        let dir = std::env::temp_dir().join(format!("this-me-sign-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = signature_path_for(&dir.join("message.txt"));
+        assert!(path.ends_with("message.txt.signature"));
+        detached.write(&path).unwrap();
+        let read = DetachedSignature::read(&path).unwrap();
+        assert_eq!(read, detached);
+        assert!(read.verify(b"statement").is_ok());
+        assert!(read.verify(b"tampered").is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,13 +1,48 @@
 //this.me/crate/src/core/store.rs
 use async_trait::async_trait;
 use super::model::{Entry, GetFilter};
+use super::verbs::Verb;
+use super::arc::ArcEntry;
+use super::query::Query;
+use crate::utils::me_error::MeError;
+pub mod conformance;
 #[async_trait]
 pub trait MeStore: Send + Sync {
     // identity
-    async fn create_identity(&self, username: &str, public_key: &str, encrypted_private_key: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-    async fn load_keys(&self, username: &str) -> Result<(String /*public*/, String /*encrypted_priv*/ ), Box<dyn std::error::Error + Send + Sync>>;
-    async fn update_encrypted_private(&self, username: &str, encrypted: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// Falla con `MeError::AlreadyExists` si el username ya existe; nunca sobreescribe la fila.
+    async fn create_identity(&self, username: &str, public_key: &str, encrypted_private_key: &str) -> Result<(), MeError>;
+    async fn load_keys(&self, username: &str) -> Result<(String /*public*/, String /*encrypted_priv*/ ), MeError>;
+    async fn update_encrypted_private(&self, username: &str, encrypted: &str) -> Result<(), MeError>;
+    /// Reemplaza en una sola transacción el cifrado de la llave `main` y el de cada `(authority, encrypted)`.
+    /// Todo o nada: si el username o alguna autoridad no existe, no cambia ninguna fila.
+    /// `keys` tiene que cubrir exactamente las autoridades guardadas (las de `list_keys`), comprobado
+    /// dentro de la transacción; si otra escritura agregó una llave en medio, `MeError::KeysChanged`.
+    async fn rewrap_keys(&self, username: &str, encrypted_private_key: &str, keys: &[(String, String)]) -> Result<(), MeError>;
+    // authority keys (tabla `keys`; la llave `main` vive en `me`)
+    async fn insert_key(&self, username: &str, authority: &str, public_key: &str, encrypted_private_key: &str) -> Result<(), MeError>;
+    async fn load_key(&self, username: &str, authority: &str) -> Result<(String /*public*/, String /*encrypted_priv*/ ), MeError>;
+    async fn list_keys(&self, username: &str) -> Result<Vec<String> /*authorities*/, MeError>;
+    async fn delete_key(&self, username: &str, authority: &str) -> Result<(), MeError>;
     // verbs
-    async fn insert(&self, verb: &str, context_id: &str, key: &str, value: &str, timestamp: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
-    async fn get(&self, filter: &GetFilter) -> Result<Vec<Entry>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn insert(&self, verb: Verb, context_id: &str, key: &str, value: &str, timestamp: &str) -> Result<(), MeError>;
+    async fn get(&self, filter: &GetFilter) -> Result<Vec<Entry>, MeError>;
+    /// Consulta del lenguaje de `core::query` (compilada con `Query::to_sql` o evaluada con `Query::matches`);
+    /// orden cronológico inverso, `limit`/`offset` globales.
+    async fn query(&self, query: &Query) -> Result<Vec<Entry>, MeError>;
+    // log firmado (ver core::chain)
+    /// Guarda `entry` tal cual, con su `proof` y `correlation_id`.
+    async fn insert_signed(&self, context_id: &str, entry: &Entry) -> Result<(), MeError>;
+    /// Todas las entradas del contexto (todas las tablas, sin LIMIT), en orden cronológico ascendente.
+    async fn log(&self, context_id: &str) -> Result<Vec<Entry>, MeError>;
+    /// Última entrada firmada del contexto (la cabeza de la cadena).
+    async fn log_head(&self, context_id: &str) -> Result<Option<Entry>, MeError>;
+    /// `context_id` distintos con filas en alguna tabla de verbo o en `arc`, ordenados.
+    async fn contexts(&self) -> Result<Vec<String>, MeError>;
+    // ARC (tabla `arc`, ver core::arc)
+    async fn insert_arc(&self, context_id: &str, entry: &ArcEntry) -> Result<(), MeError>;
+    /// Muestras de `actor` hacia `target` (solo esa dirección) en orden cronológico ascendente;
+    /// `since`/`until` son inclusivos.
+    async fn arc_timeline(&self, context_id: &str, actor: &str, target: &str, since: Option<&str>, until: Option<&str>) -> Result<Vec<ArcEntry>, MeError>;
+    /// Todas las muestras del contexto (cualquier actor y target) en orden cronológico ascendente.
+    async fn arc_log(&self, context_id: &str) -> Result<Vec<ArcEntry>, MeError>;
 }
\ No newline at end of file
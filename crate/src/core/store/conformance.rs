@@ -0,0 +1,522 @@
+//this.me/crate/src/core/store/conformance.rs
+// Suite de conformidad genérica para backends de MeStore.
+// Cualquier implementación (PgStore, SqliteStore, MemoryStore o de terceros) debe pasarla:
+//
+//     let store = MyStore::new(/* base vacía */);
+//     this_me::core::store::conformance::run(&store).await.unwrap();
+//
+// Espera un store VACÍO: los conteos asumen que no hay filas previas.
+use super::MeStore;
+use crate::core::model::{Entry, GetFilter, Proof};
+use crate::core::verbs::{Verb, VerbSelector};
+use crate::core::arc::{ArcEntry, ArcScores};
+use crate::core::query::Query;
+use crate::utils::me_error::MeError;
+
+type CheckResult = Result<(), String>;
+
+macro_rules! check {
+    ($name:expr, $cond:expr, $($msg:tt)+) => {
+        if !$cond {
+            return Err(format!("[{}] {}", $name, format!($($msg)+)));
+        }
+    };
+}
+
+/// Ejecuta todos los chequeos en orden y devuelve el primero que falle.
+pub async fn run<S: MeStore>(store: &S) -> CheckResult {
+    identity_round_trip(store).await?;
+    duplicate_username(store).await?;
+    authority_keys(store).await?;
+    rewrap_keys(store).await?;
+    verb_selector(store).await?;
+    verb_column_mapping(store).await?;
+    ordering(store).await?;
+    filters(store).await?;
+    pattern_filters(store).await?;
+    default_limit(store).await?;
+    all_verbs(store).await?;
+    signed_log(store).await?;
+    arc_timeline(store).await?;
+    query_language(store).await?;
+    contexts(store).await?;
+    Ok(())
+}
+
+fn filter(verb: impl Into<VerbSelector>, context_id: &str) -> GetFilter {
+    GetFilter {
+        verb: verb.into(),
+        key: None,
+        value: None,
+        context_id: Some(context_id.to_string()),
+        limit: None,
+        offset: None,
+        since: None,
+        until: None,
+    }
+}
+
+fn pairs(entries: &[Entry]) -> Vec<(&str, &str)> {
+    entries.iter().map(|e| (e.key.as_str(), e.value.as_str())).collect()
+}
+
+async fn get<S: MeStore>(store: &S, name: &str, f: &GetFilter) -> Result<Vec<Entry>, String> {
+    store.get(f).await.map_err(|e| format!("[{}] get({}) failed: {}", name, f.verb, e))
+}
+
+async fn insert<S: MeStore>(store: &S, name: &str, verb: Verb, cid: &str, key: &str, value: &str, ts: &str) -> CheckResult {
+    store
+        .insert(verb, cid, key, value, ts)
+        .await
+        .map_err(|e| format!("[{}] insert({}) failed: {}", name, verb, e))
+}
+
+/// create_identity → load_keys devuelve exactamente lo guardado; update_encrypted_private lo reemplaza.
+pub async fn identity_round_trip<S: MeStore>(store: &S) -> CheckResult {
+    let name = "identity_round_trip";
+    store
+        .create_identity("conformance.a", "pub-a", "enc-a")
+        .await
+        .map_err(|e| format!("[{}] create_identity failed: {}", name, e))?;
+    let keys = store.load_keys("conformance.a").await.map_err(|e| format!("[{}] load_keys failed: {}", name, e))?;
+    check!(name, keys == ("pub-a".to_string(), "enc-a".to_string()), "load_keys returned {:?}", keys);
+
+    store
+        .update_encrypted_private("conformance.a", "enc-a2")
+        .await
+        .map_err(|e| format!("[{}] update_encrypted_private failed: {}", name, e))?;
+    let keys = store.load_keys("conformance.a").await.map_err(|e| format!("[{}] load_keys failed: {}", name, e))?;
+    check!(name, keys == ("pub-a".to_string(), "enc-a2".to_string()), "after update load_keys returned {:?}", keys);
+
+    check!(name, matches!(store.load_keys("conformance.missing").await, Err(MeError::IdentityNotFound(_))), "load_keys of an unknown username must fail with IdentityNotFound");
+    Ok(())
+}
+
+/// Un segundo create_identity con el mismo username falla con AlreadyExists y nunca sobreescribe
+/// las llaves originales; de varios create concurrentes, exactamente uno gana.
+pub async fn duplicate_username<S: MeStore>(store: &S) -> CheckResult {
+    let name = "duplicate_username";
+    store
+        .create_identity("conformance.b", "pub-b", "enc-b")
+        .await
+        .map_err(|e| format!("[{}] create_identity failed: {}", name, e))?;
+    let again = store.create_identity("conformance.b", "pub-other", "enc-other").await;
+    check!(name, matches!(again, Err(MeError::AlreadyExists(_))), "duplicate create returned {:?}", again);
+    let keys = store.load_keys("conformance.b").await.map_err(|e| format!("[{}] load_keys failed: {}", name, e))?;
+    check!(name, keys == ("pub-b".to_string(), "enc-b".to_string()), "duplicate create overwrote keys: {:?}", keys);
+
+    let (a, b, c, d) = tokio::join!(
+        store.create_identity("conformance.race", "pub-1", "enc-1"),
+        store.create_identity("conformance.race", "pub-2", "enc-2"),
+        store.create_identity("conformance.race", "pub-3", "enc-3"),
+        store.create_identity("conformance.race", "pub-4", "enc-4"),
+    );
+    let results = [a, b, c, d];
+    let won: Vec<usize> = (0..results.len()).filter(|&i| results[i].is_ok()).collect();
+    check!(name, won.len() == 1, "{} concurrent creates succeeded", won.len());
+    check!(
+        name,
+        results.iter().all(|r| matches!(r, Ok(()) | Err(MeError::AlreadyExists(_)))),
+        "concurrent creates failed with something other than AlreadyExists: {:?}",
+        results
+    );
+    let keys = store.load_keys("conformance.race").await.map_err(|e| format!("[{}] load_keys failed: {}", name, e))?;
+    let winner = won[0] + 1;
+    check!(name, keys == (format!("pub-{}", winner), format!("enc-{}", winner)), "stored keys {:?} are not the winner's", keys);
+    Ok(())
+}
+
+/// Llaves por autoridad: round-trip, duplicados rechazados, listado por username y borrado.
+pub async fn authority_keys<S: MeStore>(store: &S) -> CheckResult {
+    let name = "authority_keys";
+    let err = |what: &str, e: MeError| format!("[{}] {} failed: {}", name, what, e);
+    store.insert_key("conformance.k", "cleaker", "pub-c", "enc-c").await.map_err(|e| err("insert_key", e))?;
+    store.insert_key("conformance.k", "networkX", "pub-n", "enc-n").await.map_err(|e| err("insert_key", e))?;
+    store.insert_key("conformance.other", "cleaker", "pub-o", "enc-o").await.map_err(|e| err("insert_key", e))?;
+    check!(name, matches!(store.insert_key("conformance.k", "cleaker", "pub-x", "enc-x").await, Err(MeError::KeyExists(_))), "duplicate authority must be rejected with KeyExists");
+
+    let keys = store.load_key("conformance.k", "cleaker").await.map_err(|e| err("load_key", e))?;
+    check!(name, keys == ("pub-c".to_string(), "enc-c".to_string()), "load_key returned {:?}", keys);
+
+    let listed = store.list_keys("conformance.k").await.map_err(|e| err("list_keys", e))?;
+    check!(name, listed == ["cleaker", "networkX"], "list_keys returned {:?}", listed);
+
+    store.delete_key("conformance.k", "cleaker").await.map_err(|e| err("delete_key", e))?;
+    check!(name, matches!(store.load_key("conformance.k", "cleaker").await, Err(MeError::KeyNotFound(_))), "deleted key must fail with KeyNotFound");
+    let listed = store.list_keys("conformance.k").await.map_err(|e| err("list_keys", e))?;
+    check!(name, listed == ["networkX"], "after delete list_keys returned {:?}", listed);
+    check!(name, store.load_key("conformance.other", "cleaker").await.is_ok(), "delete_key touched another username");
+    Ok(())
+}
+
+/// rewrap_keys cambia `main` y las autoridades juntas; si una falta (en el store o en la lista), no cambia nada.
+pub async fn rewrap_keys<S: MeStore>(store: &S) -> CheckResult {
+    let name = "rewrap_keys";
+    let err = |what: &str, e: MeError| format!("[{}] {} failed: {}", name, what, e);
+    store.create_identity("conformance.r", "pub-r", "enc-r").await.map_err(|e| err("create_identity", e))?;
+    store.insert_key("conformance.r", "cleaker", "pub-c", "enc-c").await.map_err(|e| err("insert_key", e))?;
+
+    let partial = [("cleaker".to_string(), "enc-c2".to_string()), ("ghost".to_string(), "enc-g2".to_string())];
+    let result = store.rewrap_keys("conformance.r", "enc-r2", &partial).await;
+    check!(name, matches!(result, Err(MeError::KeyNotFound(_))), "rewrap with an unknown authority returned {:?}", result);
+    let main = store.load_keys("conformance.r").await.map_err(|e| err("load_keys", e))?;
+    let cleaker = store.load_key("conformance.r", "cleaker").await.map_err(|e| err("load_key", e))?;
+    check!(name, main.1 == "enc-r" && cleaker.1 == "enc-c", "failed rewrap left {:?} / {:?}", main, cleaker);
+
+    let result = store.rewrap_keys("conformance.missing", "enc", &[]).await;
+    check!(name, matches!(result, Err(MeError::IdentityNotFound(_))), "rewrap of an unknown username returned {:?}", result);
+
+    store.rewrap_keys("conformance.r", "enc-r2", &partial[..1]).await.map_err(|e| err("rewrap_keys", e))?;
+    let main = store.load_keys("conformance.r").await.map_err(|e| err("load_keys", e))?;
+    let cleaker = store.load_key("conformance.r", "cleaker").await.map_err(|e| err("load_key", e))?;
+    check!(name, main == ("pub-r".to_string(), "enc-r2".to_string()), "load_keys returned {:?}", main);
+    check!(name, cleaker == ("pub-c".to_string(), "enc-c2".to_string()), "load_key returned {:?}", cleaker);
+
+    // una llave agregada después de leer la lista: el re-wrap no puede dejarla con el cifrado viejo
+    store.insert_key("conformance.r", "wallet", "pub-w", "enc-w").await.map_err(|e| err("insert_key", e))?;
+    let result = store.rewrap_keys("conformance.r", "enc-r3", &partial[..1]).await;
+    check!(name, matches!(result, Err(MeError::KeysChanged(_))), "rewrap missing a stored authority returned {:?}", result);
+    let main = store.load_keys("conformance.r").await.map_err(|e| err("load_keys", e))?;
+    check!(name, main.1 == "enc-r2", "failed rewrap left {:?}", main);
+    Ok(())
+}
+
+/// `VerbSelector::Many` lee solo las tablas pedidas (una vez cada una, aunque se repitan).
+pub async fn verb_selector<S: MeStore>(store: &S) -> CheckResult {
+    let name = "verb_selector";
+    let cid = "c:selector";
+    for (i, verb) in [Verb::Be, Verb::Have, Verb::At].into_iter().enumerate() {
+        insert(store, name, verb, cid, "k", verb.name(), &format!("2025-01-0{}T00:00:00+00:00", i + 1)).await?;
+    }
+    let got = get(store, name, &filter(VerbSelector::Many(vec![Verb::Be, Verb::At, Verb::Be]), cid)).await?;
+    let verbs: Vec<Verb> = got.iter().map(|e| e.verb).collect();
+    check!(name, verbs == [Verb::At, Verb::Be], "got {:?}", verbs);
+    Ok(())
+}
+
+/// Cada verbo devuelve (key, value) tal como se insertaron, aunque react/communicate
+/// los guarden como target/emoji y target/message.
+pub async fn verb_column_mapping<S: MeStore>(store: &S) -> CheckResult {
+    let name = "verb_column_mapping";
+    let cid = "c:mapping";
+    let cases = [
+        (Verb::Be, "species", "human"),
+        (Verb::Have, "age", "34"),
+        (Verb::At, "location", "Cancún"),
+        (Verb::Relate, "friend", "jose"),
+        (Verb::React, "meeting", "❤️"),
+        (Verb::Communicate, "group", "see you soon"),
+        (Verb::Do, "task", "build"),
+    ];
+    for (verb, key, value) in cases {
+        insert(store, name, verb, cid, key, value, "2025-01-01T00:00:00+00:00").await?;
+        let got = get(store, name, &filter(verb, cid)).await?;
+        check!(name, pairs(&got) == vec![(key, value)], "{} returned {:?}", verb, pairs(&got));
+        check!(name, got[0].verb == verb, "{} reported verb {:?}", verb, got[0].verb);
+
+        let mut by_key = filter(verb, cid);
+        by_key.key = Some(key.to_string());
+        check!(name, get(store, name, &by_key).await?.len() == 1, "{}: key filter must match the exposed key", verb);
+        let mut by_value = filter(verb, cid);
+        by_value.value = Some(value.to_string());
+        check!(name, get(store, name, &by_value).await?.len() == 1, "{}: value filter must match the exposed value", verb);
+    }
+    Ok(())
+}
+
+/// Resultados en orden cronológico inverso, sin importar el orden de inserción.
+pub async fn ordering<S: MeStore>(store: &S) -> CheckResult {
+    let name = "ordering";
+    let cid = "c:ordering";
+    for (ts, value) in [
+        ("2025-03-01T00:00:00+00:00", "march"),
+        ("2025-01-01T00:00:00+00:00", "january"),
+        ("2025-02-01T00:00:00+00:00", "february"),
+    ] {
+        insert(store, name, Verb::Be, cid, "month", value, ts).await?;
+    }
+    let got = get(store, name, &filter(Verb::Be, cid)).await?;
+    let values: Vec<&str> = got.iter().map(|e| e.value.as_str()).collect();
+    check!(name, values == ["march", "february", "january"], "got {:?}", values);
+    Ok(())
+}
+
+/// key / value / context_id / since / until / limit / offset.
+pub async fn filters<S: MeStore>(store: &S) -> CheckResult {
+    let name = "filters";
+    let cid = "c:filters";
+    for (i, (key, value)) in [("a", "1"), ("b", "2"), ("a", "3"), ("b", "4"), ("a", "5")].iter().enumerate() {
+        insert(store, name, Verb::Have, cid, key, value, &format!("2025-01-0{}T00:00:00+00:00", i + 1)).await?;
+    }
+    insert(store, name, Verb::Have, "c:filters-other", "a", "1", "2025-01-01T00:00:00+00:00").await?;
+
+    let mut f = filter(Verb::Have, cid);
+    f.key = Some("a".into());
+    let got = get(store, name, &f).await?;
+    check!(name, pairs(&got) == vec![("a", "5"), ("a", "3"), ("a", "1")], "key filter got {:?}", pairs(&got));
+
+    let mut f = filter(Verb::Have, cid);
+    f.value = Some("2".into());
+    check!(name, pairs(&get(store, name, &f).await?) == vec![("b", "2")], "value filter mismatch");
+
+    let mut f = filter(Verb::Have, cid);
+    f.context_id = None;
+    f.key = Some("a".into());
+    f.value = Some("1".into());
+    check!(name, get(store, name, &f).await?.len() == 2, "without context_id every context must be searched");
+
+    let mut f = filter(Verb::Have, cid);
+    f.since = Some("2025-01-02T00:00:00+00:00".into());
+    f.until = Some("2025-01-04T00:00:00+00:00".into());
+    let got = get(store, name, &f).await?;
+    check!(name, pairs(&got) == vec![("b", "4"), ("a", "3"), ("b", "2")], "since/until must be inclusive, got {:?}", pairs(&got));
+
+    let mut f = filter(Verb::Have, cid);
+    f.limit = Some(2);
+    f.offset = Some(1);
+    let got = get(store, name, &f).await?;
+    check!(name, pairs(&got) == vec![("b", "4"), ("a", "3")], "limit/offset got {:?}", pairs(&got));
+    Ok(())
+}
+
+/// Mini-sintaxis de valores: `like:` en key y value, `json:campo=valor` en value.
+pub async fn pattern_filters<S: MeStore>(store: &S) -> CheckResult {
+    let name = "pattern_filters";
+    let cid = "c:patterns";
+    insert(store, name, Verb::Have, cid, "wallet:main", r#"{"type":"eth","alias":"main"}"#, "2025-01-01T00:00:00+00:00").await?;
+    insert(store, name, Verb::Have, cid, "wallet:cold", r#"{"type":"btc","alias":"cold"}"#, "2025-01-02T00:00:00+00:00").await?;
+    insert(store, name, Verb::Have, cid, "tool", "cleaker", "2025-01-03T00:00:00+00:00").await?;
+
+    let mut f = filter(Verb::Have, cid);
+    f.key = Some("like:wallet".into());
+    check!(name, get(store, name, &f).await?.len() == 2, "key like: must match substrings");
+
+    let mut f = filter(Verb::Have, cid);
+    f.value = Some("like:leak".into());
+    check!(name, pairs(&get(store, name, &f).await?) == vec![("tool", "cleaker")], "value like: must match substrings");
+
+    let mut f = filter(Verb::Have, cid);
+    f.value = Some("json:type=eth".into());
+    let got = get(store, name, &f).await?;
+    check!(name, got.len() == 1 && got[0].key == "wallet:main", "json: filter got {:?}", pairs(&got));
+
+    // casos borde: mismas respuestas que json_extract/LIKE de SQLite
+    let cid = "c:patterns-edge";
+    insert(store, name, Verb::Have, cid, "Wallet:Hot", r#"{"type":"eth","meta":{"chain":"main"}}"#, "2025-01-01T00:00:00+00:00").await?;
+    insert(store, name, Verb::Have, cid, "count", r#"{"type":34}"#, "2025-01-02T00:00:00+00:00").await?;
+    insert(store, name, Verb::Have, cid, "broken", r#"{"type":"eth""#, "2025-01-03T00:00:00+00:00").await?;
+    insert(store, name, Verb::Have, cid, "path", r"C:\tmp", "2025-01-04T00:00:00+00:00").await?;
+    let keys = |value: &str| {
+        let mut f = filter(Verb::Have, cid);
+        f.value = Some(value.to_string());
+        async move { get(store, name, &f).await.map(|got| got.into_iter().map(|e| e.key).collect::<Vec<_>>()) }
+    };
+    check!(name, keys("json:type=eth").await? == ["Wallet:Hot"], "json: must skip values that are not JSON");
+    check!(name, keys("json:meta.chain=main").await? == ["Wallet:Hot"], "json: must follow nested keys");
+    check!(name, keys("json:type=34").await?.is_empty(), "json: must only match strings");
+    check!(name, keys(r"like:\t").await? == ["path"], "like: must treat backslash literally");
+    let mut f = filter(Verb::Have, cid);
+    f.key = Some("like:WALLET:h".into());
+    check!(name, pairs(&get(store, name, &f).await?).len() == 1, "key like: must ignore ASCII case");
+    Ok(())
+}
+
+/// Sin `limit` se devuelven como máximo 100 filas por verbo.
+pub async fn default_limit<S: MeStore>(store: &S) -> CheckResult {
+    let name = "default_limit";
+    let cid = "c:limit";
+    for i in 0..101 {
+        insert(store, name, Verb::At, cid, "tick", &i.to_string(), &format!("2025-01-01T00:{:02}:{:02}+00:00", i / 60, i % 60)).await?;
+    }
+    let got = get(store, name, &filter(Verb::At, cid)).await?;
+    check!(name, got.len() == 100, "expected 100 rows, got {}", got.len());
+    check!(name, got[0].value == "100", "newest row must come first, got {:?}", got[0].value);
+    Ok(())
+}
+
+/// `VerbSelector::All` recorre todas las tablas y devuelve todo en orden cronológico inverso.
+pub async fn all_verbs<S: MeStore>(store: &S) -> CheckResult {
+    let name = "all_verbs";
+    let cid = "c:all";
+    for (i, verb) in Verb::ALL.into_iter().enumerate() {
+        insert(store, name, verb, cid, "k", verb.name(), &format!("2025-01-0{}T00:00:00+00:00", i + 1)).await?;
+    }
+    let got = get(store, name, &filter(VerbSelector::All, cid)).await?;
+    let tables: Vec<&str> = got.iter().map(|e| e.verb.table()).collect();
+    check!(
+        name,
+        tables == ["do_", "communicate", "react", "relate", "at", "have", "be"],
+        "got {:?}", tables
+    );
+    Ok(())
+}
+
+/// insert_signed guarda el Proof y el correlation_id tal cual; log() trae todo el contexto en
+/// orden ascendente (filas con y sin firma, sin límite) y log_head() la última fila firmada.
+pub async fn signed_log<S: MeStore>(store: &S) -> CheckResult {
+    let name = "signed_log";
+    let cid = "c:log";
+    let proof = |n: u32| Proof { hash: format!("h{}", n), prev_hash: format!("h{}", n - 1), signature: format!("s{}", n) };
+
+    let head = store.log_head(cid).await.map_err(|e| format!("[{}] log_head failed: {}", name, e))?;
+    check!(name, head.is_none(), "empty context must have no head, got {:?}", head);
+
+    insert(store, name, Verb::Be, cid, "legacy", "unsigned", "2025-01-01T00:00:00+00:00").await?;
+    for (n, verb) in [(1, Verb::React), (2, Verb::Do)] {
+        let mut entry = Entry::new(verb, "k", verb.name(), &format!("2025-01-0{}T00:00:00+00:00", n + 1));
+        entry.proof = Some(proof(n));
+        entry.correlation_id = (n == 2).then(|| "travel:1".to_string());
+        store
+            .insert_signed(cid, &entry)
+            .await
+            .map_err(|e| format!("[{}] insert_signed({}) failed: {}", name, verb, e))?;
+    }
+    insert(store, name, Verb::Have, "c:log-other", "k", "v", "2025-02-01T00:00:00+00:00").await?;
+    for i in 0..101 {
+        insert(store, name, Verb::At, cid, "tick", &i.to_string(), &format!("2024-12-31T00:{:02}:{:02}+00:00", i / 60, i % 60)).await?;
+    }
+
+    let log = store.log(cid).await.map_err(|e| format!("[{}] log failed: {}", name, e))?;
+    check!(name, log.len() == 104, "log must not apply the default limit, got {} rows", log.len());
+    let tail: Vec<(&str, Option<&Proof>)> = log[101..].iter().map(|e| (e.verb.table(), e.proof.as_ref())).collect();
+    let (p1, p2) = (proof(1), proof(2));
+    check!(
+        name,
+        tail == vec![("be", None), ("react", Some(&p1)), ("do_", Some(&p2))],
+        "log must be ascending with proofs, got {:?}", tail
+    );
+    check!(name, (log[102].key.as_str(), log[102].value.as_str()) == ("k", "react"), "log must expose key/value like get()");
+    let correlations: Vec<Option<&str>> = log[101..].iter().map(|e| e.correlation_id.as_deref()).collect();
+    check!(name, correlations == [None, None, Some("travel:1")], "correlation_id must round-trip, got {:?}", correlations);
+
+    let got = get(store, name, &filter(Verb::React, cid)).await?;
+    check!(name, got[0].proof.as_ref() == Some(&p1), "get must return the stored proof, got {:?}", got[0].proof);
+
+    let head = store.log_head(cid).await.map_err(|e| format!("[{}] log_head failed: {}", name, e))?;
+    check!(name, head.and_then(|h| h.proof) == Some(p2), "log_head must be the newest signed row");
+    Ok(())
+}
+
+/// insert_arc guarda los tres puntajes como f64; arc_timeline filtra por contexto y dirección
+/// (actor → target), aplica since/until inclusivos y ordena de forma ascendente.
+pub async fn arc_timeline<S: MeStore>(store: &S) -> CheckResult {
+    let name = "arc_timeline";
+    let rows = [
+        ("c:arc", "me1", "me2", 0.25, "2025-01-03T00:00:00+00:00"),
+        ("c:arc", "me1", "me2", -0.5, "2025-01-01T00:00:00+00:00"),
+        ("c:arc", "me2", "me1", 0.75, "2025-01-02T00:00:00+00:00"),
+        ("c:arc-other", "me1", "me2", 1.0, "2025-01-02T00:00:00+00:00"),
+        ("c:arc", "me1", "me2", 0.1, "2025-01-02T00:00:00+00:00"),
+    ];
+    for (cid, actor, target, affinity, ts) in rows {
+        let entry = ArcEntry {
+            actor: actor.into(),
+            target: target.into(),
+            scores: ArcScores::new(affinity, 0.5, 1.0 / 3.0),
+            timestamp: ts.into(),
+        };
+        store
+            .insert_arc(cid, &entry)
+            .await
+            .map_err(|e| format!("[{}] insert_arc failed: {}", name, e))?;
+    }
+    let timeline = |since: Option<&'static str>, until: Option<&'static str>| async move {
+        store
+            .arc_timeline("c:arc", "me1", "me2", since, until)
+            .await
+            .map_err(|e| format!("[{}] arc_timeline failed: {}", name, e))
+    };
+
+    let all = timeline(None, None).await?;
+    let affinity: Vec<f64> = all.iter().map(|e| e.scores.affinity).collect();
+    check!(name, affinity == [-0.5, 0.1, 0.25], "timeline must be ascending and one-directional, got {:?}", affinity);
+    check!(
+        name,
+        all[0].scores == ArcScores::new(-0.5, 0.5, 1.0 / 3.0) && (all[0].actor.as_str(), all[0].target.as_str()) == ("me1", "me2"),
+        "scores must round-trip as f64, got {:?}", all[0]
+    );
+    let window = timeline(Some("2025-01-02T00:00:00+00:00"), Some("2025-01-02T00:00:00+00:00")).await?;
+    check!(name, window.len() == 1 && window[0].scores.affinity == 0.1, "since/until must be inclusive, got {:?}", window);
+    let none = store
+        .arc_timeline("c:arc", "me2", "me3", None, None)
+        .await
+        .map_err(|e| format!("[{}] arc_timeline failed: {}", name, e))?;
+    check!(name, none.is_empty(), "unknown pair must have no samples, got {:?}", none);
+
+    let log = store
+        .arc_log("c:arc")
+        .await
+        .map_err(|e| format!("[{}] arc_log failed: {}", name, e))?;
+    // dos muestras comparten timestamp: entre ellas el orden lo decide cada backend
+    check!(name, log.windows(2).all(|w| w[0].timestamp <= w[1].timestamp), "arc_log must be ascending, got {:?}", log);
+    let mut pairs: Vec<(&str, f64)> = log.iter().map(|e| (e.actor.as_str(), e.scores.affinity)).collect();
+    pairs.sort_by(|a, b| a.1.total_cmp(&b.1));
+    check!(
+        name,
+        pairs == [("me1", -0.5), ("me1", 0.1), ("me1", 0.25), ("me2", 0.75)],
+        "arc_log must hold every pair of the context, got {:?}", pairs
+    );
+    Ok(())
+}
+
+/// `contexts` lista cada contexto con filas (verbos o `arc`) una sola vez y ordenado.
+/// Corre después de los demás checks, así que ve sus contextos.
+pub async fn contexts<S: MeStore>(store: &S) -> CheckResult {
+    let name = "contexts";
+    let contexts = store
+        .contexts()
+        .await
+        .map_err(|e| format!("[{}] contexts failed: {}", name, e))?;
+    for expected in ["c:log", "c:log-other", "c:arc", "c:arc-other", "c:query"] {
+        check!(name, contexts.iter().any(|c| c == expected), "missing context {:?} in {:?}", expected, contexts);
+    }
+    check!(name, contexts.windows(2).all(|w| w[0] < w[1]), "contexts must be sorted and distinct, got {:?}", contexts);
+    Ok(())
+}
+
+/// `MeStore::query` da el mismo resultado en todos los backends: LIKE sin mayúsculas con escape,
+/// comparaciones tipadas dentro del JSON, NOT sobre valores que no son JSON, y limit/offset globales.
+pub async fn query_language<S: MeStore>(store: &S) -> CheckResult {
+    let name = "query_language";
+    let cid = "c:query";
+    let rows = [
+        (Verb::Have, "wallet:main", r#"{"chain":"eth","balance":12.5,"tags":["hot"]}"#, "2025-01-01T00:00:00+00:00"),
+        (Verb::Have, "Wallet:cold", r#"{"chain":"btc","balance":3,"cold":true}"#, "2025-01-02T00:00:00+00:00"),
+        (Verb::Have, "wallet:odd", r#"{"chain":"eth","balance":"12.5","0":"zero"}"#, "2025-01-03T00:00:00+00:00"),
+        (Verb::Have, "wallet_100%", "plain text", "2025-01-04T00:00:00+00:00"),
+        (Verb::React, "post:1", "🔥", "2025-01-05T00:00:00+00:00"),
+        (Verb::Be, "age", r#"{"years":34}"#, "2025-02-01T00:00:00+00:00"),
+    ];
+    for (verb, key, value, ts) in rows {
+        insert(store, name, verb, cid, key, value, ts).await?;
+    }
+    insert(store, name, Verb::Have, "c:query-other", "wallet:main", r#"{"chain":"eth"}"#, "2025-01-01T00:00:00+00:00").await?;
+
+    let cases: [(&str, &[&str]); 14] = [
+        (r#"have where key ~ "wallet%" and value.$.chain = "eth" since 2025-01 limit 20"#, &["wallet:odd", "wallet:main"]),
+        (r#"have where key ~ "WALLET:%""#, &["wallet:odd", "Wallet:cold", "wallet:main"]),
+        (r#"have where key ~ "wallet\_100\%""#, &["wallet_100%"]),
+        (r#"have where value.$.balance > 10"#, &["wallet:main"]),
+        (r#"have where value.$.balance = "12.5""#, &["wallet:odd"]),
+        (r#"have where value.$.cold = true or value.$.tags[0] = "hot""#, &["Wallet:cold", "wallet:main"]),
+        (r#"have where value.$[0] = "zero""#, &[]),
+        (r#"have where not value.$.chain = "eth""#, &["wallet_100%", "Wallet:cold"]),
+        (r#"have where value.$.chain != "eth""#, &["Wallet:cold"]),
+        (r#"have where value.$.missing = null or value.$ = null"#, &[]),
+        (r#"have where key >= "w" until "2025-01-03T00:00:00+00:00""#, &["wallet:odd", "wallet:main"]),
+        (r#"react, be where key = "post:1" or value.$.years >= 34"#, &["age", "post:1"]),
+        (r#"all where key !~ "%wallet%" limit 1 offset 1"#, &["post:1"]),
+        (r#"have limit 2 offset 1"#, &["wallet:odd", "Wallet:cold"]),
+    ];
+    for (text, expected) in cases {
+        let mut query: Query = text.parse().map_err(|e| format!("[{}] {}: {}", name, text, e))?;
+        query.context_id.get_or_insert_with(|| cid.to_string());
+        let got = store
+            .query(&query)
+            .await
+            .map_err(|e| format!("[{}] query({}) failed: {}", name, text, e))?;
+        let keys: Vec<&str> = got.iter().map(|e| e.key.as_str()).collect();
+        check!(name, keys == expected, "{} → {:?}, expected {:?}", text, keys, expected);
+    }
+    Ok(())
+}
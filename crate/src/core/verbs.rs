@@ -1 +1,183 @@
-//this.me/crate/src/core/verbs.rs
\ No newline at end of file
+//this.me/crate/src/core/verbs.rs
+// Verbos primarios del protocolo .me y su mapeo a tablas.
+// Nombres canónicos: be, have, do, at, relate, react, communicate.
+// Se aceptan al parsear los alias históricos `do_` (nombre de la tabla) y `say` (= communicate).
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Verb {
+    Be,
+    Have,
+    At,
+    Relate,
+    React,
+    Communicate,
+    Do,
+}
+
+impl Verb {
+    /// Todos los verbos, en el orden en que se recorren las tablas con `VerbSelector::All`.
+    pub const ALL: [Verb; 7] = [Verb::Be, Verb::Have, Verb::At, Verb::Relate, Verb::React, Verb::Communicate, Verb::Do];
+
+    /// Nombre canónico (`do`, no `do_`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Verb::Be => "be",
+            Verb::Have => "have",
+            Verb::At => "at",
+            Verb::Relate => "relate",
+            Verb::React => "react",
+            Verb::Communicate => "communicate",
+            Verb::Do => "do",
+        }
+    }
+
+    /// Tabla en migrate_schema / me.* (`do` es palabra reservada en SQL → `do_`).
+    pub fn table(self) -> &'static str {
+        match self {
+            Verb::Do => "do_",
+            other => other.name(),
+        }
+    }
+
+    /// Columnas que se exponen como (key, value): react guarda target/emoji y communicate target/message.
+    pub fn columns(self) -> (&'static str, &'static str) {
+        match self {
+            Verb::React => ("target", "emoji"),
+            Verb::Communicate => ("target", "message"),
+            _ => ("key", "value"),
+        }
+    }
+}
+
+impl fmt::Display for Verb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unsupported verb: {0}")]
+pub struct ParseVerbError(pub String);
+
+impl FromStr for Verb {
+    type Err = ParseVerbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "be" => Ok(Verb::Be),
+            "have" => Ok(Verb::Have),
+            "at" => Ok(Verb::At),
+            "relate" => Ok(Verb::Relate),
+            "react" => Ok(Verb::React),
+            "communicate" | "say" => Ok(Verb::Communicate),
+            "do" | "do_" => Ok(Verb::Do),
+            other => Err(ParseVerbError(other.to_string())),
+        }
+    }
+}
+
+impl Serialize for Verb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Verb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Qué tablas lee `get()`: un verbo, varios, o todos (`"all"`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerbSelector {
+    One(Verb),
+    Many(Vec<Verb>),
+    All,
+}
+
+impl VerbSelector {
+    /// Verbos seleccionados, sin repetir, en el orden dado (`All` → `Verb::ALL`).
+    pub fn verbs(&self) -> Vec<Verb> {
+        match self {
+            VerbSelector::One(verb) => vec![*verb],
+            VerbSelector::Many(verbs) => {
+                let mut out: Vec<Verb> = Vec::with_capacity(verbs.len());
+                for verb in verbs {
+                    if !out.contains(verb) {
+                        out.push(*verb);
+                    }
+                }
+                out
+            }
+            VerbSelector::All => Verb::ALL.to_vec(),
+        }
+    }
+}
+
+impl From<Verb> for VerbSelector {
+    fn from(verb: Verb) -> Self {
+        VerbSelector::One(verb)
+    }
+}
+
+impl FromStr for VerbSelector {
+    type Err = ParseVerbError;
+
+    /// `"all"`, `"be"` o una lista separada por comas (`"be,have"`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(VerbSelector::All);
+        }
+        let verbs = s.split(',').map(|v| v.trim().parse()).collect::<Result<Vec<Verb>, _>>()?;
+        match verbs.as_slice() {
+            [verb] => Ok(VerbSelector::One(*verb)),
+            _ => Ok(VerbSelector::Many(verbs)),
+        }
+    }
+}
+
+impl fmt::Display for VerbSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerbSelector::One(verb) => write!(f, "{}", verb),
+            VerbSelector::Many(verbs) => {
+                let names: Vec<&str> = verbs.iter().map(|v| v.name()).collect();
+                f.write_str(&names.join(","))
+            }
+            VerbSelector::All => f.write_str("all"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_aliases_and_selectors() {
+        for verb in Verb::ALL {
+            assert_eq!(verb.name().parse::<Verb>().unwrap(), verb);
+            assert_eq!(verb.table().parse::<Verb>().unwrap(), verb);
+        }
+        assert_eq!("say".parse::<Verb>().unwrap(), Verb::Communicate);
+        assert_eq!(Verb::Do.to_string(), "do");
+        assert_eq!(Verb::Do.table(), "do_");
+        assert_eq!("dance".parse::<Verb>(), Err(ParseVerbError("dance".into())));
+
+        assert_eq!("all".parse::<VerbSelector>().unwrap().verbs(), Verb::ALL);
+        assert_eq!("do_".parse::<VerbSelector>().unwrap(), VerbSelector::One(Verb::Do));
+        let many: VerbSelector = "be, have,be".parse().unwrap();
+        assert_eq!(many.verbs(), [Verb::Be, Verb::Have]);
+        assert_eq!(many.to_string(), "be,have,be");
+        assert!("be,dance".parse::<VerbSelector>().is_err());
+
+        assert_eq!(serde_json::to_string(&Verb::Do).unwrap(), r#""do""#);
+        assert_eq!(serde_json::from_str::<Verb>(r#""do_""#).unwrap(), Verb::Do);
+        assert!(serde_json::from_str::<Verb>(r#""all""#).is_err());
+    }
+}
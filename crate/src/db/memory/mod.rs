@@ -0,0 +1,4 @@
+//this.me/crate/src/db/memory/mod.rs
+// re-exporta MemoryStore (MeStore en memoria: tests e identidades efímeras).
+pub mod store;
+pub use store::MemoryStore;
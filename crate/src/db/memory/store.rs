@@ -0,0 +1,459 @@
+//this.me/crate/src/db/memory/store.rs
+// MeStore en memoria (mapas en proceso). Sin persistencia: tests, previews, identidades efímeras.
+// Replica las "tablas" de migrate_schema y las mismas rarezas por verbo que PgStore/SqliteStore.
+use std::collections::{hash_map, BTreeSet, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use async_trait::async_trait;
+use crate::core::store::MeStore;
+use crate::core::model::{Entry, GetFilter, Proof};
+use crate::core::verbs::Verb;
+use crate::core::arc::ArcEntry;
+use crate::core::query::Query;
+use crate::utils::me_error::MeError;
+
+/// Fila de la tabla `me`.
+#[derive(Debug, Clone)]
+struct IdentityRow {
+    public_key: String,
+    encrypted_private_key: String,
+    #[allow(dead_code)]
+    created_at: String,
+}
+
+/// Fila de la tabla `keys` (llaves por autoridad).
+#[derive(Debug, Clone)]
+struct KeyRow {
+    username: String,
+    authority: String,
+    public_key: String,
+    encrypted_private_key: String,
+}
+
+/// Fila genérica de una tabla de verbo.
+/// `target` solo se usa en relate/react/communicate; en react `value` es el emoji
+/// y en communicate es el message.
+#[derive(Debug, Clone)]
+struct VerbRow {
+    context_id: String,
+    key: String,
+    target: String,
+    value: String,
+    timestamp: String,
+    proof: Option<Proof>,
+    correlation_id: Option<String>,
+}
+
+impl VerbRow {
+    /// (key, value) tal como los expone get() para cada tabla.
+    fn projected(&self, verb: Verb) -> (&str, &str) {
+        match verb {
+            Verb::React | Verb::Communicate => (&self.target, &self.value),
+            _ => (&self.key, &self.value),
+        }
+    }
+
+    fn entry(&self, verb: Verb) -> Entry {
+        let (key, value) = self.projected(verb);
+        Entry {
+            verb,
+            key: key.to_string(),
+            value: value.to_string(),
+            timestamp: self.timestamp.clone(),
+            proof: self.proof.clone(),
+            correlation_id: self.correlation_id.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    identities: Mutex<HashMap<String, IdentityRow>>,
+    keys: Mutex<Vec<KeyRow>>,
+    tables: Mutex<HashMap<Verb, Vec<VerbRow>>>,
+    /// Tabla `arc`: (context_id, fila).
+    arc: Mutex<Vec<(String, ArcEntry)>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self { Self::default() }
+
+    fn lock<T>(m: &Mutex<T>) -> Result<MutexGuard<'_, T>, MeError> {
+        m.lock().map_err(|_| MeError::Storage("MemoryStore mutex poisoned".into()))
+    }
+
+    fn insert_row(
+        &self,
+        context_id: &str,
+        entry: &Entry,
+    ) -> Result<(), MeError> {
+        let (verb, key) = (entry.verb, entry.key.as_str());
+        let (key, target) = match verb {
+            Verb::React => ("", key),        // key vacío, target = key, emoji = value
+            Verb::Communicate => (key, key), // target = key, message = value
+            Verb::Relate => (key, ""),       // target vacío
+            _ => (key, ""),
+        };
+        Self::lock(&self.tables)?.entry(verb).or_default().push(VerbRow {
+            context_id: context_id.to_string(),
+            key: key.to_string(),
+            target: target.to_string(),
+            value: entry.value.clone(),
+            timestamp: entry.timestamp.clone(),
+            proof: entry.proof.clone(),
+            correlation_id: entry.correlation_id.clone(),
+        });
+        Ok(())
+    }
+
+    /// Entradas de un contexto en todas las tablas, en orden cronológico ascendente.
+    fn context_rows(&self, context_id: &str) -> Result<Vec<Entry>, MeError> {
+        let data = Self::lock(&self.tables)?;
+        let mut out: Vec<Entry> = Verb::ALL
+            .into_iter()
+            .filter_map(|verb| data.get(&verb).map(|rows| (verb, rows)))
+            .flat_map(|(verb, rows)| {
+                rows.iter()
+                    .filter(|row| row.context_id == context_id)
+                    .map(move |row| row.entry(verb))
+            })
+            .collect();
+        out.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(out)
+    }
+}
+
+#[async_trait]
+impl MeStore for MemoryStore {
+    async fn create_identity(
+        &self,
+        username: &str,
+        public_key: &str,
+        encrypted_private_key: &str,
+    ) -> Result<(), MeError> {
+        // username es PRIMARY KEY: el primero gana, los demás reciben AlreadyExists
+        match Self::lock(&self.identities)?.entry(username.to_string()) {
+            hash_map::Entry::Occupied(_) => Err(MeError::AlreadyExists(username.to_string())),
+            hash_map::Entry::Vacant(slot) => {
+                slot.insert(IdentityRow {
+                    public_key: public_key.to_string(),
+                    encrypted_private_key: encrypted_private_key.to_string(),
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                });
+                Ok(())
+            }
+        }
+    }
+
+    async fn load_keys(
+        &self,
+        username: &str,
+    ) -> Result<(String, String), MeError> {
+        let identities = Self::lock(&self.identities)?;
+        let row = identities
+            .get(username)
+            .ok_or_else(|| MeError::IdentityNotFound(username.to_string()))?;
+        Ok((row.public_key.clone(), row.encrypted_private_key.clone()))
+    }
+
+    async fn update_encrypted_private(
+        &self,
+        username: &str,
+        encrypted: &str,
+    ) -> Result<(), MeError> {
+        if let Some(row) = Self::lock(&self.identities)?.get_mut(username) {
+            row.encrypted_private_key = encrypted.to_string();
+        }
+        Ok(())
+    }
+
+    async fn rewrap_keys(
+        &self,
+        username: &str,
+        encrypted_private_key: &str,
+        keys: &[(String, String)],
+    ) -> Result<(), MeError> {
+        // mismo orden de locks en todo el store: identities, luego keys
+        let mut identities = Self::lock(&self.identities)?;
+        let mut rows = Self::lock(&self.keys)?;
+        let identity = identities
+            .get_mut(username)
+            .ok_or_else(|| MeError::IdentityNotFound(username.to_string()))?;
+        let mut positions = Vec::with_capacity(keys.len());
+        for (authority, encrypted) in keys {
+            let position = rows
+                .iter()
+                .position(|k| k.username == username && k.authority == *authority)
+                .ok_or_else(|| MeError::KeyNotFound(authority.clone()))?;
+            positions.push((position, encrypted));
+        }
+        let stored = rows.iter().filter(|k| k.username == username).count();
+        if stored != positions.len() {
+            return Err(MeError::KeysChanged(username.to_string()));
+        }
+        identity.encrypted_private_key = encrypted_private_key.to_string();
+        for (position, encrypted) in positions {
+            rows[position].encrypted_private_key = encrypted.clone();
+        }
+        Ok(())
+    }
+
+    async fn insert_key(
+        &self,
+        username: &str,
+        authority: &str,
+        public_key: &str,
+        encrypted_private_key: &str,
+    ) -> Result<(), MeError> {
+        let mut keys = Self::lock(&self.keys)?;
+        // context_id es PRIMARY KEY en `keys`
+        if keys.iter().any(|k| k.username == username && k.authority == authority) {
+            return Err(MeError::KeyExists(authority.to_string()));
+        }
+        keys.push(KeyRow {
+            username: username.to_string(),
+            authority: authority.to_string(),
+            public_key: public_key.to_string(),
+            encrypted_private_key: encrypted_private_key.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn load_key(
+        &self,
+        username: &str,
+        authority: &str,
+    ) -> Result<(String, String), MeError> {
+        let keys = Self::lock(&self.keys)?;
+        let row = keys
+            .iter()
+            .find(|k| k.username == username && k.authority == authority)
+            .ok_or_else(|| MeError::KeyNotFound(authority.to_string()))?;
+        Ok((row.public_key.clone(), row.encrypted_private_key.clone()))
+    }
+
+    async fn list_keys(
+        &self,
+        username: &str,
+    ) -> Result<Vec<String>, MeError> {
+        // orden de inserción = ORDER BY created_at
+        Ok(Self::lock(&self.keys)?
+            .iter()
+            .filter(|k| k.username == username)
+            .map(|k| k.authority.clone())
+            .collect())
+    }
+
+    async fn delete_key(
+        &self,
+        username: &str,
+        authority: &str,
+    ) -> Result<(), MeError> {
+        Self::lock(&self.keys)?.retain(|k| !(k.username == username && k.authority == authority));
+        Ok(())
+    }
+
+    async fn insert(
+        &self,
+        verb: Verb,
+        context_id: &str,
+        key: &str,
+        value: &str,
+        timestamp: &str,
+    ) -> Result<(), MeError> {
+        self.insert_row(context_id, &Entry::new(verb, key, value, timestamp))
+    }
+
+    async fn get(
+        &self,
+        filter: &GetFilter,
+    ) -> Result<Vec<Entry>, MeError> {
+        // misma semántica que los backends SQL: el filtro se evalúa como una consulta de core::query
+        let query = Query::try_from(filter)?;
+        let data = Self::lock(&self.tables)?;
+        let mut out: Vec<Entry> = Vec::new();
+
+        for verb in filter.verb.verbs() {
+            let Some(rows) = data.get(&verb) else { continue };
+            let mut matched: Vec<&VerbRow> = rows
+                .iter()
+                .filter(|row| {
+                    let (key, value) = row.projected(verb);
+                    query.matches(&row.context_id, key, value, &row.timestamp)
+                })
+                .collect();
+            matched.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+            // LIMIT/OFFSET por tabla, límite por defecto 100 (como PgStore)
+            let entries = matched
+                .into_iter()
+                .skip(filter.offset.unwrap_or(0))
+                .take(filter.limit.unwrap_or(100))
+                .map(|row| row.entry(verb));
+            out.extend(entries);
+        }
+
+        out.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(out)
+    }
+
+    async fn query(
+        &self,
+        query: &Query,
+    ) -> Result<Vec<Entry>, MeError> {
+        let data = Self::lock(&self.tables)?;
+        let mut out: Vec<Entry> = Vec::new();
+        for verb in query.verbs.verbs() {
+            let Some(rows) = data.get(&verb) else { continue };
+            out.extend(
+                rows.iter()
+                    .filter(|row| {
+                        let (key, value) = row.projected(verb);
+                        query.matches(&row.context_id, key, value, &row.timestamp)
+                    })
+                    .map(|row| row.entry(verb)),
+            );
+        }
+        Ok(query.finish(out))
+    }
+
+    async fn insert_signed(
+        &self,
+        context_id: &str,
+        entry: &Entry,
+    ) -> Result<(), MeError> {
+        self.insert_row(context_id, entry)
+    }
+
+    async fn log(
+        &self,
+        context_id: &str,
+    ) -> Result<Vec<Entry>, MeError> {
+        self.context_rows(context_id)
+    }
+
+    async fn log_head(
+        &self,
+        context_id: &str,
+    ) -> Result<Option<Entry>, MeError> {
+        Ok(self.context_rows(context_id)?.into_iter().rev().find(|e| e.proof.is_some()))
+    }
+
+    async fn contexts(
+        &self,
+    ) -> Result<Vec<String>, MeError> {
+        let mut out: BTreeSet<String> = Self::lock(&self.tables)?
+            .values()
+            .flatten()
+            .map(|row| row.context_id.clone())
+            .collect();
+        out.extend(Self::lock(&self.arc)?.iter().map(|(cid, _)| cid.clone()));
+        Ok(out.into_iter().collect())
+    }
+
+    async fn insert_arc(
+        &self,
+        context_id: &str,
+        entry: &ArcEntry,
+    ) -> Result<(), MeError> {
+        Self::lock(&self.arc)?.push((context_id.to_string(), entry.clone()));
+        Ok(())
+    }
+
+    async fn arc_timeline(
+        &self,
+        context_id: &str,
+        actor: &str,
+        target: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<ArcEntry>, MeError> {
+        let mut out: Vec<ArcEntry> = Self::lock(&self.arc)?
+            .iter()
+            .filter(|(cid, e)| cid == context_id && e.actor == actor && e.target == target)
+            .filter(|(_, e)| since.is_none_or(|s| e.timestamp.as_str() >= s))
+            .filter(|(_, e)| until.is_none_or(|u| e.timestamp.as_str() <= u))
+            .map(|(_, e)| e.clone())
+            .collect();
+        out.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(out)
+    }
+
+    async fn arc_log(
+        &self,
+        context_id: &str,
+    ) -> Result<Vec<ArcEntry>, MeError> {
+        let mut out: Vec<ArcEntry> = Self::lock(&self.arc)?
+            .iter()
+            .filter(|(cid, _)| cid == context_id)
+            .map(|(_, e)| e.clone())
+            .collect();
+        out.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::core::Me;
+
+    fn filter(verb: &str) -> GetFilter {
+        GetFilter {
+            verb: verb.parse().unwrap(),
+            key: None,
+            value: None,
+            context_id: None,
+            limit: None,
+            offset: None,
+            since: None,
+            until: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_load_and_get_without_a_database() {
+        let store = Arc::new(MemoryStore::new());
+        let me = Me::create(store.clone(), "preview", "123456").await.unwrap();
+        let loaded = Me::load(store.clone(), "preview", "123456").await.unwrap();
+        assert_eq!(me.public_key, loaded.public_key);
+        assert!(Me::load(store.clone(), "nobody", "123456").await.is_err());
+
+        me.react(&me.context_id, "meeting:2025", "❤️").await.unwrap();
+        me.communicate(&me.context_id, "group", "see you soon").await.unwrap();
+        me.do_(&me.context_id, "task", "build").await.unwrap();
+
+        let react = loaded.get(&filter("react")).await.unwrap();
+        assert_eq!((react[0].key.as_str(), react[0].value.as_str()), ("meeting:2025", "❤️"));
+        let said = loaded.get(&filter("communicate")).await.unwrap();
+        assert_eq!((said[0].key.as_str(), said[0].value.as_str()), ("group", "see you soon"));
+        assert_eq!(loaded.get(&filter("do")).await.unwrap()[0].verb, Verb::Do);
+        assert_eq!(loaded.get(&filter("all")).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn all_applies_the_default_limit_per_table() {
+        let store = MemoryStore::new();
+        for i in 0..120 {
+            store.insert(Verb::Be, "ctx", "n", &i.to_string(), &format!("2025-01-01T00:00:{:03}Z", i)).await.unwrap();
+        }
+        store.insert(Verb::Have, "ctx", "age", "34", "2025-02-01T00:00:00Z").await.unwrap();
+
+        let all = store.get(&filter("all")).await.unwrap();
+        assert_eq!(all.len(), 101);
+        assert_eq!(all[0].verb, Verb::Have);
+    }
+
+    #[tokio::test]
+    async fn passes_store_conformance() {
+        crate::core::store::conformance::run(&MemoryStore::new()).await.unwrap();
+    }
+
+    #[test]
+    fn like_matches_sqlite_semantics() {
+        use crate::core::query::like;
+        assert!(like("%wallet%", "my WALLET main"));
+        assert!(like("w_llet", "wallet"));
+        assert!(!like("wallet", "wallets"));
+    }
+}
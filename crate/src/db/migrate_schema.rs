@@ -13,16 +13,19 @@ pub fn migrate_schema(conn: &Connection) -> SqlResult<()> {
         [],
     )?;
     // Table for registered keys (secrets)
+    // Per-authority keys: type = authority, public_address = public key (base64)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS keys (
             context_id TEXT PRIMARY KEY,
             username TEXT,
             type TEXT DEFAULT 'generic',
             public_address TEXT,
+            encrypted_private_key TEXT,
             created_at TEXT NOT NULL
         )",
         [],
     )?;
+    add_column_if_missing(conn, "keys", "encrypted_private_key", "TEXT")?;
 
     // Table for "be" verb
     conn.execute(
@@ -94,6 +97,11 @@ pub fn migrate_schema(conn: &Connection) -> SqlResult<()> {
         )",
         [],
     )?;
+    // ARC timelines are read per (actor, target) pair
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS arc_pair_idx ON arc (context_id, actor, target, timestamp)",
+        [],
+    )?;
 
     // Table for "do" verb
     conn.execute(
@@ -119,5 +127,26 @@ pub fn migrate_schema(conn: &Connection) -> SqlResult<()> {
         [],
     )?;
 
+    // Signed log columns (see core::chain); NULL on rows written before the chain existed.
+    // correlation_id groups the primary rows of a secondary verb (see core::ontology)
+    for table in ["be", "have", "at", "relate", "react", "communicate", "do_"] {
+        for column in ["hash", "prev_hash", "signature", "correlation_id"] {
+            add_column_if_missing(conn, table, column, "TEXT")?;
+        }
+    }
+
+    Ok(())
+}
+
+// Databases created before a column existed keep their old table definition.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, ddl_type: &str) -> SqlResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, ddl_type), [])?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
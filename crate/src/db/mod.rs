@@ -1,10 +1,14 @@
 // this.me/src/db/mod.rs
 // by suiGn
+pub mod memory;
 #[cfg(feature = "pg")]
 pub mod pg;
 #[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "sqlite")]
 pub mod migrate_schema;
 #[cfg(feature = "sqlite")]
+#[allow(clippy::module_inception)]
 pub mod db;
 #[cfg(feature = "sqlite")]
 pub use db::connect;
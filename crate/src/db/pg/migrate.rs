@@ -31,12 +31,17 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
             username TEXT,
             type TEXT DEFAULT 'generic',
             public_address TEXT,
+            encrypted_private_key TEXT,
             created_at TEXT NOT NULL
         )
         "#,
     )
     .execute(pool)
     .await?;
+    // llaves por autoridad: type = authority, public_address = public key (base64)
+    sqlx::query(r#"ALTER TABLE me.keys ADD COLUMN IF NOT EXISTS encrypted_private_key TEXT"#)
+        .execute(pool)
+        .await?;
 
     // 3) Verbos (timestamps como TEXT para espejo 1:1 con SQLite)
     for ddl in [
@@ -50,6 +55,90 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
     ] {
         sqlx::query(ddl).execute(pool).await?;
     }
+    // log firmado (core::chain): NULL en filas anteriores a la cadena;
+    // correlation_id agrupa las filas de un verbo secundario (core::ontology)
+    for table in ["be", "do_", "have", "at", "relate", "react", "communicate"] {
+        for column in ["hash", "prev_hash", "signature", "correlation_id"] {
+            sqlx::query(&format!("ALTER TABLE me.{table} ADD COLUMN IF NOT EXISTS {column} TEXT"))
+                .execute(pool)
+                .await?;
+        }
+    }
+
+    // 4) ARC (core::arc). DOUBLE PRECISION = REAL de SQLite (8 bytes); el REAL de Postgres es de 4.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS me.arc (
+            context_id TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            target TEXT NOT NULL,
+            affinity DOUBLE PRECISION DEFAULT 0.0,
+            reality DOUBLE PRECISION DEFAULT 0.0,
+            communication DOUBLE PRECISION DEFAULT 0.0,
+            timestamp TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(r#"CREATE INDEX IF NOT EXISTS arc_pair_idx ON me.arc (context_id, actor, target, timestamp)"#)
+        .execute(pool)
+        .await?;
+
+    // 5) core::query: los filtros `value.$...` leen valores que pueden no ser JSON; el cast directo
+    // (`value::jsonb`) abortaría toda la consulta, así que se usa esta versión que devuelve NULL.
+    sqlx::query(
+        r#"
+        CREATE OR REPLACE FUNCTION me.try_jsonb(input TEXT) RETURNS JSONB AS $$
+        BEGIN
+            RETURN input::jsonb;
+        EXCEPTION WHEN others THEN
+            RETURN NULL;
+        END;
+        $$ LANGUAGE plpgsql IMMUTABLE
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // 6) Índices para los filtros de GetFilter / core::query. GIN jsonb_path_ops sobre el valor
+    // parseado atiende los `json:` (que se compilan con `@>`).
+    for (table, value_col) in [
+        ("be", "value"),
+        ("do_", "value"),
+        ("have", "value"),
+        ("at", "value"),
+        ("relate", "value"),
+        ("react", "emoji"),
+        ("communicate", "message"),
+    ] {
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {table}_value_json_idx ON me.{table} USING GIN (me.try_jsonb({value_col}) jsonb_path_ops)"
+        ))
+        .execute(pool)
+        .await?;
+    }
+    // Trigramas para los `like:` (ILIKE '%p%'). pg_trgm puede no estar disponible o requerir
+    // permisos que el rol no tiene: sin él los filtros funcionan igual, solo sin índice.
+    if sqlx::query(r#"CREATE EXTENSION IF NOT EXISTS pg_trgm"#).execute(pool).await.is_ok() {
+        for (table, key_col, value_col) in [
+            ("be", "key", "value"),
+            ("do_", "key", "value"),
+            ("have", "key", "value"),
+            ("at", "key", "value"),
+            ("relate", "key", "value"),
+            ("react", "target", "emoji"),
+            ("communicate", "target", "message"),
+        ] {
+            for column in [key_col, value_col] {
+                sqlx::query(&format!(
+                    "CREATE INDEX IF NOT EXISTS {table}_{column}_trgm_idx ON me.{table} USING GIN ({column} gin_trgm_ops)"
+                ))
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
 
     Ok(())
 }
\ No newline at end of file
@@ -1,7 +1,12 @@
 use async_trait::async_trait;
-use sqlx::{Pool, Postgres, Row, QueryBuilder};
+use sqlx::{Pool, Postgres, Row, QueryBuilder, Transaction};
 use crate::core::store::MeStore;
-use crate::core::model::{Entry, GetFilter};
+use crate::core::model::{Entry, GetFilter, Proof};
+use crate::core::verbs::Verb;
+use crate::core::arc::{ArcEntry, ArcScores};
+use crate::core::query::{Dialect, Param, Query};
+use crate::core::keys::key_context_id;
+use crate::utils::me_error::MeError;
 
 #[derive(Clone)]
 pub struct PgStore {
@@ -11,21 +16,197 @@ pub struct PgStore {
 impl PgStore {
     pub fn new(pool: Pool<Postgres>) -> Self { Self { pool } }
 
-    fn table_for_verb(verb: &str) -> Option<&'static str> {
+    /// Serializa las escrituras de `me.keys` de un solo username hasta el fin de `tx`
+    /// (rewrap_keys contra insert_key/delete_key); los demás usernames no esperan.
+    async fn lock_keys(tx: &mut Transaction<'_, Postgres>, username: &str) -> Result<(), MeError> {
+        sqlx::query(r#"SELECT pg_advisory_xact_lock(hashtext($1))"#)
+            .bind(username)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
+    async fn insert_row(
+        &self,
+        context_id: &str,
+        entry: &Entry,
+    ) -> Result<(), MeError> {
+        let (verb, key, value, timestamp) = (entry.verb, &entry.key, &entry.value, &entry.timestamp);
+        let proof = entry.proof.as_ref();
+        let correlation_id = entry.correlation_id.as_deref();
+        let hash = proof.map(|p| p.hash.as_str());
+        let prev_hash = proof.map(|p| p.prev_hash.as_str());
+        let signature = proof.map(|p| p.signature.as_str());
+
         match verb {
-            "be" => Some("be"),
-            "have" => Some("have"),
-            "at" => Some("at"),
-            "relate" => Some("relate"),
-            "react" => Some("react"),
-            "communicate" => Some("communicate"),
-            "do" | "do_" => Some("do_"),
-            "all" => None,
-            _ => None,
+            // mirror SQLite schema
+            Verb::React => {
+                // INSERT INTO react(context_id, key, target, emoji, timestamp, ...proof)
+                sqlx::query(
+                    r#"INSERT INTO me.react (context_id, key, target, emoji, timestamp, hash, prev_hash, signature, correlation_id)
+                       VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)"#,
+                )
+                .bind(context_id)
+                .bind("")            // key empty (as in SQLite)
+                .bind(key)           // target = provided "key"
+                .bind(value)         // emoji = provided "value"
+                .bind(timestamp)
+                .bind(hash)
+                .bind(prev_hash)
+                .bind(signature)
+                .bind(correlation_id)
+                .execute(&self.pool)
+                .await?;
+            }
+            Verb::Communicate => {
+                // INSERT INTO communicate(context_id, key, target, message, timestamp, ...proof)
+                sqlx::query(
+                    r#"INSERT INTO me.communicate (context_id, key, target, message, timestamp, hash, prev_hash, signature, correlation_id)
+                       VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)"#,
+                )
+                .bind(context_id)
+                .bind(key)           // key
+                .bind(key)           // target = key (as in SQLite)
+                .bind(value)         // message
+                .bind(timestamp)
+                .bind(hash)
+                .bind(prev_hash)
+                .bind(signature)
+                .bind(correlation_id)
+                .execute(&self.pool)
+                .await?;
+            }
+            Verb::Relate => {
+                // INSERT INTO relate(context_id, key, target, value, timestamp, ...proof)
+                sqlx::query(
+                    r#"INSERT INTO me.relate (context_id, key, target, value, timestamp, hash, prev_hash, signature, correlation_id)
+                       VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)"#,
+                )
+                .bind(context_id)
+                .bind(key)           // key
+                .bind("")            // target empty (as in SQLite)
+                .bind(value)         // value
+                .bind(timestamp)
+                .bind(hash)
+                .bind(prev_hash)
+                .bind(signature)
+                .bind(correlation_id)
+                .execute(&self.pool)
+                .await?;
+            }
+            // default generic schema: (context_id, key, value, timestamp, ...proof)
+            _ => {
+                let sql = format!(
+                    "INSERT INTO me.{} (context_id, key, value, timestamp, hash, prev_hash, signature, correlation_id)
+                     VALUES ($1,$2,$3,$4,$5,$6,$7,$8)",
+                    verb.table()
+                );
+                sqlx::query(&sql)
+                    .bind(context_id)
+                    .bind(key)
+                    .bind(value)
+                    .bind(timestamp)
+                    .bind(hash)
+                    .bind(prev_hash)
+                    .bind(signature)
+                    .bind(correlation_id)
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// SELECT que expone (key, value, ts, hash, prev_hash, signature, correlation_id) para cada tabla.
+    fn select_for_verb(verb: Verb) -> String {
+        let (key_col, value_col) = verb.columns();
+        format!(
+            "SELECT {key_col} AS key, {value_col} AS value, timestamp AS ts, hash, prev_hash, signature, correlation_id FROM me.{} WHERE 1=1",
+            verb.table()
+        )
+    }
+
+    /// Filas de la tabla de `verb` que cumplen `query`, las más nuevas primero. Todo lo que viene del
+    /// usuario va ligado (`$N`): el texto del SQL solo depende de la forma de la consulta.
+    async fn select(&self, verb: Verb, query: &Query, limit: usize, offset: usize) -> Result<Vec<Entry>, sqlx::Error> {
+        let condition = query.to_sql(verb, Dialect::Postgres);
+        let n = condition.params.len();
+        // select_for_verb termina en "WHERE 1=1"
+        let sql = format!(
+            "{} AND {} ORDER BY timestamp DESC LIMIT ${} OFFSET ${}",
+            Self::select_for_verb(verb),
+            condition.sql,
+            n + 1,
+            n + 2
+        );
+        let mut q = sqlx::query(&sql);
+        for param in condition.params {
+            q = match param {
+                Param::Text(s) => q.bind(s),
+                Param::Number(n) => q.bind(n),
+                Param::TextArray(parts) => q.bind(parts),
+            };
         }
+        let limit = limit.min(i64::MAX as usize) as i64;
+        let offset = offset.min(i64::MAX as usize) as i64;
+        let rows = q.bind(limit).bind(offset).fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(|row| Self::entry_from_row(verb, row)).collect())
     }
-    fn all_tables() -> [&'static str; 7] {
-        ["be","have","at","relate","react","communicate","do_"]
+
+    fn entry_from_row(verb: Verb, row: &sqlx::postgres::PgRow) -> Entry {
+        let hash: Option<String> = row.get("hash");
+        let proof = hash.map(|hash| Proof {
+            hash,
+            prev_hash: row.get::<Option<String>, _>("prev_hash").unwrap_or_default(),
+            signature: row.get::<Option<String>, _>("signature").unwrap_or_default(),
+        });
+        Entry {
+            verb,
+            key: row.get("key"),
+            value: row.get("value"),
+            timestamp: row.get("ts"),
+            proof,
+            correlation_id: row.get("correlation_id"),
+        }
+    }
+
+    /// Entradas de un contexto en todas las tablas, en orden cronológico ascendente.
+    /// Con `signed_only` y `newest_only` queda solo la cabeza de la cadena.
+    async fn context_rows(
+        &self,
+        context_id: &str,
+        signed_only: bool,
+        newest_only: bool,
+    ) -> Result<Vec<Entry>, MeError> {
+        let mut out: Vec<Entry> = Vec::new();
+        for verb in Verb::ALL {
+            let mut qb = QueryBuilder::new(Self::select_for_verb(verb));
+            qb.push(" AND context_id = ").push_bind(context_id);
+            if signed_only { qb.push(" AND hash IS NOT NULL"); }
+            if newest_only { qb.push(" ORDER BY timestamp DESC LIMIT 1"); }
+            let rows = qb.build().fetch_all(&self.pool).await?;
+            out.extend(rows.iter().map(|row| Self::entry_from_row(verb, row)));
+        }
+        out.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        if newest_only {
+            out = out.pop().into_iter().collect();
+        }
+        Ok(out)
+    }
+
+    /// (actor, target, affinity, reality, communication, ts) → ArcEntry
+    fn arc_from_row(row: &sqlx::postgres::PgRow) -> ArcEntry {
+        ArcEntry {
+            actor: row.get("actor"),
+            target: row.get("target"),
+            scores: ArcScores::new(
+                row.get::<Option<f64>, _>("affinity").unwrap_or_default(),
+                row.get::<Option<f64>, _>("reality").unwrap_or_default(),
+                row.get::<Option<f64>, _>("communication").unwrap_or_default(),
+            ),
+            timestamp: row.get("ts"),
+        }
     }
 }
 
@@ -36,9 +217,10 @@ impl MeStore for PgStore {
         username: &str,
         public_key: &str,
         encrypted_private_key: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // igual que SQLite pero en schema me: tabla me.me
-        sqlx::query(
+    ) -> Result<(), MeError> {
+        // igual que SQLite pero en schema me: tabla me.me. Con ON CONFLICT la carrera la resuelve
+        // el índice único: quien no insertó la fila recibe AlreadyExists.
+        let inserted = sqlx::query(
             r#"
             INSERT INTO me.me (username, public_key, encrypted_private_key, created_at)
             VALUES ($1, $2, $3, $4)
@@ -50,21 +232,26 @@ impl MeStore for PgStore {
         .bind(encrypted_private_key)
         .bind(chrono::Utc::now().to_rfc3339())
         .execute(&self.pool)
-        .await?;
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(MeError::AlreadyExists(username.to_string()));
+        }
         Ok(())
     }
 
     async fn load_keys(
         &self,
         username: &str,
-    ) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(String, String), MeError> {
         // lee de me.me (no me.identity)
         let row = sqlx::query(
             r#"SELECT public_key, encrypted_private_key FROM me.me WHERE username = $1"#,
         )
         .bind(username)
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| MeError::IdentityNotFound(username.to_string()))?;
 
         let public_key: String = row.get("public_key");
         let enc_priv: String = row.get("encrypted_private_key");
@@ -75,7 +262,7 @@ impl MeStore for PgStore {
         &self,
         username: &str,
         encrypted: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), MeError> {
         // actualiza me.me
         sqlx::query(
             r#"UPDATE me.me SET encrypted_private_key = $1 WHERE username = $2"#,
@@ -87,123 +274,271 @@ impl MeStore for PgStore {
         Ok(())
     }
 
+    async fn rewrap_keys(
+        &self,
+        username: &str,
+        encrypted_private_key: &str,
+        keys: &[(String, String)],
+    ) -> Result<(), MeError> {
+        // sin commit, el Drop de la transacción hace rollback
+        let mut tx = self.pool.begin().await?;
+        // insert_key/delete_key de este username esperan al commit, así el conteo de abajo sigue valiendo
+        Self::lock_keys(&mut tx, username).await?;
+        let updated = sqlx::query(r#"UPDATE me.me SET encrypted_private_key = $1 WHERE username = $2"#)
+            .bind(encrypted_private_key)
+            .bind(username)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if updated == 0 {
+            return Err(MeError::IdentityNotFound(username.to_string()));
+        }
+        for (authority, encrypted) in keys {
+            let updated = sqlx::query(r#"UPDATE me.keys SET encrypted_private_key = $1 WHERE context_id = $2"#)
+                .bind(encrypted)
+                .bind(key_context_id(username, authority))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            if updated == 0 {
+                return Err(MeError::KeyNotFound(authority.clone()));
+            }
+        }
+        // ninguna autoridad guardada puede quedar con el cifrado viejo
+        let stored: i64 = sqlx::query_scalar(
+            r#"SELECT COUNT(*) FROM me.keys WHERE username = $1 AND encrypted_private_key IS NOT NULL"#,
+        )
+        .bind(username)
+        .fetch_one(&mut *tx)
+        .await?;
+        if stored != keys.len() as i64 {
+            return Err(MeError::KeysChanged(username.to_string()));
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn insert_key(
+        &self,
+        username: &str,
+        authority: &str,
+        public_key: &str,
+        encrypted_private_key: &str,
+    ) -> Result<(), MeError> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_keys(&mut tx, username).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO me.keys (context_id, username, type, public_address, encrypted_private_key, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(key_context_id(username, authority))
+        .bind(username)
+        .bind(authority)
+        .bind(public_key)
+        .bind(encrypted_private_key)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => MeError::KeyExists(authority.to_string()),
+            _ => e.into(),
+        })?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_key(
+        &self,
+        username: &str,
+        authority: &str,
+    ) -> Result<(String, String), MeError> {
+        let row = sqlx::query(
+            r#"SELECT public_address, encrypted_private_key FROM me.keys WHERE context_id = $1"#,
+        )
+        .bind(key_context_id(username, authority))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| MeError::KeyNotFound(authority.to_string()))?;
+
+        let public_key: String = row.get("public_address");
+        let enc_priv: String = row.get("encrypted_private_key");
+        Ok((public_key, enc_priv))
+    }
+
+    async fn list_keys(
+        &self,
+        username: &str,
+    ) -> Result<Vec<String>, MeError> {
+        let rows = sqlx::query(
+            r#"SELECT type FROM me.keys
+               WHERE username = $1 AND encrypted_private_key IS NOT NULL
+               ORDER BY created_at, type"#,
+        )
+        .bind(username)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(|row| row.get("type")).collect())
+    }
+
+    async fn delete_key(
+        &self,
+        username: &str,
+        authority: &str,
+    ) -> Result<(), MeError> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_keys(&mut tx, username).await?;
+        sqlx::query(r#"DELETE FROM me.keys WHERE context_id = $1"#)
+            .bind(key_context_id(username, authority))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn insert(
         &self,
-        verb: &str,
+        verb: Verb,
         context_id: &str,
         key: &str,
         value: &str,
         timestamp: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let table = Self::table_for_verb(verb)
-            .ok_or_else(|| format!("Unsupported verb for insert: {}", verb))?;
-
-        match table {
-            // mirror SQLite schema
-            "react" => {
-                // INSERT INTO react(context_id, key, target, emoji, timestamp)
-                sqlx::query(
-                    r#"INSERT INTO me.react (context_id, key, target, emoji, timestamp)
-                       VALUES ($1,$2,$3,$4,$5)"#,
-                )
-                .bind(context_id)
-                .bind("")            // key empty (as in SQLite)
-                .bind(key)           // target = provided "key"
-                .bind(value)         // emoji = provided "value"
-                .bind(timestamp)
-                .execute(&self.pool)
-                .await?;
-            }
-            "communicate" => {
-                // INSERT INTO communicate(context_id, key, target, message, timestamp)
-                sqlx::query(
-                    r#"INSERT INTO me.communicate (context_id, key, target, message, timestamp)
-                       VALUES ($1,$2,$3,$4,$5)"#,
-                )
-                .bind(context_id)
-                .bind(key)           // key
-                .bind(key)           // target = key (as in SQLite)
-                .bind(value)         // message
-                .bind(timestamp)
-                .execute(&self.pool)
-                .await?;
-            }
-            "relate" => {
-                // INSERT INTO relate(context_id, key, target, value, timestamp)
-                sqlx::query(
-                    r#"INSERT INTO me.relate (context_id, key, target, value, timestamp)
-                       VALUES ($1,$2,$3,$4,$5)"#,
-                )
-                .bind(context_id)
-                .bind(key)           // key
-                .bind("")            // target empty (as in SQLite)
-                .bind(value)         // value
-                .bind(timestamp)
-                .execute(&self.pool)
-                .await?;
-            }
-            // default generic schema: (context_id, key, value, timestamp)
-            _ => {
-                let sql = format!(
-                    "INSERT INTO me.{table} (context_id, key, value, timestamp)
-                     VALUES ($1,$2,$3,$4)"
-                );
-                sqlx::query(&sql)
-                    .bind(context_id)
-                    .bind(key)
-                    .bind(value)
-                    .bind(timestamp)
-                    .execute(&self.pool)
-                    .await?;
-            }
-        }
-
-        Ok(())
+    ) -> Result<(), MeError> {
+        self.insert_row(context_id, &Entry::new(verb, key, value, timestamp)).await
     }
 
     async fn get(
         &self,
         filter: &GetFilter,
-    ) -> Result<Vec<Entry>, Box<dyn std::error::Error + Send + Sync>> {
-        let tables: Vec<&str> = if filter.verb == "all" {
-            Self::all_tables().to_vec()
-        } else {
-            vec![Self::table_for_verb(&filter.verb)
-                .ok_or_else(|| format!("Unsupported verb for get(): {}", filter.verb))?]
-        };
+    ) -> Result<Vec<Entry>, MeError> {
+        let query = Query::try_from(filter)?;
+        let mut out: Vec<Entry> = Vec::new();
+        for verb in filter.verb.verbs() {
+            out.extend(self.select(verb, &query, filter.limit.unwrap_or(100), filter.offset.unwrap_or(0)).await?);
+        }
+        // con varios verbos (o All) las tablas se mezclan: orden cronológico inverso global
+        out.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(out)
+    }
 
+    async fn query(
+        &self,
+        query: &Query,
+    ) -> Result<Vec<Entry>, MeError> {
         let mut out: Vec<Entry> = Vec::new();
+        for verb in query.verbs.verbs() {
+            out.extend(self.select(verb, query, query.fetch_limit(), 0).await?);
+        }
+        Ok(query.finish(out))
+    }
 
-        for table in tables {
-            // Build base SELECT depending on table schema
-            let base_sql = match table {
-                "react" => "SELECT target AS key, emoji AS value, timestamp AS ts FROM me.react WHERE 1=1",
-                "communicate" => "SELECT target AS key, message AS value, timestamp AS ts FROM me.communicate WHERE 1=1",
-                _ => {
-                    // be, have, at, relate, do_
-                    &*format!("SELECT key, value, timestamp AS ts FROM me.{table} WHERE 1=1")
-                }
-            };
+    async fn insert_signed(
+        &self,
+        context_id: &str,
+        entry: &Entry,
+    ) -> Result<(), MeError> {
+        self.insert_row(context_id, entry).await
+    }
 
-            let mut qb = QueryBuilder::new(base_sql.to_string());
-            if let Some(cid) = &filter.context_id { qb.push(" AND context_id = ").push_bind(cid); }
-            if let Some(k) = &filter.key { qb.push(" AND key = ").push_bind(k); }
-            if let Some(vv) = &filter.value { qb.push(" AND value = ").push_bind(vv); }
-            if let Some(since) = &filter.since { qb.push(" AND timestamp >= ").push_bind(since); }
-            if let Some(until) = &filter.until { qb.push(" AND timestamp <= ").push_bind(until); }
+    async fn log(
+        &self,
+        context_id: &str,
+    ) -> Result<Vec<Entry>, MeError> {
+        self.context_rows(context_id, false, false).await
+    }
 
-            qb.push(" ORDER BY timestamp DESC ");
-            if let Some(lim) = &filter.limit { qb.push(" LIMIT ").push_bind(*lim as i64); } else { qb.push(" LIMIT 100 "); }
-            if let Some(off) = &filter.offset { qb.push(" OFFSET ").push_bind(*off as i64); }
+    async fn log_head(
+        &self,
+        context_id: &str,
+    ) -> Result<Option<Entry>, MeError> {
+        Ok(self.context_rows(context_id, true, true).await?.pop())
+    }
 
-            let rows = qb.build().fetch_all(&self.pool).await?;
-            for row in rows {
-                let key: String = row.get("key");
-                let value: String = row.get("value");
-                let ts: String = row.get("ts");
-                out.push(Entry { verb: table.to_string(), key, value, timestamp: ts });
-            }
-        }
-        Ok(out)
+    async fn contexts(
+        &self,
+    ) -> Result<Vec<String>, MeError> {
+        let sql = Verb::ALL
+            .iter()
+            .map(|verb| format!("SELECT context_id FROM me.{}", verb.table()))
+            .chain(std::iter::once("SELECT context_id FROM me.arc".to_string()))
+            .collect::<Vec<_>>()
+            .join(" UNION ");
+        let rows = sqlx::query(&format!("SELECT context_id FROM ({}) c WHERE context_id IS NOT NULL ORDER BY context_id", sql))
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.iter().map(|row| row.get("context_id")).collect())
+    }
+
+    async fn insert_arc(
+        &self,
+        context_id: &str,
+        entry: &ArcEntry,
+    ) -> Result<(), MeError> {
+        sqlx::query(
+            r#"INSERT INTO me.arc (context_id, actor, target, affinity, reality, communication, timestamp)
+               VALUES ($1,$2,$3,$4,$5,$6,$7)"#,
+        )
+        .bind(context_id)
+        .bind(&entry.actor)
+        .bind(&entry.target)
+        .bind(entry.scores.affinity)
+        .bind(entry.scores.reality)
+        .bind(entry.scores.communication)
+        .bind(&entry.timestamp)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
     }
-}
\ No newline at end of file
+
+    async fn arc_timeline(
+        &self,
+        context_id: &str,
+        actor: &str,
+        target: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<ArcEntry>, MeError> {
+        let mut qb = QueryBuilder::new(
+            "SELECT actor, target, affinity, reality, communication, timestamp AS ts FROM me.arc WHERE context_id = ",
+        );
+        qb.push_bind(context_id);
+        qb.push(" AND actor = ").push_bind(actor);
+        qb.push(" AND target = ").push_bind(target);
+        if let Some(since) = since { qb.push(" AND timestamp >= ").push_bind(since); }
+        if let Some(until) = until { qb.push(" AND timestamp <= ").push_bind(until); }
+        qb.push(" ORDER BY timestamp ASC");
+
+        let rows = qb.build().fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(Self::arc_from_row).collect())
+    }
+
+    async fn arc_log(
+        &self,
+        context_id: &str,
+    ) -> Result<Vec<ArcEntry>, MeError> {
+        let rows = sqlx::query(
+            "SELECT actor, target, affinity, reality, communication, timestamp AS ts FROM me.arc WHERE context_id = $1 ORDER BY timestamp ASC",
+        )
+        .bind(context_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(Self::arc_from_row).collect())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Requiere una base desechable: borra y recrea el schema `me`.
+    /// THIS_ME_TEST_DATABASE_URL=postgres://... cargo test --features pg -- --ignored
+    #[tokio::test]
+    #[ignore = "needs a disposable Postgres in THIS_ME_TEST_DATABASE_URL"]
+    async fn passes_store_conformance() {
+        let url = std::env::var("THIS_ME_TEST_DATABASE_URL").expect("THIS_ME_TEST_DATABASE_URL not set");
+        let pool = sqlx::postgres::PgPoolOptions::new().connect(&url).await.unwrap();
+        sqlx::query("DROP SCHEMA IF EXISTS me CASCADE").execute(&pool).await.unwrap();
+        crate::db::pg::run_migrations(&pool).await.unwrap();
+        crate::core::store::conformance::run(&PgStore::new(pool)).await.unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+//this.me/crate/src/db/sqlite/mod.rs
+// re-exporta SqliteStore (MeStore sobre ~/.this/me/<alias>/<alias>.db).
+#![cfg(feature = "sqlite")]
+pub mod store;
+pub use store::SqliteStore;
@@ -0,0 +1,627 @@
+//this.me/crate/src/db/sqlite/store.rs
+// MeStore sobre el layout local por alias: ~/.this/me/<alias>/<alias>.db
+// Usa las mismas tablas que migrate_schema (y que PgStore, sin el schema `me.`).
+use std::path::Path;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use rusqlite::{Connection, ErrorCode, OptionalExtension, params};
+use crate::core::store::MeStore;
+use crate::core::model::{Entry, GetFilter, Proof};
+use crate::core::verbs::Verb;
+use crate::core::arc::{ArcEntry, ArcScores};
+use crate::core::query::{Dialect, Param, Query};
+use crate::core::keys::key_context_id;
+use crate::db::migrate_schema::migrate_schema;
+use crate::utils::me_error::MeError;
+
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Abre (o crea) la base local del alias en `~/.this/me/<alias>/<alias>.db`.
+    pub fn open(alias: &str) -> Result<Self, rusqlite::Error> {
+        let (conn, _existed) = crate::db::connect(alias, true)?;
+        // connect() solo migra bases nuevas; las tablas son IF NOT EXISTS, así que es seguro repetir.
+        migrate_schema(&conn)?;
+        Ok(Self::new(conn))
+    }
+
+    /// Abre una base SQLite en una ruta arbitraria (tests, directorios temporales).
+    pub fn open_path(path: &Path) -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open(path)?;
+        migrate_schema(&conn)?;
+        Ok(Self::new(conn))
+    }
+
+    pub fn new(conn: Connection) -> Self { Self { conn: Mutex::new(conn) } }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, MeError> {
+        self.conn.lock().map_err(|_| MeError::Storage("SQLite connection mutex poisoned".into()))
+    }
+
+    fn insert_row(
+        &self,
+        context_id: &str,
+        entry: &Entry,
+    ) -> Result<(), MeError> {
+        let Entry { verb, key, value, timestamp, proof, correlation_id } = entry;
+        let (hash, prev_hash, signature) = match proof {
+            Some(p) => (Some(p.hash.as_str()), Some(p.prev_hash.as_str()), Some(p.signature.as_str())),
+            None => (None, None, None),
+        };
+        let conn = self.lock()?;
+
+        match verb {
+            // react: key vacío, target = key, emoji = value (igual que Verbs::react)
+            Verb::React => conn.execute(
+                "INSERT INTO react (context_id, key, target, emoji, timestamp, hash, prev_hash, signature, correlation_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![context_id, "", key, value, timestamp, hash, prev_hash, signature, correlation_id],
+            )?,
+            // communicate: target = key, message = value
+            Verb::Communicate => conn.execute(
+                "INSERT INTO communicate (context_id, key, target, message, timestamp, hash, prev_hash, signature, correlation_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![context_id, key, key, value, timestamp, hash, prev_hash, signature, correlation_id],
+            )?,
+            // relate: target vacío
+            Verb::Relate => conn.execute(
+                "INSERT INTO relate (context_id, key, target, value, timestamp, hash, prev_hash, signature, correlation_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![context_id, key, "", value, timestamp, hash, prev_hash, signature, correlation_id],
+            )?,
+            // esquema genérico: (context_id, key, value, timestamp)
+            _ => conn.execute(
+                &format!(
+                    "INSERT INTO {} (context_id, key, value, timestamp, hash, prev_hash, signature, correlation_id)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    verb.table()
+                ),
+                params![context_id, key, value, timestamp, hash, prev_hash, signature, correlation_id],
+            )?,
+        };
+
+        Ok(())
+    }
+
+    /// Entradas de un contexto en todas las tablas, en orden cronológico ascendente.
+    /// Con `signed_only` y `newest_only` queda solo la cabeza de la cadena.
+    fn context_rows(
+        &self,
+        context_id: &str,
+        signed_only: bool,
+        newest_only: bool,
+    ) -> Result<Vec<Entry>, MeError> {
+        let conn = self.lock()?;
+        let mut out: Vec<Entry> = Vec::new();
+        for verb in Verb::ALL {
+            let (key_col, value_col) = verb.columns();
+            let mut sql = format!(
+                "SELECT {key_col}, {value_col}, timestamp, hash, prev_hash, signature, correlation_id FROM {} WHERE context_id = ?1",
+                verb.table()
+            );
+            if signed_only {
+                sql.push_str(" AND hash IS NOT NULL");
+            }
+            if newest_only {
+                sql.push_str(" ORDER BY timestamp DESC LIMIT 1");
+            }
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params![context_id], |row| Self::entry_from_row(verb, row))?;
+            for row in rows {
+                out.push(row?);
+            }
+        }
+        out.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        if newest_only {
+            out = out.pop().into_iter().collect();
+        }
+        Ok(out)
+    }
+
+    /// (actor, target, affinity, reality, communication, timestamp) → ArcEntry
+    fn arc_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ArcEntry> {
+        Ok(ArcEntry {
+            actor: row.get(0)?,
+            target: row.get(1)?,
+            // columnas con DEFAULT 0.0, pero nullable en el esquema
+            scores: ArcScores::new(
+                row.get::<_, Option<f64>>(2)?.unwrap_or_default(),
+                row.get::<_, Option<f64>>(3)?.unwrap_or_default(),
+                row.get::<_, Option<f64>>(4)?.unwrap_or_default(),
+            ),
+            timestamp: row.get(5)?,
+        })
+    }
+
+    /// (key, value, timestamp, hash, prev_hash, signature, correlation_id) → Entry
+    fn entry_from_row(verb: Verb, row: &rusqlite::Row<'_>) -> rusqlite::Result<Entry> {
+        let hash: Option<String> = row.get(3)?;
+        let proof = match hash {
+            Some(hash) => Some(Proof {
+                hash,
+                prev_hash: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                signature: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            }),
+            None => None,
+        };
+        Ok(Entry {
+            verb,
+            key: row.get(0)?,
+            value: row.get(1)?,
+            timestamp: row.get(2)?,
+            proof,
+            correlation_id: row.get(6)?,
+        })
+    }
+}
+
+/// `GetFilter` sobre una conexión: LIMIT/OFFSET por tabla (100 por defecto) y mezcla en orden
+/// cronológico inverso. También lo usa `Verbs::get`.
+pub(crate) fn get_entries(conn: &Connection, filter: &GetFilter) -> Result<Vec<Entry>, MeError> {
+    let query = Query::try_from(filter)?;
+    let mut out: Vec<Entry> = Vec::new();
+    for verb in filter.verb.verbs() {
+        out.extend(select(conn, verb, &query, filter.limit.unwrap_or(100), filter.offset.unwrap_or(0))?);
+    }
+    // con varios verbos (o All) las tablas se mezclan: orden cronológico inverso global
+    out.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(out)
+}
+
+/// Filas de la tabla de `verb` que cumplen `query`, las más nuevas primero. Todo lo que viene del
+/// usuario va ligado (`?N`): el texto del SQL solo depende de la forma de la consulta.
+fn select(conn: &Connection, verb: Verb, query: &Query, limit: usize, offset: usize) -> rusqlite::Result<Vec<Entry>> {
+    let (key_col, value_col) = verb.columns();
+    let condition = query.to_sql(verb, Dialect::Sqlite);
+    let n = condition.params.len();
+    let sql = format!(
+        "SELECT {key_col}, {value_col}, timestamp, hash, prev_hash, signature, correlation_id FROM {} WHERE {} ORDER BY timestamp DESC LIMIT ?{} OFFSET ?{}",
+        verb.table(),
+        condition.sql,
+        n + 1,
+        n + 2
+    );
+    let mut args: Vec<rusqlite::types::Value> = condition.params.into_iter().map(sqlite_value).collect();
+    args.push((limit.min(i64::MAX as usize) as i64).into());
+    args.push((offset.min(i64::MAX as usize) as i64).into());
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(args), |row| SqliteStore::entry_from_row(verb, row))?;
+    rows.collect()
+}
+
+/// Parámetro de `Query::to_sql` → valor de rusqlite (`Dialect::Sqlite` nunca produce `TextArray`).
+fn sqlite_value(param: Param) -> rusqlite::types::Value {
+    match param {
+        Param::Text(s) => s.into(),
+        Param::Number(n) => n.into(),
+        Param::TextArray(parts) => parts.join(".").into(),
+    }
+}
+
+#[async_trait]
+impl MeStore for SqliteStore {
+    async fn create_identity(
+        &self,
+        username: &str,
+        public_key: &str,
+        encrypted_private_key: &str,
+    ) -> Result<(), MeError> {
+        let conn = self.lock()?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO me (username, public_key, encrypted_private_key, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![username, public_key, encrypted_private_key, chrono::Utc::now().to_rfc3339()],
+        )?;
+        if inserted == 0 {
+            return Err(MeError::AlreadyExists(username.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn load_keys(
+        &self,
+        username: &str,
+    ) -> Result<(String, String), MeError> {
+        let conn = self.lock()?;
+        let keys = conn.query_row(
+            "SELECT public_key, encrypted_private_key FROM me WHERE username = ?1",
+            params![username],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+        keys.ok_or_else(|| MeError::IdentityNotFound(username.to_string()))
+    }
+
+    async fn update_encrypted_private(
+        &self,
+        username: &str,
+        encrypted: &str,
+    ) -> Result<(), MeError> {
+        let conn = self.lock()?;
+        conn.execute(
+            "UPDATE me SET encrypted_private_key = ?1 WHERE username = ?2",
+            params![encrypted, username],
+        )?;
+        Ok(())
+    }
+
+    async fn rewrap_keys(
+        &self,
+        username: &str,
+        encrypted_private_key: &str,
+        keys: &[(String, String)],
+    ) -> Result<(), MeError> {
+        let mut conn = self.lock()?;
+        let tx = conn.transaction()?;
+        if tx.execute(
+            "UPDATE me SET encrypted_private_key = ?1 WHERE username = ?2",
+            params![encrypted_private_key, username],
+        )? == 0 {
+            return Err(MeError::IdentityNotFound(username.to_string()));
+        }
+        for (authority, encrypted) in keys {
+            if tx.execute(
+                "UPDATE keys SET encrypted_private_key = ?1 WHERE context_id = ?2",
+                params![encrypted, key_context_id(username, authority)],
+            )? == 0 {
+                return Err(MeError::KeyNotFound(authority.clone()));
+            }
+        }
+        // ninguna autoridad guardada puede quedar con el cifrado viejo
+        let stored: usize = tx.query_row(
+            "SELECT COUNT(*) FROM keys WHERE username = ?1 AND encrypted_private_key IS NOT NULL",
+            params![username],
+            |row| row.get(0),
+        )?;
+        if stored != keys.len() {
+            return Err(MeError::KeysChanged(username.to_string()));
+        }
+        // sin commit (cualquier return de arriba) la transacción se descarta al soltarla
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn insert_key(
+        &self,
+        username: &str,
+        authority: &str,
+        public_key: &str,
+        encrypted_private_key: &str,
+    ) -> Result<(), MeError> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO keys (context_id, username, type, public_address, encrypted_private_key, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                key_context_id(username, authority),
+                username,
+                authority,
+                public_key,
+                encrypted_private_key,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        ).map_err(|e| match e.sqlite_error_code() {
+            // context_id es PRIMARY KEY en `keys`
+            Some(ErrorCode::ConstraintViolation) => MeError::KeyExists(authority.to_string()),
+            _ => e.into(),
+        })?;
+        Ok(())
+    }
+
+    async fn load_key(
+        &self,
+        username: &str,
+        authority: &str,
+    ) -> Result<(String, String), MeError> {
+        let conn = self.lock()?;
+        let keys = conn.query_row(
+            "SELECT public_address, encrypted_private_key FROM keys WHERE context_id = ?1",
+            params![key_context_id(username, authority)],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+        keys.ok_or_else(|| MeError::KeyNotFound(authority.to_string()))
+    }
+
+    async fn list_keys(
+        &self,
+        username: &str,
+    ) -> Result<Vec<String>, MeError> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT type FROM keys WHERE username = ?1 AND encrypted_private_key IS NOT NULL ORDER BY created_at, type",
+        )?;
+        let authorities = stmt
+            .query_map(params![username], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(authorities)
+    }
+
+    async fn delete_key(
+        &self,
+        username: &str,
+        authority: &str,
+    ) -> Result<(), MeError> {
+        let conn = self.lock()?;
+        conn.execute(
+            "DELETE FROM keys WHERE context_id = ?1",
+            params![key_context_id(username, authority)],
+        )?;
+        Ok(())
+    }
+
+    async fn insert(
+        &self,
+        verb: Verb,
+        context_id: &str,
+        key: &str,
+        value: &str,
+        timestamp: &str,
+    ) -> Result<(), MeError> {
+        self.insert_row(context_id, &Entry::new(verb, key, value, timestamp))
+    }
+
+    async fn get(
+        &self,
+        filter: &GetFilter,
+    ) -> Result<Vec<Entry>, MeError> {
+        let conn = self.lock()?;
+        get_entries(&conn, filter)
+    }
+
+    async fn query(
+        &self,
+        query: &Query,
+    ) -> Result<Vec<Entry>, MeError> {
+        let conn = self.lock()?;
+        let mut out: Vec<Entry> = Vec::new();
+        for verb in query.verbs.verbs() {
+            out.extend(select(&conn, verb, query, query.fetch_limit(), 0)?);
+        }
+        Ok(query.finish(out))
+    }
+
+    async fn insert_signed(
+        &self,
+        context_id: &str,
+        entry: &Entry,
+    ) -> Result<(), MeError> {
+        self.insert_row(context_id, entry)
+    }
+
+    async fn log(
+        &self,
+        context_id: &str,
+    ) -> Result<Vec<Entry>, MeError> {
+        self.context_rows(context_id, false, false)
+    }
+
+    async fn log_head(
+        &self,
+        context_id: &str,
+    ) -> Result<Option<Entry>, MeError> {
+        Ok(self.context_rows(context_id, true, true)?.pop())
+    }
+
+    async fn contexts(
+        &self,
+    ) -> Result<Vec<String>, MeError> {
+        let conn = self.lock()?;
+        let sql = Verb::ALL
+            .iter()
+            .map(|verb| format!("SELECT context_id FROM {}", verb.table()))
+            .chain(std::iter::once("SELECT context_id FROM arc".to_string()))
+            .collect::<Vec<_>>()
+            .join(" UNION ");
+        let mut stmt = conn.prepare(&format!("SELECT context_id FROM ({}) WHERE context_id IS NOT NULL ORDER BY context_id", sql))?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<Result<Vec<String>, _>>()?)
+    }
+
+    async fn insert_arc(
+        &self,
+        context_id: &str,
+        entry: &ArcEntry,
+    ) -> Result<(), MeError> {
+        let conn = self.lock()?;
+        conn.execute(
+            "INSERT INTO arc (context_id, actor, target, affinity, reality, communication, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                context_id,
+                entry.actor,
+                entry.target,
+                entry.scores.affinity,
+                entry.scores.reality,
+                entry.scores.communication,
+                entry.timestamp
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn arc_timeline(
+        &self,
+        context_id: &str,
+        actor: &str,
+        target: &str,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Vec<ArcEntry>, MeError> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT actor, target, affinity, reality, communication, timestamp FROM arc
+             WHERE context_id = ?1 AND actor = ?2 AND target = ?3
+               AND (?4 IS NULL OR timestamp >= ?4) AND (?5 IS NULL OR timestamp <= ?5)
+             ORDER BY timestamp ASC",
+        )?;
+        let rows = stmt.query_map(params![context_id, actor, target, since, until], Self::arc_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    async fn arc_log(
+        &self,
+        context_id: &str,
+    ) -> Result<Vec<ArcEntry>, MeError> {
+        let conn = self.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT actor, target, affinity, reality, communication, timestamp FROM arc
+             WHERE context_id = ?1 ORDER BY timestamp ASC",
+        )?;
+        let rows = stmt.query_map(params![context_id], Self::arc_from_row)?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::core::Me;
+
+    fn temp_store(name: &str) -> (SqliteStore, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("this-me-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.db", name));
+        let _ = std::fs::remove_file(&path);
+        (SqliteStore::open_path(&path).unwrap(), dir)
+    }
+
+    fn filter(verb: &str) -> GetFilter {
+        GetFilter {
+            verb: verb.parse().unwrap(),
+            key: None,
+            value: None,
+            context_id: None,
+            limit: None,
+            offset: None,
+            since: None,
+            until: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_load_and_query_identity() {
+        let (store, dir) = temp_store("sqlite_store_identity");
+        let store = Arc::new(store);
+
+        let me = Me::create(store.clone(), "suign", "123456").await.unwrap();
+        let loaded = Me::load(store.clone(), "suign", "123456").await.unwrap();
+        assert_eq!(me.public_key, loaded.public_key);
+        assert_eq!(me.context_id, loaded.context_id);
+        assert!(Me::load(store.clone(), "suign", "wrong").await.is_err());
+
+        me.be(&me.context_id, "species", "human").await.unwrap();
+        me.react(&me.context_id, "project:cleaker", "🚀").await.unwrap();
+        me.have(&me.context_id, "wallet", r#"{"type":"eth","alias":"main"}"#).await.unwrap();
+
+        let react = loaded.get(&filter("react")).await.unwrap();
+        assert_eq!(react.len(), 1);
+        assert_eq!(react[0].key, "project:cleaker");
+        assert_eq!(react[0].value, "🚀");
+
+        let mut f = filter("have");
+        f.value = Some("json:type=eth".into());
+        assert_eq!(loaded.get(&f).await.unwrap().len(), 1);
+
+        assert_eq!(loaded.get(&filter("all")).await.unwrap().len(), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_log_detects_rows_edited_in_the_database() {
+        let (store, dir) = temp_store("sqlite_store_chain");
+        let store = Arc::new(store);
+        let me = Me::create(store.clone(), "suign", "123456").await.unwrap();
+        me.have(&me.context_id, "age", "34").await.unwrap();
+        me.communicate(&me.context_id, "group", "see you soon").await.unwrap();
+        assert!(me.verify_log(&me.context_id).await.unwrap().is_intact());
+
+        store.lock().unwrap().execute("UPDATE have SET value = '21'", []).unwrap();
+        let report = me.verify_log(&me.context_id).await.unwrap();
+        let broken = report.broken.unwrap();
+        assert_eq!((broken.position, broken.entry.value.as_str()), (0, "21"));
+        assert_eq!(report.verified, 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn imports_a_bundle_exported_from_another_store() {
+        use crate::core::bundle::Bundle;
+        use crate::db::memory::MemoryStore;
+        use crate::utils::kdf::FAST;
+        let memory = Arc::new(MemoryStore::new());
+        let me = Me::create_with_kdf(memory.clone(), "suign", "123456", FAST).await.unwrap();
+        me.be(&me.context_id, "species", "human").await.unwrap();
+        me.react(&me.context_id, "project:cleaker", "🚀").await.unwrap();
+        me.declare(&me.context_id, "travel", "to", "cdmx").await.unwrap();
+        me.arc(&me.context_id, "suign", "alice", 0.5, 0.25, 1.0).await.unwrap();
+        let bundle = me.export_bundle_with_kdf("pass", None, FAST).await.unwrap();
+
+        let (store, dir) = temp_store("sqlite_store_bundle");
+        let store = Arc::new(store);
+        let contents = Bundle::from_json(&bundle.to_json().unwrap()).unwrap().open("pass").unwrap();
+        let report = contents.import_into(store.as_ref()).await.unwrap();
+        assert!(report.created && report.conflicts.is_empty());
+        assert_eq!((report.entries_imported, report.arc_imported), (contents.metadata.entries, 1));
+
+        let moved = Me::load(store.clone(), "suign", "123456").await.unwrap();
+        assert!(moved.verify_log(&me.context_id).await.unwrap().is_intact());
+        assert_eq!(moved.declared(&me.context_id, "travel").await.unwrap().len(), 1);
+        assert_eq!(moved.get(&filter("react")).await.unwrap()[0].value, "🚀");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn passes_store_conformance() {
+        let (store, dir) = temp_store("sqlite_store_conformance");
+        crate::core::store::conformance::run(&store).await.unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn random_filters_match_memory_and_leave_the_schema_alone() {
+        use crate::core::query::fuzz;
+        use crate::db::memory::MemoryStore;
+        let (store, dir) = temp_store("sqlite_store_fuzz");
+        let memory = MemoryStore::new();
+        for (i, (verb, cid, key, value)) in fuzz::ROWS.into_iter().enumerate() {
+            let ts = format!("2025-01-01T00:00:{:02}+00:00", i);
+            store.insert(verb, cid, key, value, &ts).await.unwrap();
+            memory.insert(verb, cid, key, value, &ts).await.unwrap();
+        }
+        let schema = || -> String {
+            store.lock().unwrap().query_row("SELECT group_concat(sql, ';') FROM sqlite_master", [], |row| row.get(0)).unwrap()
+        };
+        let before = schema();
+        let rows = |entries: Vec<Entry>| -> Vec<(Verb, String, String)> { entries.into_iter().map(|e| (e.verb, e.key, e.value)).collect() };
+
+        let mut rng = fuzz::rng(17);
+        let mut hits = 0;
+        for _ in 0..fuzz::CASES {
+            let f = fuzz::filter(&mut rng, false);
+            match (store.get(&f).await, memory.get(&f).await) {
+                (Ok(a), Ok(b)) => {
+                    hits += usize::from(!a.is_empty());
+                    assert_eq!(rows(a), rows(b), "{:?}", f);
+                }
+                (Err(_), Err(_)) => {}
+                (a, b) => panic!("{:?}: sqlite {:?} / memory {:?}", f, a.map(|_| ()), b.map(|_| ())),
+            }
+            let text = fuzz::query(&mut rng, false);
+            if let Ok(q) = text.parse::<Query>() {
+                let a = store.query(&q).await.unwrap_or_else(|e| panic!("{}: {}", text, e));
+                hits += usize::from(!a.is_empty());
+                assert_eq!(rows(a), rows(memory.query(&q).await.unwrap()), "{}", text);
+            }
+        }
+        assert!(hits > fuzz::CASES / 10, "only {} cases matched rows", hits);
+        assert_eq!(schema(), before);
+        assert_eq!(store.get(&filter("all")).await.unwrap().len(), fuzz::ROWS.len());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -10,35 +10,67 @@ use std::process::Command;
 use owo_colors::OwoColorize;
 use atty;
 use get_if_addrs::get_if_addrs;
+use serde::Serialize;
+use crate::utils::me_error::MeError;
+
+/// What `this-me host` reports (also its `--json` output).
+#[derive(Debug, Clone, Serialize)]
+pub struct HostInfo {
+    pub hostname: Option<String>,
+    pub primary_ip: Option<String>,
+    pub os: &'static str,
+    pub arch: &'static str,
+}
+
+/// One entry of `this-me network`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub ip: String,
+}
+
+pub fn host_info() -> HostInfo {
+    HostInfo {
+        hostname: current_hostname(),
+        primary_ip: discover_primary_ip(),
+        os: env::consts::OS,
+        arch: env::consts::ARCH,
+    }
+}
+
+pub fn network_interfaces() -> Result<Vec<NetworkInterface>, MeError> {
+    let ifaces = get_if_addrs()?;
+    Ok(ifaces
+        .into_iter()
+        .map(|iface| NetworkInterface { ip: iface.addr.ip().to_string(), name: iface.name })
+        .collect())
+}
+
 /// Print a small summary about the machine running the CLI.
 ///
 /// We keep the API result-based so callers can surface a friendly
 /// error message without panicking.
-pub fn print_host_summary() -> Result<(), String> {
+pub fn print_host_summary() -> Result<(), MeError> {
     // Enable color on stdout if terminal supports it
     if atty::is(atty::Stream::Stdout) {
         // No manual override needed; owo_colors auto-detects support.
     }
     println!("\n{}", "======HOST INFORMATION======".bright_yellow().bold());
-    let hostname = current_hostname().unwrap_or_else(|| "(unknown)".to_string());
-    let primary_ip = discover_primary_ip().unwrap_or_else(|| "(unavailable)".to_string());
+    let info = host_info();
+    let hostname = info.hostname.unwrap_or_else(|| "(unknown)".to_string());
+    let primary_ip = info.primary_ip.unwrap_or_else(|| "(unavailable)".to_string());
     println!("{} {}", "🖥️  Hostname :".bright_blue().bold(), hostname.white());
     println!("{} {}", "🌐 Primary IP :".cyan().bold(), primary_ip.white());
-    println!("{} {} {}", "🧰 Platform :".magenta().bold(), env::consts::OS.white(), env::consts::ARCH.white());
+    println!("{} {} {}", "🧰 Platform :".magenta().bold(), info.os.white(), info.arch.white());
     println!("{} {}", "🗓️  Rust env :".bright_cyan().bold(), env::var("RUSTUP_TOOLCHAIN").unwrap_or_else(|_| "default".into()).bright_white());
     println!("{}", "---------------------------------\n".bright_black());
     Ok(())
 }
 
-pub fn print_network_summary() -> Result<(), String> {
+pub fn print_network_summary() -> Result<(), MeError> {
     println!("\n{}", "====== NETWORK INTERFACES ======".bright_yellow().bold());
-    let ifaces = get_if_addrs().map_err(|e| format!("Failed to get network interfaces: {}", e))?;
-    for iface in ifaces {
-        let name_colored = iface.name.bright_blue();
-        let ip = iface.addr.ip();
-        let ip_string = ip.to_string();
-        let ip_colored = ip_string.white();
-        println!("🔸 {} {}", name_colored, ip_colored);
+    for iface in network_interfaces()? {
+        println!("🔸 {} {}", iface.name.bright_blue(), iface.ip.white());
     }
     println!("{}", "---------------------------------".bright_black());
     Ok(())
@@ -60,7 +92,7 @@ fn current_hostname() -> Option<String> {
     }
 
     // Fallback: ask the OS via the `hostname` command.
-    Command::new(if cfg!(target_os = "windows") { "hostname" } else { "hostname" })
+    Command::new("hostname")
         .output()
         .ok()
         .and_then(|out| String::from_utf8(out.stdout).ok())
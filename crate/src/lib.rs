@@ -1 +1,16 @@
 // Crate root — this-me/crate/src/lib.rs
+pub mod core;
+pub mod db;
+pub mod utils;
+pub mod manager;
+pub mod host;
+#[allow(non_snake_case)]
+pub mod Link;
+#[cfg(feature = "sqlite")]
+pub mod verbs;
+#[cfg(feature = "sqlite")]
+pub mod qrcode;
+#[cfg(feature = "sqlite")]
+pub mod cli;
+#[cfg(feature = "sqlite")]
+pub mod shell;
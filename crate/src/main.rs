@@ -1,5 +1,14 @@
 // this.me/crate/src/main.rs
 // by suiGn
-fn main() {
-    println!("Hello, this.me!");
-}
\ No newline at end of file
+use clap::Parser;
+use this_me::cli::{self, Cli};
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+    if let Err(e) = cli::run(cli).await {
+        cli::report_error(json, e.as_ref());
+        std::process::exit(1);
+    }
+}
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 use crate::utils::me_error::MeError;
 #[cfg(feature = "sqlite")]
 use rusqlite;
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize)]
 pub struct MeSummary {
     pub username: String,
     pub path: PathBuf,
@@ -14,10 +14,9 @@ pub struct MeSummary {
 /// Loads public data (e.g., username and public_key) for an identity
 #[cfg(feature = "sqlite")]
 pub fn load_public(username: &str) -> Result<(String, String), MeError> {
-    let home = dirs::home_dir().ok_or_else(|| MeError::Validation("No HOME dir".to_string()))?;
-    let db_path = home.join(".this").join("me").join(username).join(format!("{}.db", username));
+    let db_path = db_path(username)?;
     if !db_path.exists() {
-        return Err(MeError::Validation("Identity does not exist.".to_string()));
+        return Err(MeError::IdentityNotFound(username.to_string()));
     }
 
     let conn = rusqlite::Connection::open(&db_path).map_err(MeError::Database)?;
@@ -32,6 +31,12 @@ pub fn load_public(username: &str) -> Result<(String, String), MeError> {
     }
 }
 
+/// `~/.this/me/<username>/<username>.db` (la misma ruta que abre `db::connect`); puede no existir.
+pub fn db_path(username: &str) -> Result<PathBuf, MeError> {
+    let home = dirs::home_dir().ok_or_else(|| MeError::Validation("No HOME dir".to_string()))?;
+    Ok(home.join(".this").join("me").join(username).join(format!("{}.db", username)))
+}
+
 pub fn list_us() -> Result<Vec<MeSummary>, MeError> {
     let home = dirs::home_dir().ok_or_else(|| MeError::Validation("No HOME dir".to_string()))?;
     let base_path = home.join(".this").join("me");
@@ -1,89 +1,77 @@
-use aes_gcm::aead::{Aead, KeyInit, OsRng, generic_array::GenericArray};
-use aes_gcm::{Aes256Gcm, Nonce}; // 96-bits nonce
-use pbkdf2::pbkdf2_hmac;
-use sha2::Sha256;
-use hmac::Hmac;
-use rand::RngCore;
 use base64::{engine::general_purpose, Engine as _};
 use qrcode::QrCode;
 use qrcode::render::unicode;
+use owo_colors::OwoColorize;
 use image::Luma;
-use std::fs;
 use std::path::Path;
 use std::io::{self, Write};
 use rusqlite::{Connection, params};
-use serde_json::json;
-
-type HmacSha256 = Hmac<Sha256>;
-
-/// Deriva una clave AES-256 (32 bytes) desde una passphrase y salt (16 bytes)
-fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
-    let mut key = [0u8; 32];
-    // PBKDF2 con 100_000 iteraciones (ajustable según perfil de seguridad)
-    let iterations: u32 = 100_000;
-    pbkdf2_hmac::<HmacSha256>(
-        passphrase.as_bytes(),
-        salt,
-        iterations,
-        &mut key,
-    );
-    key
+use serde::{Deserialize, Serialize};
+use crate::core::recovery::RecoveryShare;
+use crate::core::store::MeStore;
+use crate::utils::envelope::{AeadId, Envelope};
+use crate::utils::kdf::Kdf;
+use crate::utils::me_error::MeError;
+
+/// JSON cifrado dentro del sello híbrido.
+/// `public_key` y `encrypted_private_key` (la llave `main` tal como la guarda el store, cifrada con
+/// el password de la identidad) van desde que los sellos se pueden importar (`import_seal`);
+/// los sellos anteriores solo traen username, context_id y versión.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealPayload {
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_private_key: Option<String>,
 }
 
-/// Cifra `plaintext` con passphrase. Devuelve un string base64 que concatena salt||nonce||ciphertext
-pub fn encrypt_payload_to_base64(plaintext: &str, passphrase: &str) -> Result<String, String> {
-    // salt (16 bytes) y nonce (12 bytes)
-    let mut salt = [0u8; 16];
-    let mut nonce_bytes = [0u8; 12];
-    rand::rngs::OsRng.fill_bytes(&mut salt);
-    rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
-
-    let key_bytes = derive_key(passphrase, &salt);
-    let key = GenericArray::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-
-    let nonce = Nonce::from_slice(&nonce_bytes); // 96-bit nonce
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext.as_bytes())
-        .map_err(|e| format!("encrypt error: {}", e))?;
-
-    // Output: salt || nonce || ciphertext (todos bytes), luego base64
-    let mut out = Vec::with_capacity(16 + 12 + ciphertext.len());
-    out.extend_from_slice(&salt);
-    out.extend_from_slice(&nonce_bytes);
-    out.extend_from_slice(&ciphertext);
-
-    Ok(general_purpose::STANDARD.encode(&out))
+impl SealPayload {
+    pub fn new(username: &str, context_id: Option<&str>, public_key: &str, encrypted_private_key: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            context_id: context_id.map(str::to_string),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            public_key: Some(public_key.to_string()),
+            encrypted_private_key: Some(encrypted_private_key.to_string()),
+        }
+    }
 }
 
-/// Dado el base64 (salt||nonce||ciphertext) y la passphrase, retorna plaintext
-pub fn decrypt_base64_to_payload(b64: &str, passphrase: &str) -> Result<String, String> {
-    let bytes = general_purpose::STANDARD
-        .decode(b64)
-        .map_err(|e| format!("base64 decode error: {}", e))?;
+/// AEAD y KDF de los sellos QR: AES-256-GCM con PBKDF2 (100_000 iteraciones, ajustable según perfil de seguridad).
+/// Quedan registrados en el envelope, así que cambiarlos no rompe sellos ya impresos.
+const SEAL_AEAD: AeadId = AeadId::Aes256Gcm;
+const SEAL_KDF: Kdf = Kdf::Pbkdf2Sha256 { iterations: 100_000 };
 
-    if bytes.len() < 16 + 12 + 1 {
-        return Err("ciphertext too short".into());
-    }
-    let salt = &bytes[0..16];
-    let nonce_bytes = &bytes[16..28];
-    let ciphertext = &bytes[28..];
+/// Cifra `plaintext` con passphrase. Devuelve un `Envelope` en base64 (salt, nonce y parámetros incluidos)
+pub fn encrypt_payload_to_base64(plaintext: &str, passphrase: &str) -> Result<String, MeError> {
+    Ok(Envelope::seal_with_password(plaintext.as_bytes(), passphrase, SEAL_AEAD, SEAL_KDF, &[])?.to_base64()?)
+}
 
-    let key_bytes = derive_key(passphrase, salt);
-    let key = GenericArray::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Nonce::from_slice(nonce_bytes);
+/// Dado el base64 (envelope o layout legacy salt||nonce||ciphertext) y la passphrase, retorna plaintext
+pub fn decrypt_base64_to_payload(b64: &str, passphrase: &str) -> Result<String, MeError> {
+    let bytes = general_purpose::STANDARD.decode(b64)?;
 
-    let plaintext_bytes = cipher
-        .decrypt(nonce, ciphertext.as_ref())
-        .map_err(|e| format!("decrypt error: {}", e))?;
+    let envelope = if Envelope::is_envelope(&bytes) {
+        Envelope::from_bytes(&bytes)
+    } else {
+        Envelope::from_legacy_qr(&bytes)
+    }
+    .map_err(|e| MeError::CorruptData(e.to_string()))?;
 
-    String::from_utf8(plaintext_bytes).map_err(|e| format!("utf8 error: {}", e))
+    let plaintext_bytes = envelope
+        .open_with_password(passphrase)
+        .map_err(MeError::from_unseal)?;
+
+    String::from_utf8(plaintext_bytes).map_err(|e| MeError::CorruptData(format!("utf8 error: {}", e)))
 }
 
 /// Genera QR (unicode) en terminal y salva seal.png con el blob base64
-pub fn render_encrypted_qr_and_png(base64_blob: &str, ctx_path: &Path) -> Result<(), String> {
-    let code = QrCode::new(base64_blob.as_bytes()).map_err(|e| format!("qr error: {}", e))?;
+pub fn render_encrypted_qr_and_png(base64_blob: &str, ctx_path: &Path) -> Result<(), MeError> {
+    let code = QrCode::new(base64_blob.as_bytes()).map_err(|e| MeError::Validation(format!("qr error: {}", e)))?;
     let qr_text = code.render::<unicode::Dense1x2>().quiet_zone(true).build();
     println!();
     println!("{}", "🔏 Encrypted seal (scan to import):".bright_white().bold());
@@ -94,52 +82,78 @@ pub fn render_encrypted_qr_and_png(base64_blob: &str, ctx_path: &Path) -> Result
     // imagen PNG
     let img = code.render::<Luma<u8>>().build();
     let seal_path = ctx_path.join("seal_encrypted.png");
-    img.save(&seal_path).map_err(|e| format!("save png error: {}", e))?;
+    img.save(&seal_path).map_err(|e| MeError::Io(io::Error::other(format!("save png error: {}", e))))?;
+    println!("(also saved as {})", seal_path.display());
+    Ok(())
+}
+
+/// Sello QR de un share de recuperación (`core::recovery`) para un guardián sin identidad .me:
+/// JSON del share cifrado con passphrase, en terminal y como `seal_share_<índice>.png`.
+/// Se importa con `decrypt_base64_to_payload` + `RecoveryShare::from_json`.
+pub fn render_share_qr(share: &RecoveryShare, label: &str, passphrase: &str, ctx_path: &Path) -> Result<(), MeError> {
+    let blob = encrypt_payload_to_base64(&share.to_json()?, passphrase)?;
+    let code = QrCode::new(blob.as_bytes()).map_err(|e| MeError::Validation(format!("qr error: {}", e)))?;
+    let qr_text = code.render::<unicode::Dense1x2>().quiet_zone(true).build();
+    println!();
+    println!(
+        "{}",
+        format!("🧩 Recovery share {}/{} for {} ({} needed):", share.index, share.total, label, share.threshold)
+            .bright_white()
+            .bold()
+    );
+    println!();
+    println!("{}", qr_text);
+    println!();
+
+    let img = code.render::<Luma<u8>>().build();
+    let seal_path = ctx_path.join(format!("seal_share_{}.png", share.index));
+    img.save(&seal_path).map_err(|e| MeError::Io(io::Error::other(format!("save png error: {}", e))))?;
     println!("(also saved as {})", seal_path.display());
     Ok(())
 }
 
 /// Helper: leer passphrase de manera segura (no eco)
-pub fn prompt_passphrase(prompt: &str) -> Result<String, String> {
+pub fn prompt_passphrase(prompt: &str) -> Result<String, MeError> {
     print!("{}", prompt);
-    io::stdout().flush().map_err(|e| format!("{}", e))?;
-    rpassword::read_password().map_err(|e| format!("{}", e))
+    io::stdout().flush()?;
+    Ok(rpassword::read_password()?)
 }
 
 /// Crea un blob híbrido con formato BASE64(pub)||"."||BASE64(enc_blob)
-pub fn create_hybrid_blob(public_key: &str, plaintext_payload: &str, passphrase: &str) -> Result<String, String> {
+pub fn create_hybrid_blob(public_key: &str, plaintext_payload: &str, passphrase: &str) -> Result<String, MeError> {
     let enc_blob = encrypt_payload_to_base64(plaintext_payload, passphrase)?;
     let pub_b64 = general_purpose::STANDARD.encode(public_key.as_bytes());
-    Ok(format!("{}.{})", pub_b64, enc_blob))
+    Ok(format!("{}.{}", pub_b64, enc_blob))
 }
 
 /// Parsea el blob híbrido y retorna (public_key, encrypted_blob)
-pub fn parse_hybrid_blob(hybrid: &str) -> Result<(String, String), String> {
+pub fn parse_hybrid_blob(hybrid: &str) -> Result<(String, String), MeError> {
     let parts: Vec<&str> = hybrid.splitn(2, '.').collect();
     if parts.len() != 2 {
-        return Err("invalid hybrid blob format".into());
+        return Err(MeError::Validation("invalid hybrid blob format".into()));
     }
     let pub_bytes = general_purpose::STANDARD
         .decode(parts[0])
-        .map_err(|e| format!("base64 decode error for public key: {}", e))?;
-    let public_key = String::from_utf8(pub_bytes).map_err(|e| format!("utf8 error for public key: {}", e))?;
-    let enc_blob = parts[1].to_string();
+        .map_err(|e| MeError::Validation(format!("base64 decode error for public key: {}", e)))?;
+    let public_key = String::from_utf8(pub_bytes).map_err(|e| MeError::Validation(format!("utf8 error for public key: {}", e)))?;
+    // los sellos impresos antes del fix de `create_hybrid_blob` terminan en ")" (nunca es base64)
+    let enc_blob = parts[1].strip_suffix(')').unwrap_or(parts[1]).to_string();
 
     // sanity checks
     if public_key.is_empty() {
-        return Err("empty public key in hybrid blob".into());
+        return Err(MeError::Validation("empty public key in hybrid blob".into()));
     }
     if enc_blob.is_empty() {
-        return Err("empty encrypted blob in hybrid blob".into());
+        return Err(MeError::Validation("empty encrypted blob in hybrid blob".into()));
     }
 
     Ok((public_key, enc_blob))
 }
 
 /// Renderiza QR híbrido con public key visible y salva imagen PNG
-pub fn render_hybrid_qr(public_key: &str, plaintext_payload: &str, passphrase: &str, ctx_path: &Path) -> Result<(), String> {
+pub fn render_hybrid_qr(public_key: &str, plaintext_payload: &str, passphrase: &str, ctx_path: &Path) -> Result<(), MeError> {
     let hybrid_blob = create_hybrid_blob(public_key, plaintext_payload, passphrase)?;
-    let code = QrCode::new(hybrid_blob.as_bytes()).map_err(|e| format!("qr error: {}", e))?;
+    let code = QrCode::new(hybrid_blob.as_bytes()).map_err(|e| MeError::Validation(format!("qr error: {}", e)))?;
     let qr_text = code.render::<unicode::Dense1x2>().quiet_zone(true).build();
 
     println!();
@@ -153,39 +167,140 @@ pub fn render_hybrid_qr(public_key: &str, plaintext_payload: &str, passphrase: &
     println!();
 
     // Print the hybrid blob structure showing visible base64-encoded public key and encrypted blob
-    println!("{}", format!("Hybrid Blob Structure: <base64(pubkey)>.<base64(encrypted_blob)>").bright_black());
+    println!("{}", "Hybrid Blob Structure: <base64(pubkey)>.<base64(encrypted_blob)>".bright_black());
     println!("{}", hybrid_blob.bright_black());
     println!();
 
     let img = code.render::<Luma<u8>>().build();
     let seal_path = ctx_path.join("seal_hybrid_encrypted.png");
-    img.save(&seal_path).map_err(|e| format!("save png error: {}", e))?;
+    img.save(&seal_path).map_err(|e| MeError::Io(io::Error::other(format!("save png error: {}", e))))?;
     println!("(also saved as {})", seal_path.display());
     Ok(())
 }
 
-pub fn from_sqlite_to_qr(db_path: &Path, passphrase: &str, ctx_path: &Path) -> Result<(), String> {
-    let conn = Connection::open(db_path).map_err(|e| format!("failed to open db: {}", e))?;
-    let mut stmt = conn.prepare("SELECT username, public_key, context_id, version FROM me")
-        .map_err(|e| format!("failed to prepare query: {}", e))?;
-    let mut rows = stmt.query(params![]).map_err(|e| format!("failed to execute query: {}", e))?;
-
-    while let Some(row) = rows.next().map_err(|e| format!("failed to fetch row: {}", e))? {
-        let username: String = row.get(0).map_err(|e| format!("failed to get username: {}", e))?;
-        let public_key: String = row.get(1).map_err(|e| format!("failed to get public_key: {}", e))?;
-        let context_id: String = row.get(2).map_err(|e| format!("failed to get context_id: {}", e))?;
-        let version: String = row.get(3).map_err(|e| format!("failed to get version: {}", e))?;
-
-        let json_obj = json!({
-            "username": username,
-            "context_id": context_id,
-            "version": version
-        });
-        let json_string = json_obj.to_string();
-
-        render_hybrid_qr(&public_key, &json_string, passphrase, ctx_path)?;
+pub fn from_sqlite_to_qr(db_path: &Path, passphrase: &str, ctx_path: &Path) -> Result<(), MeError> {
+    let conn = Connection::open(db_path)?;
+    let mut stmt = conn.prepare("SELECT username, public_key, encrypted_private_key FROM me")?;
+    let mut rows = stmt.query(params![])?;
+
+    while let Some(row) = rows.next()? {
+        let username: String = row.get(0)?;
+        let public_key: String = row.get(1)?;
+        let encrypted_private_key: String = row.get(2)?;
+
+        // context_id sale de la llave descifrada: sin password no se conoce
+        let payload = SealPayload::new(&username, None, &public_key, &encrypted_private_key);
+        render_hybrid_qr(&public_key, &serde_json::to_string(&payload)?, passphrase, ctx_path)?;
         println!("✅ Generated hybrid QR for {}", username);
     }
 
     Ok(())
+}
+
+/// Lee el QR de una imagen (PNG o JPEG, p. ej. un `seal_*.png` o una foto) y devuelve su texto.
+pub fn decode_qr_image(path: &Path) -> Result<String, MeError> {
+    let img = image::open(path)
+        .map_err(|e| MeError::Validation(format!("cannot read image {}: {}", path.display(), e)))?
+        .to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(img);
+    let grids = prepared.detect_grids();
+    let grid = grids
+        .first()
+        .ok_or_else(|| MeError::Validation(format!("no QR code found in {}", path.display())))?;
+    let (_, content) = grid.decode().map_err(|e| MeError::Validation(format!("qr decode error: {}", e)))?;
+    Ok(content.trim().to_string())
+}
+
+/// Descifra un sello híbrido. Si el payload trae public key, tiene que ser la visible:
+/// la parte visible no está autenticada, el payload sí.
+pub fn open_seal(hybrid: &str, passphrase: &str) -> Result<SealPayload, MeError> {
+    let (public_key, enc_blob) = parse_hybrid_blob(hybrid.trim())?;
+    let payload: SealPayload = serde_json::from_str(&decrypt_base64_to_payload(&enc_blob, passphrase)?)
+        .map_err(|e| MeError::CorruptData(format!("seal payload: {}", e)))?;
+    if payload.public_key.as_deref().is_some_and(|inner| inner != public_key) {
+        return Err(MeError::CorruptData("the visible public key does not match the sealed one".into()));
+    }
+    Ok(payload)
+}
+
+/// Vuelve a crear la fila de la identidad del sello en `store` (después se abre con `Me::load` y el
+/// password de siempre). `Ok(false)` si ya estaba con la misma public key; con otra,
+/// `MeError::AlreadyExists`. Los verbos no viajan en el sello.
+pub async fn import_seal<S: MeStore + ?Sized>(store: &S, payload: &SealPayload) -> Result<bool, MeError> {
+    let (Some(public_key), Some(encrypted_private_key)) = (&payload.public_key, &payload.encrypted_private_key) else {
+        return Err(MeError::Validation(format!(
+            "The seal of '{}' predates key export and cannot restore the identity",
+            payload.username
+        )));
+    };
+    match store.create_identity(&payload.username, public_key, encrypted_private_key).await {
+        Ok(()) => Ok(true),
+        Err(MeError::AlreadyExists(_)) if store.load_keys(&payload.username).await?.0 == *public_key => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::core::Me;
+    use crate::db::memory::MemoryStore;
+    use crate::utils::kdf::FAST;
+
+    #[test]
+    fn hybrid_blobs_round_trip() {
+        let blob = create_hybrid_blob("pk", "{}", "passphrase").unwrap();
+        let (public_key, enc_blob) = parse_hybrid_blob(&blob).unwrap();
+        assert_eq!(public_key, "pk");
+        assert_eq!(decrypt_base64_to_payload(&enc_blob, "passphrase").unwrap(), "{}");
+        assert!(matches!(decrypt_base64_to_payload(&enc_blob, "wrong"), Err(MeError::WrongPassword)));
+    }
+
+    #[test]
+    fn parses_blobs_written_with_the_trailing_paren() {
+        // formato viejo: format!("{}.{})", pub_b64, enc_blob)
+        let payload = serde_json::to_string(&SealPayload::new("suign", None, "pk", "enc")).unwrap();
+        let old = format!("{})", create_hybrid_blob("pk", &payload, "passphrase").unwrap());
+        let (public_key, enc_blob) = parse_hybrid_blob(&old).unwrap();
+        assert_eq!(public_key, "pk");
+        assert_eq!(decrypt_base64_to_payload(&enc_blob, "passphrase").unwrap(), payload);
+        assert_eq!(open_seal(&old, "passphrase").unwrap().username, "suign");
+    }
+
+    #[tokio::test]
+    async fn seals_rehydrate_the_identity_from_a_png() {
+        let store = Arc::new(MemoryStore::new());
+        let me = Me::create_with_kdf(store.clone(), "suign", "123456", FAST).await.unwrap();
+        let (_, encrypted) = store.load_keys("suign").await.unwrap();
+        let payload = SealPayload::new("suign", Some(&me.context_id), &me.public_key, &encrypted);
+        let blob = create_hybrid_blob(&me.public_key, &serde_json::to_string(&payload).unwrap(), "seal-pass").unwrap();
+
+        let dir = std::env::temp_dir().join(format!("this-me-seal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png = dir.join("seal.png");
+        QrCode::new(blob.as_bytes()).unwrap().render::<Luma<u8>>().build().save(&png).unwrap();
+        let scanned = decode_qr_image(&png).unwrap();
+        assert_eq!(scanned, blob);
+        assert_eq!(parse_hybrid_blob(&scanned).unwrap().0, me.public_key);
+
+        let opened = open_seal(&scanned, "seal-pass").unwrap();
+        assert_eq!(opened, payload);
+        let target = Arc::new(MemoryStore::new());
+        assert!(import_seal(target.as_ref(), &opened).await.unwrap());
+        assert!(!import_seal(target.as_ref(), &opened).await.unwrap());
+        let restored = Me::load(target, "suign", "123456").await.unwrap();
+        assert_eq!(restored.context_id, me.context_id);
+
+        // parte visible cambiada por otra public key
+        let (_, enc_blob) = parse_hybrid_blob(&blob).unwrap();
+        let other = format!("{}.{}", general_purpose::STANDARD.encode("someone-else"), enc_blob);
+        assert!(matches!(open_seal(&other, "seal-pass"), Err(MeError::CorruptData(_))));
+        // sello viejo, sin llave
+        let legacy = SealPayload { public_key: None, encrypted_private_key: None, ..payload };
+        assert!(matches!(import_seal(store.as_ref(), &legacy).await, Err(MeError::Validation(_))));
+        assert!(decode_qr_image(&dir.join("missing.png")).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
\ No newline at end of file
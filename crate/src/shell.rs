@@ -0,0 +1,362 @@
+//this.me/crate/src/shell.rs
+// `me shell`: desbloquea la identidad una vez y deja el `Me` descifrado en memoria.
+//
+//     suign@wikipedia.org/page> be name "Ana"
+//     suign@wikipedia.org/page> travel to Berlin            (secundario de la ontología)
+//     suign@wikipedia.org/page> get have --since 2025-01-01
+//
+// El contexto activo es el de la pila de `Link` de la identidad (`link`, `back`, `forward`, `go`);
+// sin link, el contexto propio de la identidad.
+// El historial se guarda en ~/.this/me/<username>/shell_history.
+use std::collections::BTreeSet;
+use std::error::Error;
+use clap::Parser;
+use owo_colors::OwoColorize;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
+use crate::cli::{print_entries, print_link, FilterArgs};
+use crate::core::{GetFilter, Me, Ontology, Query, QueryError, Verb, VerbSelector};
+use crate::db::sqlite::SqliteStore;
+use crate::manager;
+use crate::Link::ContextStack;
+
+/// Comandos propios del shell (además de los verbos y los secundarios).
+const COMMANDS: [&str; 11] = ["get", "query", "link", "back", "forward", "go", "bookmark", "context", "help", "exit", "quit"];
+
+#[derive(Debug)]
+enum Statement {
+    Write { verb: Verb, key: String, value: String },
+    Declare { secondary: String, key: String, value: String },
+    Get(Box<GetLine>),
+    Query(Box<Query>),
+    Link(String),
+    Back,
+    Forward,
+    Go(String),
+    Bookmark(String),
+    Context,
+    Help,
+    Exit,
+}
+
+/// `get [verbos] [--key ..] [--value ..] [--since ..] ...`: los mismos filtros que `me get`.
+#[derive(Debug, Parser)]
+#[command(name = "get", no_binary_name = true)]
+struct GetLine {
+    /// A verb, a comma-separated list (`be,have`) or `all`.
+    #[arg(default_value = "all")]
+    verb: VerbSelector,
+    #[command(flatten)]
+    filter: FilterArgs,
+}
+
+/// Parte una línea en palabras; `"..."` y `'...'` agrupan, `\` escapa el siguiente carácter
+/// (salvo dentro de comillas simples).
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => word.get_or_insert_with(String::new).push(c),
+            (_, '\\') => {
+                let escaped = chars.next().ok_or("Trailing backslash")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote".into());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// `None` para una línea vacía.
+fn parse(line: &str, ontology: &Ontology) -> Result<Option<Statement>, String> {
+    // el lenguaje de consulta tiene sus propias comillas: el resto de la línea va tal cual al parser
+    if let Some(text) = line.trim_start().strip_prefix("query").filter(|t| t.is_empty() || t.starts_with(char::is_whitespace)) {
+        return text.parse().map(|query| Some(Statement::Query(Box::new(query)))).map_err(|e: QueryError| e.to_string());
+    }
+    let words = split_words(line)?;
+    let Some((first, rest)) = words.split_first() else { return Ok(None) };
+    let key_value = |what: &str| match rest {
+        [key, value @ ..] if !value.is_empty() => Ok((key.clone(), value.join(" "))),
+        _ => Err(format!("Usage: {} <key> <value>", what)),
+    };
+    let statement = match first.as_str() {
+        "get" => Statement::Get(Box::new(GetLine::try_parse_from(rest).map_err(|e| e.to_string())?)),
+        "link" => match rest {
+            [path] => Statement::Link(path.clone()),
+            _ => return Err("Usage: link <path>".into()),
+        },
+        "back" => Statement::Back,
+        "forward" => Statement::Forward,
+        "go" => match rest {
+            [name] => Statement::Go(name.clone()),
+            _ => return Err("Usage: go <bookmark>".into()),
+        },
+        "bookmark" => match rest {
+            [name] => Statement::Bookmark(name.clone()),
+            _ => return Err("Usage: bookmark <name>".into()),
+        },
+        "context" => Statement::Context,
+        "help" | "?" => Statement::Help,
+        "exit" | "quit" => Statement::Exit,
+        word => {
+            if let Ok(verb) = word.parse::<Verb>() {
+                let (key, value) = key_value(word)?;
+                Statement::Write { verb, key, value }
+            } else if ontology.secondary(word).is_some() {
+                let (key, value) = key_value(word)?;
+                Statement::Declare { secondary: word.to_string(), key, value }
+            } else {
+                return Err(format!("Unknown verb or command '{}' (try `help`)", word));
+            }
+        }
+    };
+    Ok(Some(statement))
+}
+
+/// Completa verbos, secundarios y comandos en la primera palabra, y claves conocidas del contexto
+/// (o verbos, después de `get`) en la segunda.
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct ShellHelper {
+    first_words: Vec<String>,
+    keys: BTreeSet<String>,
+}
+
+impl ShellHelper {
+    fn new(ontology: &Ontology) -> Self {
+        let mut first_words: Vec<String> = Verb::ALL.iter().map(|v| v.name().to_string()).collect();
+        first_words.extend(ontology.secondaries().map(|s| s.name.clone()));
+        first_words.extend(COMMANDS.iter().map(|c| c.to_string()));
+        Self { first_words, keys: BTreeSet::new() }
+    }
+
+    fn candidates(&self, before: &[&str], prefix: &str) -> Vec<String> {
+        let pool: Vec<&str> = match before {
+            [] => self.first_words.iter().map(String::as_str).collect(),
+            ["get"] => Verb::ALL.iter().map(|v| v.name()).chain(["all"]).collect(),
+            [word] if !COMMANDS.contains(word) => self.keys.iter().map(String::as_str).collect(),
+            _ => Vec::new(),
+        };
+        pool.into_iter().filter(|c| c.starts_with(prefix)).map(str::to_string).collect()
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let before: Vec<&str> = line[..start].split_whitespace().collect();
+        Ok((start, self.candidates(&before, &line[start..])))
+    }
+}
+
+fn active_context(me: &Me<SqliteStore>, stack: &ContextStack) -> String {
+    match stack.current() {
+        Ok(Some(entry)) => entry.path,
+        _ => me.context_id.clone(),
+    }
+}
+
+fn prompt(me: &Me<SqliteStore>, context_id: &str) -> String {
+    let context: String = if context_id.chars().count() > 24 {
+        context_id.chars().take(23).chain(['…']).collect()
+    } else {
+        context_id.to_string()
+    };
+    format!("{}@{}> ", me.username.bright_cyan().bold(), context.bright_black())
+}
+
+/// Claves ya usadas en el contexto, para el autocompletado.
+async fn known_keys(me: &Me<SqliteStore>, context_id: &str) -> BTreeSet<String> {
+    let filter = GetFilter {
+        verb: VerbSelector::All,
+        key: None,
+        value: None,
+        context_id: Some(context_id.to_string()),
+        limit: None,
+        offset: None,
+        since: None,
+        until: None,
+    };
+    match me.get(&filter).await {
+        Ok(entries) => entries.into_iter().map(|e| e.key).filter(|k| !k.is_empty()).collect(),
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+fn remember_key(editor: &mut Editor<ShellHelper, DefaultHistory>, key: String) {
+    if let Some(helper) = editor.helper_mut() {
+        if !key.is_empty() {
+            helper.keys.insert(key);
+        }
+    }
+}
+
+fn print_help(ontology: &Ontology) {
+    let verbs: Vec<&str> = Verb::ALL.iter().map(|v| v.name()).collect();
+    let secondaries: Vec<&str> = ontology.secondaries().map(|s| s.name.as_str()).collect();
+    println!("{} <key> <value>     {}", "<verb>".bright_cyan(), verbs.join(", "));
+    println!("{} <key> <value>   {}", "<secondary>".bright_cyan(), secondaries.join(", "));
+    println!("{} [verbs] [--key k] [--value v] [--since t] [--until t] [--limit n] [--offset n] [--context c]", "get".bright_cyan());
+    println!("{} <query>           e.g. query have where key ~ \"wallet%\" and value.$.chain = \"eth\" limit 20", "query".bright_cyan());
+    println!("{} <path> / {} / {}   change the active context", "link".bright_cyan(), "back".bright_cyan(), "forward".bright_cyan());
+    println!("{} <name> / {} <name>   bookmark the active context / link to a bookmark", "bookmark".bright_cyan(), "go".bright_cyan());
+    println!("{}                  show the active context", "context".bright_cyan());
+    println!("{} / Ctrl-D            leave the shell", "exit".bright_cyan());
+}
+
+/// Bucle del REPL sobre una identidad ya desbloqueada.
+pub async fn run(me: Me<SqliteStore>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ShellHelper::new(me.ontology())));
+    let history = manager::db_path(&me.username)?.with_file_name("shell_history");
+    // no existe hasta la primera sesión
+    let _ = editor.load_history(&history);
+
+    let stack = ContextStack::for_identity(&me.username)?;
+    let mut context_id = active_context(&me, &stack);
+    let keys = known_keys(&me, &context_id).await;
+    if let Some(helper) = editor.helper_mut() {
+        helper.keys = keys;
+    }
+    println!("{} {} (type {} for commands)", "🧠 .me shell —".bright_white().bold(), me.username.bright_cyan().bold(), "help".bright_cyan());
+
+    loop {
+        let line = match editor.readline(&prompt(&me, &context_id)) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let statement = match parse(&line, me.ontology()) {
+            Ok(Some(statement)) => statement,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("{} {}", "❌".red(), e);
+                continue;
+            }
+        };
+        editor.add_history_entry(line.as_str())?;
+
+        let result: Result<(), Box<dyn Error + Send + Sync>> = match statement {
+            Statement::Write { verb, key, value } => me.insert(verb, &context_id, &key, &value).await.map(|_| {
+                println!("{} {} {} = {}", "✅".green(), verb.bright_cyan().bold(), key, value);
+                remember_key(&mut editor, key);
+            }).map_err(Into::into),
+            Statement::Declare { secondary, key, value } => me.declare(&context_id, &secondary, &key, &value).await.map(|correlation_id| {
+                println!("{} {} {} = {} {}", "✅".green(), secondary.bright_cyan().bold(), key, value, correlation_id.bright_black());
+                remember_key(&mut editor, key);
+            }).map_err(Into::into),
+            Statement::Get(get) => {
+                let GetLine { verb, mut filter } = *get;
+                filter.context = filter.context.or_else(|| Some(context_id.clone()));
+                me.get(&filter.into_filter(verb)).await.map(|entries| print_entries(&entries)).map_err(Into::into)
+            }
+            Statement::Query(mut query) => {
+                query.context_id.get_or_insert_with(|| context_id.clone());
+                me.query(&query).await.map(|entries| print_entries(&entries)).map_err(Into::into)
+            }
+            Statement::Link(path) => stack.link(&path).map(|e| print_link("🔗 Linked to", &e)).map_err(Into::into),
+            Statement::Back => stack.back().map(|e| print_link("🔙 Back to", &e)).map_err(Into::into),
+            Statement::Forward => stack.forward().map(|e| print_link("🔜 Forward to", &e)).map_err(Into::into),
+            Statement::Go(name) => stack.go(&name).map(|e| print_link("🔗 Linked to", &e)).map_err(Into::into),
+            Statement::Bookmark(name) => stack
+                .bookmark(&name, Some(&context_id))
+                .map(|b| println!("🔖 {} → {}", name.bright_cyan(), b.path))
+                .map_err(Into::into),
+            Statement::Context => {
+                println!("{} {}", "🧭 Context:".magenta().bold(), context_id);
+                Ok(())
+            }
+            Statement::Help => {
+                print_help(me.ontology());
+                Ok(())
+            }
+            Statement::Exit => break,
+        };
+        if let Err(e) = result {
+            eprintln!("{} {}", "❌".red(), e);
+        }
+
+        let active = active_context(&me, &stack);
+        if active != context_id {
+            context_id = active;
+            let keys = known_keys(&me, &context_id).await;
+            if let Some(helper) = editor.helper_mut() {
+                helper.keys = keys;
+            }
+        }
+    }
+
+    editor.save_history(&history)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_words() {
+        assert_eq!(split_words(r#"be name "Ana María""#).unwrap(), ["be", "name", "Ana María"]);
+        assert_eq!(split_words(r#"have wallet '{"type":"eth"}'"#).unwrap(), ["have", "wallet", r#"{"type":"eth"}"#]);
+        assert_eq!(split_words(r#"say _ "he said \"hi\"" "" "#).unwrap(), ["say", "_", r#"he said "hi""#, ""]);
+        assert!(split_words(r#"be name "Ana"#).is_err());
+    }
+
+    #[test]
+    fn parses_verbs_secondaries_and_get() {
+        let ontology = Ontology::builtin();
+        let Some(Statement::Write { verb, key, value }) = parse(r#"be name "Ana""#, &ontology).unwrap() else { panic!() };
+        assert_eq!((verb, key.as_str(), value.as_str()), (Verb::Be, "name", "Ana"));
+        let Some(Statement::Write { value, .. }) = parse("at location Cancún, Mexico", &ontology).unwrap() else { panic!() };
+        assert_eq!(value, "Cancún, Mexico");
+        assert!(matches!(parse("travel to Berlin", &ontology).unwrap(), Some(Statement::Declare { .. })));
+
+        let Some(Statement::Get(get)) = parse("get have --since 2025-01-01", &ontology).unwrap() else { panic!() };
+        assert_eq!(get.verb, VerbSelector::One(Verb::Have));
+        assert_eq!(get.filter.since.as_deref(), Some("2025-01-01"));
+        let Some(Statement::Get(get)) = parse("get", &ontology).unwrap() else { panic!() };
+        assert_eq!(get.verb, VerbSelector::All);
+
+        let Some(Statement::Query(query)) = parse(r#"query have where key ~ "wallet\%" limit 3"#, &ontology).unwrap() else { panic!() };
+        assert_eq!((query.verbs, query.limit), (VerbSelector::One(Verb::Have), Some(3)));
+        assert!(parse("query have where", &ontology).is_err());
+
+        assert!(parse("   ", &ontology).unwrap().is_none());
+        assert!(parse("be name", &ontology).is_err());
+        assert!(parse("fly to Mars", &ontology).is_err());
+        assert!(parse("get dance", &ontology).is_err());
+        assert!(matches!(parse("go wiki", &ontology).unwrap(), Some(Statement::Go(_))));
+        assert!(parse("bookmark my wiki", &ontology).is_err());
+    }
+
+    #[test]
+    fn completes_verbs_and_known_keys() {
+        let mut helper = ShellHelper::new(&Ontology::builtin());
+        helper.keys.extend(["name".to_string(), "nickname".to_string(), "age".to_string()]);
+        assert_eq!(helper.candidates(&[], "tr"), ["travel"]);
+        assert!(helper.candidates(&[], "c").contains(&"communicate".to_string()));
+        assert_eq!(helper.candidates(&["be"], "n"), ["name", "nickname"]);
+        assert_eq!(helper.candidates(&["get"], "a"), ["at", "all"]);
+        assert!(helper.candidates(&["be", "name"], "").is_empty());
+        assert!(helper.candidates(&["link"], "").is_empty());
+    }
+}
@@ -1,10 +1,7 @@
 //utils/crypto.rs
-use chacha20poly1305::{
-    aead::{Aead, KeyInit, OsRng},
-    ChaCha20Poly1305, Key, Nonce,
-};
-use rand::RngCore;
+use chacha20poly1305::Key;
 use thiserror::Error;
+use crate::utils::envelope::{AeadId, Envelope};
 
 #[derive(Debug, Error)]
 pub enum CryptoError {
@@ -14,6 +11,10 @@ pub enum CryptoError {
     DecryptionFailed,
     #[error("Invalid key length")]
     InvalidKeyLength,
+    #[error("Invalid KDF parameters: {0}")]
+    InvalidKdf(String),
+    #[error("Invalid envelope: {0}")]
+    InvalidEnvelope(String),
 }
 
 // Derives a fixed-size key from a hash string
@@ -28,40 +29,32 @@ fn derive_key_from_hash(hash: &str) -> Result<Key, CryptoError> {
         key_bytes.copy_from_slice(&hash_bytes[..32]);
     }
 
-    Ok(Key::from_slice(&key_bytes).clone())
+    Ok(*Key::from_slice(&key_bytes))
 }
 
 pub fn encrypt_string(hash: &str, plaintext: &str) -> Result<Vec<u8>, CryptoError> {
     let key = derive_key_from_hash(hash)?;
-    let cipher = ChaCha20Poly1305::new(&key);
-    let mut nonce_bytes = [0u8; 12];
-    OsRng.fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
-
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext.as_bytes())
-        .map_err(|_| CryptoError::EncryptionFailed)?;
-
-    // Prepend nonce to the result
-    let mut result = nonce_bytes.to_vec();
-    result.extend(ciphertext);
-    Ok(result)
+    encrypt_with_key(&key, plaintext)
 }
 
 pub fn decrypt_string(hash: &str, ciphertext: &[u8]) -> Result<String, CryptoError> {
     let key = derive_key_from_hash(hash)?;
-    let cipher = ChaCha20Poly1305::new(&key);
-
-    if ciphertext.len() < 12 {
-        return Err(CryptoError::DecryptionFailed);
-    }
-
-    let (nonce_bytes, cipher_bytes) = ciphertext.split_at(12);
-    let nonce = Nonce::from_slice(nonce_bytes);
+    decrypt_with_key(&key, ciphertext)
+}
 
-    let decrypted = cipher
-        .decrypt(nonce, cipher_bytes)
-        .map_err(|_| CryptoError::DecryptionFailed)?;
+/// Encrypts with a raw 32-byte key (e.g. the output of `utils::kdf`).
+/// Output is a version-1 `Envelope` (ChaCha20-Poly1305, no KDF).
+pub fn encrypt_with_key(key: &[u8], plaintext: &str) -> Result<Vec<u8>, CryptoError> {
+    Envelope::seal_with_key(plaintext.as_bytes(), key, AeadId::ChaCha20Poly1305, &[])?.to_bytes()
+}
 
+/// Inverse of `encrypt_with_key`. Also reads the legacy nonce || ciphertext layout.
+pub fn decrypt_with_key(key: &[u8], ciphertext: &[u8]) -> Result<String, CryptoError> {
+    let envelope = if Envelope::is_envelope(ciphertext) {
+        Envelope::from_bytes(ciphertext)?
+    } else {
+        Envelope::from_legacy_crypto(ciphertext).map_err(|_| CryptoError::DecryptionFailed)?
+    };
+    let decrypted = envelope.open_with_key(key)?;
     String::from_utf8(decrypted).map_err(|_| CryptoError::DecryptionFailed)
-}
\ No newline at end of file
+}
@@ -0,0 +1,350 @@
+//utils/envelope.rs
+// Versioned, self-describing container for every encrypted blob in this.me.
+//
+// Binary layout (version 1, integers big-endian):
+//
+//     magic      4  b".me\0"
+//     version    1
+//     aead       1  1 = ChaCha20-Poly1305, 2 = AES-256-GCM
+//     kdf        1  0 = raw key, 1 = Argon2id (m, t, p: u32 each), 2 = PBKDF2-SHA256 (iterations: u32)
+//     kdf params    as above
+//     salt       1 + n
+//     nonce      1 + n
+//     aad        2 + n  caller-supplied associated data
+//     ciphertext    rest of the blob (includes the AEAD tag)
+//
+// Everything before the ciphertext is authenticated as AEAD associated data,
+// so algorithms and parameters cannot be swapped without failing decryption.
+//
+// Two legacy layouts decode into version-0 envelopes (no header authentication):
+// - `utils::crypto`: nonce(12) || ciphertext, ChaCha20-Poly1305, raw key.
+// - `qrcode`: salt(16) || nonce(12) || ciphertext, AES-256-GCM, PBKDF2-SHA256 x 100_000.
+use aes_gcm::Aes256Gcm;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::{rngs::OsRng, RngCore};
+use crate::utils::crypto::CryptoError;
+use crate::utils::kdf::{Kdf, KdfParams};
+
+pub const MAGIC: [u8; 4] = *b".me\0";
+pub const ENVELOPE_VERSION: u8 = 1;
+/// Version reported by envelopes decoded from a legacy layout.
+pub const LEGACY_VERSION: u8 = 0;
+
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+const LEGACY_QR_ITERATIONS: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AeadId {
+    ChaCha20Poly1305 = 1,
+    Aes256Gcm = 2,
+}
+
+impl AeadId {
+    fn from_byte(b: u8) -> Result<Self, CryptoError> {
+        match b {
+            1 => Ok(AeadId::ChaCha20Poly1305),
+            2 => Ok(AeadId::Aes256Gcm),
+            other => Err(CryptoError::InvalidEnvelope(format!("unknown AEAD id {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub version: u8,
+    pub aead: AeadId,
+    /// `None` when the caller supplies the 32-byte key directly.
+    pub kdf: Option<Kdf>,
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub aad: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Encrypts under a key derived from `password` with a fresh random salt.
+    pub fn seal_with_password(plaintext: &[u8], password: &str, aead: AeadId, kdf: Kdf, aad: &[u8]) -> Result<Self, CryptoError> {
+        let params = KdfParams::new(kdf);
+        let key = params.derive(password)?;
+        Self::seal(plaintext, &key, aead, Some(kdf), params.salt, aad)
+    }
+
+    /// Encrypts under a caller-provided 32-byte key.
+    pub fn seal_with_key(plaintext: &[u8], key: &[u8], aead: AeadId, aad: &[u8]) -> Result<Self, CryptoError> {
+        Self::seal(plaintext, key, aead, None, Vec::new(), aad)
+    }
+
+    fn seal(plaintext: &[u8], key: &[u8], aead: AeadId, kdf: Option<Kdf>, salt: Vec<u8>, aad: &[u8]) -> Result<Self, CryptoError> {
+        let mut nonce = vec![0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let mut envelope = Envelope {
+            version: ENVELOPE_VERSION,
+            aead,
+            kdf,
+            salt,
+            nonce,
+            aad: aad.to_vec(),
+            ciphertext: Vec::new(),
+        };
+        let header = envelope.header_bytes()?;
+        envelope.ciphertext = aead_encrypt(aead, key, &envelope.nonce, plaintext, &header)?;
+        Ok(envelope)
+    }
+
+    /// Derives the key from `password` with the recorded KDF and decrypts.
+    pub fn open_with_password(&self, password: &str) -> Result<Vec<u8>, CryptoError> {
+        let kdf = self
+            .kdf
+            .ok_or_else(|| CryptoError::InvalidEnvelope("envelope was sealed with a raw key".into()))?;
+        let key = KdfParams { kdf, salt: self.salt.clone() }.derive(password)?;
+        self.open_with_key(&key)
+    }
+
+    /// Decrypts with a caller-provided 32-byte key.
+    pub fn open_with_key(&self, key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let header = if self.version == LEGACY_VERSION { Vec::new() } else { self.header_bytes()? };
+        aead_decrypt(self.aead, key, &self.nonce, &self.ciphertext, &header)
+    }
+
+    /// True if `bytes` starts with the envelope magic.
+    pub fn is_envelope(bytes: &[u8]) -> bool {
+        bytes.starts_with(&MAGIC)
+    }
+
+    fn header_bytes(&self) -> Result<Vec<u8>, CryptoError> {
+        let too_long = |what: &str| CryptoError::InvalidEnvelope(format!("{} too long", what));
+        let mut out = Vec::with_capacity(64 + self.aad.len());
+        out.extend_from_slice(&MAGIC);
+        out.push(self.version);
+        out.push(self.aead as u8);
+        match self.kdf {
+            None => out.push(0),
+            Some(Kdf::Argon2id { m_cost, t_cost, p_cost }) => {
+                out.push(1);
+                out.extend_from_slice(&m_cost.to_be_bytes());
+                out.extend_from_slice(&t_cost.to_be_bytes());
+                out.extend_from_slice(&p_cost.to_be_bytes());
+            }
+            Some(Kdf::Pbkdf2Sha256 { iterations }) => {
+                out.push(2);
+                out.extend_from_slice(&iterations.to_be_bytes());
+            }
+        }
+        out.push(u8::try_from(self.salt.len()).map_err(|_| too_long("salt"))?);
+        out.extend_from_slice(&self.salt);
+        out.push(u8::try_from(self.nonce.len()).map_err(|_| too_long("nonce"))?);
+        out.extend_from_slice(&self.nonce);
+        out.extend_from_slice(&u16::try_from(self.aad.len()).map_err(|_| too_long("aad"))?.to_be_bytes());
+        out.extend_from_slice(&self.aad);
+        Ok(out)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CryptoError> {
+        if self.version == LEGACY_VERSION {
+            return Err(CryptoError::InvalidEnvelope("legacy envelopes are read-only".into()));
+        }
+        let mut out = self.header_bytes()?;
+        out.extend_from_slice(&self.ciphertext);
+        Ok(out)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4)? != MAGIC {
+            return Err(CryptoError::InvalidEnvelope("bad magic".into()));
+        }
+        let version = r.u8()?;
+        if version != ENVELOPE_VERSION {
+            return Err(CryptoError::InvalidEnvelope(format!("unsupported version {}", version)));
+        }
+        let aead = AeadId::from_byte(r.u8()?)?;
+        let kdf = match r.u8()? {
+            0 => None,
+            1 => Some(Kdf::Argon2id { m_cost: r.u32()?, t_cost: r.u32()?, p_cost: r.u32()? }),
+            2 => Some(Kdf::Pbkdf2Sha256 { iterations: r.u32()? }),
+            other => return Err(CryptoError::InvalidEnvelope(format!("unknown KDF id {}", other))),
+        };
+        if let Some(kdf) = &kdf {
+            kdf.check_limits()?;
+        }
+        let salt_len = r.u8()? as usize;
+        let salt = r.take(salt_len)?.to_vec();
+        let nonce_len = r.u8()? as usize;
+        let nonce = r.take(nonce_len)?.to_vec();
+        let aad_len = u16::from_be_bytes([r.u8()?, r.u8()?]) as usize;
+        let aad = r.take(aad_len)?.to_vec();
+        let ciphertext = bytes[r.pos..].to_vec();
+        Ok(Envelope { version, aead, kdf, salt, nonce, aad, ciphertext })
+    }
+
+    pub fn to_base64(&self) -> Result<String, CryptoError> {
+        Ok(STANDARD.encode(self.to_bytes()?))
+    }
+
+    pub fn from_base64(b64: &str) -> Result<Self, CryptoError> {
+        let bytes = STANDARD
+            .decode(b64.trim())
+            .map_err(|e| CryptoError::InvalidEnvelope(format!("base64 decode error: {}", e)))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Legacy `utils::crypto` blob: nonce(12) || ciphertext under ChaCha20-Poly1305.
+    pub fn from_legacy_crypto(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < NONCE_LEN {
+            return Err(CryptoError::InvalidEnvelope("ciphertext too short".into()));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        Ok(Envelope {
+            version: LEGACY_VERSION,
+            aead: AeadId::ChaCha20Poly1305,
+            kdf: None,
+            salt: Vec::new(),
+            nonce: nonce.to_vec(),
+            aad: Vec::new(),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    /// Legacy `qrcode` blob: salt(16) || nonce(12) || ciphertext under AES-256-GCM + PBKDF2.
+    pub fn from_legacy_qr(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < SALT_LEN + NONCE_LEN + 1 {
+            return Err(CryptoError::InvalidEnvelope("ciphertext too short".into()));
+        }
+        let (salt, rest) = bytes.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        Ok(Envelope {
+            version: LEGACY_VERSION,
+            aead: AeadId::Aes256Gcm,
+            kdf: Some(Kdf::Pbkdf2Sha256 { iterations: LEGACY_QR_ITERATIONS }),
+            salt: salt.to_vec(),
+            nonce: nonce.to_vec(),
+            aad: Vec::new(),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CryptoError> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| CryptoError::InvalidEnvelope("truncated envelope".into()))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+    fn u8(&mut self) -> Result<u8, CryptoError> {
+        Ok(self.take(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, CryptoError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+fn aead_encrypt(aead: AeadId, key: &[u8], nonce: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if nonce.len() != NONCE_LEN {
+        return Err(CryptoError::InvalidEnvelope("nonce must be 12 bytes".into()));
+    }
+    let payload = Payload { msg: plaintext, aad };
+    match aead {
+        AeadId::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| CryptoError::InvalidKeyLength)?
+            .encrypt(nonce.into(), payload),
+        AeadId::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|_| CryptoError::InvalidKeyLength)?
+            .encrypt(nonce.into(), payload),
+    }
+    .map_err(|_| CryptoError::EncryptionFailed)
+}
+
+fn aead_decrypt(aead: AeadId, key: &[u8], nonce: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if nonce.len() != NONCE_LEN {
+        return Err(CryptoError::DecryptionFailed);
+    }
+    let payload = Payload { msg: ciphertext, aad };
+    match aead {
+        AeadId::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| CryptoError::InvalidKeyLength)?
+            .decrypt(nonce.into(), payload),
+        AeadId::Aes256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|_| CryptoError::InvalidKeyLength)?
+            .decrypt(nonce.into(), payload),
+    }
+    .map_err(|_| CryptoError::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHEAP: Kdf = Kdf::Argon2id { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+    #[test]
+    fn round_trips_through_bytes_for_each_aead() {
+        for aead in [AeadId::ChaCha20Poly1305, AeadId::Aes256Gcm] {
+            let env = Envelope::seal_with_password(b"hello", "pass", aead, CHEAP, b"ctx").unwrap();
+            let parsed = Envelope::from_base64(&env.to_base64().unwrap()).unwrap();
+            assert_eq!(parsed, env);
+            assert_eq!(parsed.open_with_password("pass").unwrap(), b"hello");
+            assert!(parsed.open_with_password("nope").is_err());
+        }
+    }
+
+    #[test]
+    fn header_is_authenticated() {
+        let env = Envelope::seal_with_key(b"hello", &[7u8; 32], AeadId::ChaCha20Poly1305, b"owner:a").unwrap();
+        let mut tampered = env.clone();
+        tampered.aad = b"owner:b".to_vec();
+        assert!(tampered.open_with_key(&[7u8; 32]).is_err());
+        assert_eq!(env.open_with_key(&[7u8; 32]).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn rejects_truncated_and_foreign_blobs() {
+        let bytes = Envelope::seal_with_key(b"x", &[1u8; 32], AeadId::Aes256Gcm, b"").unwrap().to_bytes().unwrap();
+        assert!(Envelope::from_bytes(&bytes[..8]).is_err());
+        assert!(Envelope::from_bytes(b"not an envelope").is_err());
+    }
+
+    #[test]
+    fn rejects_kdf_parameters_above_the_caps() {
+        let env = Envelope::seal_with_password(b"x", "pass", AeadId::ChaCha20Poly1305, CHEAP, b"").unwrap();
+        for kdf in [
+            Kdf::Argon2id { m_cost: u32::MAX, t_cost: 1, p_cost: 1 },
+            Kdf::Argon2id { m_cost: 64, t_cost: 17, p_cost: 1 },
+            Kdf::Argon2id { m_cost: 64, t_cost: 1, p_cost: 17 },
+            Kdf::Pbkdf2Sha256 { iterations: 10_000_001 },
+        ] {
+            let bytes = Envelope { kdf: Some(kdf), ..env.clone() }.to_bytes().unwrap();
+            assert!(matches!(Envelope::from_bytes(&bytes), Err(CryptoError::InvalidKdf(_))), "{:?}", kdf);
+        }
+        let at_cap = Kdf::Argon2id { m_cost: 1024 * 1024, t_cost: 16, p_cost: 16 };
+        let bytes = Envelope { kdf: Some(at_cap), ..env }.to_bytes().unwrap();
+        assert_eq!(Envelope::from_bytes(&bytes).unwrap().kdf, Some(at_cap));
+    }
+
+    #[test]
+    fn decodes_legacy_qr_layout() {
+        // salt || nonce || AES-256-GCM(PBKDF2-SHA256(passphrase, salt, 100_000))
+        let salt = [3u8; 16];
+        let nonce = [4u8; 12];
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(b"pass", &salt, 100_000, &mut key);
+        let ct = Aes256Gcm::new_from_slice(&key).unwrap().encrypt((&nonce).into(), b"seal".as_ref()).unwrap();
+        let blob = [&salt[..], &nonce[..], &ct[..]].concat();
+
+        let env = Envelope::from_legacy_qr(&blob).unwrap();
+        assert_eq!(env.version, LEGACY_VERSION);
+        assert_eq!(env.open_with_password("pass").unwrap(), b"seal");
+        assert!(env.to_bytes().is_err());
+    }
+}
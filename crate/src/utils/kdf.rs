@@ -0,0 +1,149 @@
+//utils/kdf.rs
+// Password-based key derivation for private keys at rest.
+//
+// `seal` writes a base64 `utils::envelope::Envelope` that records the KDF id,
+// its cost parameters and the per-identity salt next to the ciphertext, all in
+// the same `encrypted_private_key` column.
+//
+// Legacy values (plain base64 of nonce||ciphertext under SHA-256(username || password))
+// are detected by `is_legacy`; `core::Me::load` migrates those.
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use pbkdf2::pbkdf2_hmac;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use crate::utils::crypto::CryptoError;
+use crate::utils::envelope::{AeadId, Envelope};
+
+const SALT_LEN: usize = 16;
+
+/// Upper bounds for parameters read from stored blobs: a crafted envelope must
+/// not make `derive` allocate gigabytes or spin for minutes.
+pub const MAX_ARGON2_M_COST: u32 = 1024 * 1024; // 1 GiB in KiB
+pub const MAX_ARGON2_T_COST: u32 = 16;
+pub const MAX_ARGON2_P_COST: u32 = 16;
+pub const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+/// Cheap KDF for tests that do not measure the KDF cost.
+#[cfg(test)]
+pub const FAST: Kdf = Kdf::Pbkdf2Sha256 { iterations: 1_000 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    /// Memory cost in KiB, iterations and lanes.
+    Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
+    /// Fallback for platforms where Argon2's memory cost is not acceptable.
+    Pbkdf2Sha256 { iterations: u32 },
+}
+
+impl Default for Kdf {
+    /// OWASP baseline for Argon2id.
+    fn default() -> Self {
+        Kdf::Argon2id { m_cost: 19_456, t_cost: 2, p_cost: 1 }
+    }
+}
+
+impl Kdf {
+    pub fn pbkdf2() -> Self {
+        Kdf::Pbkdf2Sha256 { iterations: 600_000 }
+    }
+
+    /// Rejects parameters above the `MAX_*` caps.
+    pub fn check_limits(&self) -> Result<(), CryptoError> {
+        let over = match *self {
+            Kdf::Argon2id { m_cost, t_cost, p_cost } => {
+                m_cost > MAX_ARGON2_M_COST || t_cost > MAX_ARGON2_T_COST || p_cost > MAX_ARGON2_P_COST
+            }
+            Kdf::Pbkdf2Sha256 { iterations } => iterations > MAX_PBKDF2_ITERATIONS,
+        };
+        if over {
+            return Err(CryptoError::InvalidKdf(format!("parameters above the allowed limits: {:?}", self)));
+        }
+        Ok(())
+    }
+}
+
+/// A KDF together with the per-identity salt it runs with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfParams {
+    pub kdf: Kdf,
+    pub salt: Vec<u8>,
+}
+
+impl KdfParams {
+    /// Fresh parameters with a random salt.
+    pub fn new(kdf: Kdf) -> Self {
+        let mut salt = vec![0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self { kdf, salt }
+    }
+
+    /// Derives a 32-byte key from `password`.
+    pub fn derive(&self, password: &str) -> Result<[u8; 32], CryptoError> {
+        let mut key = [0u8; 32];
+        match self.kdf {
+            Kdf::Argon2id { m_cost, t_cost, p_cost } => {
+                let params = Params::new(m_cost, t_cost, p_cost, Some(key.len()))
+                    .map_err(|e| CryptoError::InvalidKdf(e.to_string()))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password.as_bytes(), &self.salt, &mut key)
+                    .map_err(|e| CryptoError::InvalidKdf(e.to_string()))?;
+            }
+            Kdf::Pbkdf2Sha256 { iterations } => {
+                if iterations == 0 {
+                    return Err(CryptoError::InvalidKdf("iterations must be > 0".into()));
+                }
+                pbkdf2_hmac::<Sha256>(password.as_bytes(), &self.salt, iterations, &mut key);
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Encrypts `plaintext` under a key derived from `password` and returns the sealed string.
+pub fn seal(password: &str, plaintext: &str, kdf: Kdf) -> Result<String, CryptoError> {
+    Envelope::seal_with_password(plaintext.as_bytes(), password, AeadId::ChaCha20Poly1305, kdf, &[])?.to_base64()
+}
+
+/// Decrypts a string produced by `seal`.
+pub fn open(password: &str, sealed: &str) -> Result<String, CryptoError> {
+    let plaintext = Envelope::from_base64(sealed)?.open_with_password(password)?;
+    String::from_utf8(plaintext).map_err(|_| CryptoError::DecryptionFailed)
+}
+
+/// True for values written before sealed keys existed.
+pub fn is_legacy(stored: &str) -> bool {
+    !STANDARD.decode(stored).is_ok_and(|bytes| Envelope::is_envelope(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHEAP: Kdf = Kdf::Argon2id { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+    #[test]
+    fn seal_round_trips_and_rejects_wrong_password() {
+        for kdf in [CHEAP, Kdf::Pbkdf2Sha256 { iterations: 10 }] {
+            let sealed = seal("123456", "secret", kdf).unwrap();
+            assert!(!is_legacy(&sealed));
+            assert_eq!(Envelope::from_base64(&sealed).unwrap().kdf, Some(kdf));
+            assert_eq!(open("123456", &sealed).unwrap(), "secret");
+            assert!(open("654321", &sealed).is_err());
+        }
+    }
+
+    #[test]
+    fn salts_are_per_seal() {
+        let a = seal("123456", "secret", CHEAP).unwrap();
+        let b = seal("123456", "secret", CHEAP).unwrap();
+        assert_ne!(Envelope::from_base64(&a).unwrap().salt, Envelope::from_base64(&b).unwrap().salt);
+    }
+
+    #[test]
+    fn envelopes_are_not_legacy() {
+        assert!(!is_legacy(&seal("123456", "secret", CHEAP).unwrap()));
+        assert!(is_legacy("bm9uY2U="));
+    }
+}
@@ -1,16 +1,68 @@
 // src/utils/me_error.rs
+// Error único de la API (core::Me, MeStore y sus backends, crypto, Verbs, qrcode, host).
+// Las variantes separan lo que el llamador debe tratar distinto: identidad inexistente,
+// password incorrecto (≠ datos corruptos), username duplicado, verbo no soportado,
+// fallas del backend y de crypto. `status_code` las lleva a HTTP.
 use thiserror::Error;
 #[cfg(feature = "sqlite")]
 use rusqlite;
 use std::io;
 use serde_json;
+use crate::core::ontology::OntologyError;
+use crate::core::query::QueryError;
+use crate::core::verbs::ParseVerbError;
+use crate::utils::crypto::CryptoError;
 
 #[derive(Debug, Error)]
 pub enum MeError {
+    #[error("Identity '{0}' not found")]
+    IdentityNotFound(String),
+
+    #[error("No key for authority '{0}'")]
+    KeyNotFound(String),
+
+    #[error("Identity '{0}' already exists")]
+    AlreadyExists(String),
+
+    #[error("Key for authority '{0}' already exists")]
+    KeyExists(String),
+
+    /// Las autoridades guardadas ya no son las que se leyeron (otra escritura en medio); reintentar.
+    #[error("Keys of '{0}' changed while re-wrapping them")]
+    KeysChanged(String),
+
+    /// El AEAD no autenticó: password incorrecto (o un blob alterado, que el AEAD no distingue).
+    #[error("Wrong password")]
+    WrongPassword,
+
+    /// Lo guardado no se puede leer aunque el password sea correcto (formato, base64, llave inválida).
+    #[error("Corrupt data: {0}")]
+    CorruptData(String),
+
+    #[error("Unsupported verb '{0}'")]
+    UnsupportedVerb(String),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error(transparent)]
+    Query(#[from] QueryError),
+
+    #[error(transparent)]
+    Ontology(OntologyError),
+
     #[cfg(feature = "sqlite")]
     #[error("Database error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[cfg(feature = "pg")]
+    #[error("Postgres error: {0}")]
+    Postgres(#[from] sqlx::Error),
+
+    /// Falla del backend que no viene de un driver (p. ej. un mutex envenenado).
+    #[error("Storage error: {0}")]
+    Storage(String),
+
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
@@ -21,5 +73,71 @@ pub enum MeError {
     Serialization(#[from] serde_json::Error),
 
     #[error("Crypto error: {0}")]
-    Crypto(String),
-}
\ No newline at end of file
+    Crypto(#[from] CryptoError),
+}
+
+impl MeError {
+    /// Código HTTP para la capa de API.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            MeError::IdentityNotFound(_) | MeError::KeyNotFound(_) => 404,
+            MeError::AlreadyExists(_) | MeError::KeyExists(_) | MeError::KeysChanged(_) => 409,
+            MeError::WrongPassword | MeError::InvalidSignature(_) => 401,
+            MeError::UnsupportedVerb(_) | MeError::Query(_) | MeError::Ontology(_) | MeError::Validation(_) => 400,
+            _ => 500,
+        }
+    }
+
+    /// Descifrado con password: solo `DecryptionFailed` significa password incorrecto;
+    /// el resto (KDF o envelope ilegibles) es dato corrupto.
+    pub fn from_unseal(e: CryptoError) -> Self {
+        match e {
+            CryptoError::DecryptionFailed => MeError::WrongPassword,
+            other => MeError::CorruptData(other.to_string()),
+        }
+    }
+}
+
+impl From<OntologyError> for MeError {
+    fn from(e: OntologyError) -> Self {
+        match e {
+            OntologyError::UnknownSecondary(name) => MeError::UnsupportedVerb(name),
+            other => MeError::Ontology(other),
+        }
+    }
+}
+
+impl From<ParseVerbError> for MeError {
+    fn from(e: ParseVerbError) -> Self {
+        MeError::UnsupportedVerb(e.0)
+    }
+}
+
+impl From<base64::DecodeError> for MeError {
+    fn from(e: base64::DecodeError) -> Self {
+        MeError::CorruptData(format!("invalid base64: {}", e))
+    }
+}
+
+impl From<ed25519_dalek::SignatureError> for MeError {
+    fn from(e: ed25519_dalek::SignatureError) -> Self {
+        MeError::InvalidSignature(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_to_http_status_codes() {
+        assert_eq!(MeError::IdentityNotFound("a".into()).status_code(), 404);
+        assert_eq!(MeError::AlreadyExists("a".into()).status_code(), 409);
+        assert_eq!(MeError::WrongPassword.status_code(), 401);
+        assert_eq!(MeError::from_unseal(CryptoError::DecryptionFailed).status_code(), 401);
+        assert_eq!(MeError::from_unseal(CryptoError::InvalidKdf("x".into())).status_code(), 500);
+        assert_eq!(MeError::from("fly".parse::<crate::core::Verb>().unwrap_err()).status_code(), 400);
+        assert!(matches!(MeError::from(OntologyError::UnknownSecondary("fly".into())), MeError::UnsupportedVerb(_)));
+        assert_eq!(MeError::Storage("poisoned".into()).status_code(), 500);
+    }
+}
@@ -2,4 +2,6 @@
 // by suiGn
 pub mod validate_input;
 pub mod me_error;
-pub mod crypto;
\ No newline at end of file
+pub mod crypto;
+pub mod kdf;
+pub mod envelope;
\ No newline at end of file
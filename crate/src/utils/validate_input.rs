@@ -2,10 +2,8 @@
 use std::io;
 
 pub fn validate_username(username: &str) -> io::Result<()> {
-    println!("🔍 Validating username: '{}' (chars: {}, bytes: {})", username, username.chars().count(), username.len());
-
     let username_len = username.len();
-    if username_len < 5 || username_len > 21 {
+    if !(5..=21).contains(&username_len) {
         return Err(io::Error::new(io::ErrorKind::InvalidInput, "❌ Username must be 5-21 characters long."));
     }
 